@@ -1,21 +1,38 @@
+use std::collections::BTreeMap;
+
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Empty, Env, MessageInfo, Reply, StdResult, WasmMsg};
+use cosmwasm_std::{
+    from_slice, to_binary, Binary, Empty, Env, MessageInfo, Order, Reply, StdResult, Uint128,
+    WasmMsg,
+};
 use cw2::set_contract_version;
-use cw_utils::parse_reply_instantiate_data;
+use cw3::Status;
+use cw_utils::{parse_reply_instantiate_data, Duration};
+use serde::Deserialize;
 
 use crate::error::ContractError;
-use crate::helpers::get_config;
+use crate::helpers::{get_config, namespace_action};
 use crate::msg::{ExecuteMsg, GovToken, InstantiateMsg, MigrateMsg, QueryMsg, VoteMsg};
-use crate::state::{Config, CONFIG, GOV_TOKEN, PROPOSAL_COUNT, STAKING_CONTRACT, TREASURY_TOKENS};
-use crate::{Deps, DepsMut, Response, SubMsg};
+use crate::state::{
+    Config, ProposalDepositSummary, CALLING_PROPOSAL_ID, CONFIG, DAO_CREATED_AT, DEPOSITS,
+    DEPOSIT_SUMMARIES, ESCROWED_DEPOSITS, GOV_TOKEN, IDX_PROPS_BY_DEPOSIT, IDX_PROPS_BY_STATUS,
+    NOTIFIED_EXPIRED, PENDING_MULTISIG_LABEL, PROPOSALS, PROPOSAL_COUNT, STAKING_CONTRACT,
+    SUB_DAOS, SUB_MULTISIGS, TREASURY_TOKENS, UNNOTIFIED_OPEN,
+};
+use crate::{Deps, DepsMut, Response, SubMsg, DEFAULT_LIMIT, MAX_LIMIT};
 
 // Version info for migration info
 pub const CONTRACT_NAME: &str = "crates.io:ion-dao";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 // Reply IDs
-const INSTANTIATE_STAKING_CONTRACT_REPLY_ID: u64 = 0;
+pub(crate) const INSTANTIATE_STAKING_CONTRACT_REPLY_ID: u64 = 0;
+pub(crate) const INSTANTIATE_MULTISIG_REPLY_ID: u64 = 1;
+pub(crate) const NOTIFIER_REPLY_ID: u64 = 2;
+pub(crate) const PROPOSAL_MSGS_REPLY_ID: u64 = 3;
+pub(crate) const INSTANTIATE_SUB_DAO_REPLY_ID: u64 = 4;
+pub(crate) const CONFISCATION_REPLY_ID: u64 = 5;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -36,11 +53,39 @@ pub fn instantiate(
         deposit_period: msg.deposit_period,
         proposal_deposit: msg.proposal_deposit_amount,
         proposal_min_deposit: msg.proposal_deposit_min_amount,
+        high_participation_bonus: msg.high_participation_bonus,
+        auto_reap: msg.auto_reap,
+        require_valid_link: msg.require_valid_link,
+        allow_deposit_top_up: msg.allow_deposit_top_up,
+        force_close_grace_period: msg.force_close_grace_period.unwrap_or(msg.voting_period),
+        max_query_limit: msg.max_query_limit.unwrap_or(MAX_LIMIT),
+        default_query_limit: msg.default_query_limit.unwrap_or(DEFAULT_LIMIT),
+        execute_permission: msg.execute_permission,
+        execution_delay: msg.execution_delay,
+        max_single_deposit: msg.max_single_deposit,
+        min_distinct_depositors: msg.min_distinct_depositors,
+        auto_scale_threshold: msg.auto_scale_threshold,
+        total_token_supply: msg.total_token_supply,
+        threshold_scaling_coefficient: msg.threshold_scaling_coefficient,
+        bootstrap: msg.bootstrap,
+        notifiers: msg.notifiers,
+        gov_token_decimals: msg.gov_token_decimals,
+        event_namespace: msg.event_namespace,
+        field_thresholds: msg.field_thresholds,
+        auto_register_cw20: msg.auto_register_cw20,
+        execution_window: msg.execution_window,
+        recheck_quorum_on_execute: msg.recheck_quorum_on_execute,
+        voting_stake_age: msg.voting_stake_age,
+        reserves: msg.reserves,
+        confiscation_policy: msg.confiscation_policy,
+        record_tally_history: msg.record_tally_history,
+        max_treasury_tokens: msg.max_treasury_tokens,
     };
-    cfg.validate()?;
+    cfg.validate(deps.api)?;
 
     CONFIG.save(deps.storage, &cfg)?;
     PROPOSAL_COUNT.save(deps.storage, &0)?;
+    DAO_CREATED_AT.save(deps.storage, &env.block.clone().into())?;
 
     match msg.gov_token {
         GovToken::Create {
@@ -66,6 +111,8 @@ pub fn instantiate(
                         admin: Some(env.contract.address),
                         denom,
                         unstaking_duration,
+                        basket_denoms: vec![],
+                        bonus_denom: None,
                     })?,
                 },
                 INSTANTIATE_STAKING_CONTRACT_REPLY_ID,
@@ -77,6 +124,16 @@ pub fn instantiate(
             STAKING_CONTRACT.save(deps.storage, &addr)?;
 
             let staking_config = get_config(deps.as_ref())?;
+            // Without admin rights the DAO can't slash or reconfigure the staking contract.
+            // an admin-less contract is still accepted, it simply stays as configured
+            if let Some(admin) = staking_config.admin {
+                if admin != env.contract.address {
+                    return Err(ContractError::ForeignStakingAdmin {
+                        admin: admin.into_string(),
+                    });
+                }
+            }
+
             // Add native token to map of TREASURY TOKENS
             TREASURY_TOKENS.save(
                 deps.storage,
@@ -102,24 +159,84 @@ pub fn execute(
     use crate::execute;
     use crate::msg::ExecuteMsg::*;
 
-    match msg {
+    // taken before the handler runs, `UpdateConfig` only namespaces the events after it
+    let namespace = CONFIG.load(deps.storage)?.event_namespace;
+
+    let resp = match msg {
         Propose(propose_msg) => execute::propose(deps, env, info, propose_msg),
+        ProposeSubDao(propose_msg) => execute::propose_sub_dao(deps, env, info, *propose_msg),
         Deposit { proposal_id } => execute::deposit(deps, env, info, proposal_id),
         ExecuteMsg::ClaimDeposit { proposal_id } => {
             execute::claim_deposit(deps, env, info, proposal_id)
         }
-        Vote(VoteMsg { proposal_id, vote }) => execute::vote(deps, env, info, proposal_id, vote),
+        ExecuteMsg::Cosponsor { proposal_id } => execute::cosponsor(deps, env, info, proposal_id),
+        ExecuteMsg::WithdrawDeposit { proposal_id } => {
+            execute::withdraw_deposit(deps, env, info, proposal_id)
+        }
+        Vote(VoteMsg {
+            proposal_id,
+            vote,
+            rationale,
+        }) => execute::vote(deps, env, info, proposal_id, vote, rationale),
         Execute { proposal_id } => execute::execute(deps, env, info, proposal_id),
         Close { proposal_id } => execute::close(deps, env, info, proposal_id),
+        ForceClose { proposal_id } => execute::force_close(deps, env, info, proposal_id),
+        VetoExecution { proposal_id } => execute::veto_execution(deps, env, info, proposal_id),
+        ExpireExecution { proposal_id } => execute::expire_execution(deps, env, info, proposal_id),
+        RefreshStatus { proposal_ids } => execute::refresh_status(deps, env, proposal_ids),
+        ReconcileIndexes { start_after, limit } => {
+            execute::reconcile_indexes(deps, env, info, start_after, limit)
+        }
+        SweepStaleIndex { index, limit } => {
+            execute::sweep_stale_index(deps, env, info, index, limit)
+        }
+        InstantiateMultisig {
+            code_id,
+            label,
+            members,
+            required_weight,
+            max_voting_period,
+        } => execute::instantiate_multisig(
+            deps,
+            env,
+            info,
+            code_id,
+            label,
+            members,
+            required_weight,
+            max_voting_period,
+        ),
+        InstantiateSubDao {
+            code_id,
+            label,
+            msg,
+        } => execute::instantiate_sub_dao(deps, env, info, code_id, label, msg),
         PauseDAO { expiration } => execute::pause_dao(deps, env, info, expiration),
-        UpdateConfig(config) => execute::update_config(deps, env, info, config),
+        UpdateConfig(config) => execute::update_config(deps, env, info, *config),
+        UpdateDepositParams {
+            proposal_deposit,
+            proposal_min_deposit,
+        } => {
+            execute::update_deposit_params(deps, env, info, proposal_deposit, proposal_min_deposit)
+        }
+        Receive(wrapper) => execute::receive_cw20(deps, env, info, wrapper),
         UpdateTokenList { to_add, to_remove } => {
             execute::update_token_list(deps, env, info, to_add, to_remove)
         }
+        UpdateCategories { to_add, to_remove } => {
+            execute::update_categories(deps, env, info, to_add, to_remove)
+        }
         UpdateStakingContract {
             new_staking_contract,
         } => execute::update_staking_contract(deps, env, info, new_staking_contract),
-    }
+        UpgradeContract {
+            contract,
+            new_code_id,
+            migrate_msg,
+        } => execute::upgrade_contract(deps, env, info, contract, new_code_id, migrate_msg),
+    }?;
+
+    Ok(namespace_action(resp, namespace.as_deref()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -137,12 +254,29 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         } => to_binary(&query::token_balances(deps, env, start, limit, order)?),
 
         Proposal { proposal_id } => to_binary(&query::proposal(deps, env, proposal_id)?),
+        ProposalMaybe { proposal_id } => to_binary(&query::proposal_maybe(deps, env, proposal_id)?),
         Proposals {
             query,
             start,
             limit,
             order,
         } => to_binary(&query::proposals(deps, env, query, start, limit, order)?),
+        AllProposalsStatusMap {
+            start,
+            limit,
+            order,
+        } => to_binary(&query::all_proposals_status_map(
+            deps, env, start, limit, order,
+        )?),
+        IndexDrift { limit } => to_binary(&query::index_drift(deps, env, limit)?),
+        Audit { start_after, limit } => to_binary(&query::audit(deps, start_after, limit)?),
+        ProposalsUpdatedAfter {
+            height,
+            cursor,
+            limit,
+        } => to_binary(&query::proposals_updated_after(
+            deps, env, height, cursor, limit,
+        )?),
         ProposalCount {} => to_binary(&query::proposal_count(deps)?),
 
         Vote { proposal_id, voter } => to_binary(&query::vote(deps, proposal_id, voter)?),
@@ -152,6 +286,9 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             limit,
             order,
         } => to_binary(&query::votes(deps, proposal_id, start, limit, order)?),
+        ProposalVotesBreakdown { proposal_id, top } => {
+            to_binary(&query::votes_breakdown(deps, proposal_id, top)?)
+        }
 
         Deposit {
             proposal_id,
@@ -162,6 +299,76 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             limit,
             order,
         } => to_binary(&query::deposits(deps, query, limit, order)?),
+        TopDepositors { proposal_id, limit } => {
+            to_binary(&query::top_depositors(deps, proposal_id, limit)?)
+        }
+        EligibilityHeight { proposal_id } => {
+            to_binary(&query::eligibility_height(deps, proposal_id)?)
+        }
+        ProposalParticipationByHeight {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_binary(&query::proposal_participation_by_height(
+            deps,
+            proposal_id,
+            start_after,
+            limit,
+        )?),
+        ProposalDepositSummary { proposal_id } => {
+            to_binary(&query::proposal_deposit_summary(deps, proposal_id)?)
+        }
+        ClaimableDeposits { depositor } => to_binary(&query::claimable_deposits(deps, depositor)?),
+
+        DepositShortfall { proposal_id } => {
+            to_binary(&query::deposit_shortfall(deps, env, proposal_id)?)
+        }
+
+        SelfStake {} => to_binary(&query::self_stake(deps, env)?),
+
+        GovernanceStats {} => to_binary(&query::governance_stats(deps)?),
+        DaoStats {} => to_binary(&query::dao_stats(deps)?),
+        ConfiscatedTotals {} => to_binary(&query::confiscated_totals(deps)?),
+
+        ProposalStatusAt {
+            proposal_id,
+            height,
+        } => to_binary(&query::proposal_status_at(deps, env, proposal_id, height)?),
+
+        ProposalTimeline { proposal_id } => {
+            to_binary(&query::proposal_timeline(deps, env, proposal_id)?)
+        }
+
+        VotingPowers {
+            proposal_id,
+            addresses,
+        } => to_binary(&query::voting_powers(deps, proposal_id, addresses)?),
+
+        ListMultisigs { start_after, limit } => {
+            to_binary(&query::list_multisigs(deps, start_after, limit)?)
+        }
+        Cosponsors {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_binary(&query::cosponsors(deps, proposal_id, start_after, limit)?),
+        SubDaos { start_after, limit } => to_binary(&query::sub_daos(deps, start_after, limit)?),
+
+        ExpirationStatus { expiration } => to_binary(&query::expiration_status(env, expiration)),
+
+        Version {} => to_binary(&query::version(deps)?),
+
+        ConfigHistory { start_after, limit } => {
+            to_binary(&query::config_history(deps, start_after, limit)?)
+        }
+
+        SimulateOsmosisExecution { proposal_id } => {
+            to_binary(&query::simulate_osmosis_execution(deps, env, proposal_id)?)
+        }
+
+        SimulateExecution { proposal_id } => {
+            to_binary(&query::simulate_execution(deps, env, proposal_id)?)
+        }
     }
 }
 
@@ -169,26 +376,146 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
         INSTANTIATE_STAKING_CONTRACT_REPLY_ID => {
-            let res = parse_reply_instantiate_data(msg);
-            match res {
-                Ok(res) => {
-                    // Validate contract address
-                    let staking_contract_addr = deps.api.addr_validate(&res.contract_address)?;
+            let res = parse_reply_instantiate_data(msg).map_err(|err| {
+                ContractError::InstantiateStakingError {
+                    reason: err.to_string(),
+                }
+            })?;
+            // Validate contract address
+            let staking_contract_addr = deps.api.addr_validate(&res.contract_address)?;
 
-                    // Save gov token
-                    STAKING_CONTRACT.save(deps.storage, &staking_contract_addr)?;
+            // Save gov token
+            STAKING_CONTRACT.save(deps.storage, &staking_contract_addr)?;
 
-                    Ok(Response::new())
-                }
-                Err(_) => Err(ContractError::InstantiateGovTokenError {}),
-            }
+            Ok(Response::new())
+        }
+        INSTANTIATE_MULTISIG_REPLY_ID => {
+            let res = parse_reply_instantiate_data(msg)
+                .map_err(|_| ContractError::InstantiateMultisigError {})?;
+            let multisig_addr = deps.api.addr_validate(&res.contract_address)?;
+
+            let label = PENDING_MULTISIG_LABEL.load(deps.storage)?;
+            PENDING_MULTISIG_LABEL.remove(deps.storage);
+            SUB_MULTISIGS.save(deps.storage, label.clone(), &multisig_addr)?;
+
+            Ok(Response::new()
+                .add_attribute("multisig_label", label)
+                .add_attribute("multisig_address", multisig_addr))
+        }
+        INSTANTIATE_SUB_DAO_REPLY_ID => {
+            let res = parse_reply_instantiate_data(msg)
+                .map_err(|_| ContractError::InstantiateSubDaoError {})?;
+            let sub_dao_addr = deps.api.addr_validate(&res.contract_address)?;
+            SUB_DAOS.save(deps.storage, &sub_dao_addr, &Empty {})?;
+
+            Ok(Response::new().add_attribute("sub_dao_address", sub_dao_addr))
+        }
+        // only errors come back, a broken notifier must not block voting
+        NOTIFIER_REPLY_ID => Ok(Response::new().add_attribute("notifier_failed", "true")),
+        // only errors come back as well, the refused deposit simply stays in the treasury
+        CONFISCATION_REPLY_ID => {
+            Ok(Response::new().add_attribute("confiscation_reward_failed", "true"))
+        }
+        // the last message of an executed proposal ran, its threshold no longer vouches for
+        // what the DAO is called with
+        PROPOSAL_MSGS_REPLY_ID => {
+            CALLING_PROPOSAL_ID.remove(deps.storage);
+            Ok(Response::new())
         }
         _ => Err(ContractError::UnknownReplyId { id: msg.id }),
     }
 }
 
+/// The part of a stored `Config` telling whether it predates `force_close_grace_period`
+#[derive(Deserialize)]
+struct StoredGracePeriod {
+    force_close_grace_period: Option<Duration>,
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    // No state migrations performed, just returned a Response
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // configs stored before `force_close_grace_period` get the `instantiate` default
+    if let Some(stored) = deps.storage.get(CONFIG.as_slice()) {
+        if from_slice::<StoredGracePeriod>(&stored)?
+            .force_close_grace_period
+            .is_none()
+        {
+            CONFIG.update(deps.storage, |mut cfg| -> StdResult<_> {
+                cfg.force_close_grace_period = cfg.voting_period;
+                Ok(cfg)
+            })?;
+        }
+    }
+
+    // open proposals stored before `UNNOTIFIED_OPEN` was kept, minus the ones already announced
+    let unnotified = IDX_PROPS_BY_STATUS
+        .prefix(Status::Open as u8)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .filter(|id| !matches!(id, Ok(id) if NOTIFIED_EXPIRED.has(deps.storage, *id)))
+        .collect::<StdResult<Vec<_>>>()?;
+    for prop_id in unnotified {
+        UNNOTIFIED_OPEN.save(deps.storage, prop_id, &Empty {})?;
+    }
+
+    // a single pass over the deposits, the totals below are rebuilt from scratch so running the
+    // migration again is harmless
+    let gov_token = GOV_TOKEN.load(deps.storage)?;
+    let mut untracked = vec![];
+    let mut summaries = BTreeMap::<u64, ProposalDepositSummary>::new();
+    let mut escrowed = BTreeMap::<String, Uint128>::new();
+    // deposits come ordered by proposal, so each proposal is loaded once
+    let mut confiscated: Option<(u64, bool)> = None;
+    for item in DEPOSITS.range(deps.storage, None, None, Order::Ascending) {
+        let ((prop_id, depositor), mut deposit) = item?;
+        // deposits stored before denoms were tracked were all paid in the gov token
+        if deposit.denom.is_empty() {
+            deposit.denom = gov_token.clone();
+            untracked.push(((prop_id, depositor), deposit.clone()));
+        }
+
+        let summary = summaries.entry(prop_id).or_default();
+        summary.total_amount += deposit.amount;
+        summary.depositor_count += 1;
+        summary.claimed_count += deposit.claimed as u64;
+
+        // deposits still owed to their depositors: unclaimed, on a proposal that wasn't confiscated
+        let is_confiscated = match confiscated {
+            Some((id, is_confiscated)) if id == prop_id => is_confiscated,
+            _ => {
+                let prop = PROPOSALS.load(deps.storage, prop_id)?;
+                let is_confiscated = !prop.deposit_claimable && prop.status == Status::Rejected;
+                confiscated = Some((prop_id, is_confiscated));
+                is_confiscated
+            }
+        };
+        if !deposit.claimed && !is_confiscated {
+            *escrowed.entry(deposit.denom).or_default() += deposit.amount;
+        }
+    }
+    for (key, deposit) in untracked {
+        DEPOSITS.save(deps.storage, key, &deposit)?;
+    }
+    for (prop_id, summary) in summaries {
+        DEPOSIT_SUMMARIES.save(deps.storage, prop_id, &summary)?;
+    }
+    for (denom, amount) in escrowed {
+        ESCROWED_DEPOSITS.save(deps.storage, &denom, &amount)?;
+    }
+
+    // same for the deposit index, stale entries would otherwise keep ranking old totals
+    let stale = IDX_PROPS_BY_DEPOSIT
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for key in stale {
+        IDX_PROPS_BY_DEPOSIT.remove(deps.storage, key);
+    }
+    let deposits = PROPOSALS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(prop_id, prop)| (prop.total_deposit.u128(), prop_id)))
+        .collect::<StdResult<Vec<_>>>()?;
+    for key in deposits {
+        IDX_PROPS_BY_DEPOSIT.save(deps.storage, key, &Empty {})?;
+    }
+
     Ok(Response::default())
 }
@@ -1,5 +1,5 @@
-use cosmwasm_std::StdError;
-use cw_utils::PaymentError;
+use cosmwasm_std::{Decimal, StdError, Uint128};
+use cw_utils::{Expiration, PaymentError};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -10,6 +10,12 @@ pub enum ContractError {
     #[error("Failed to instantiate governance token")]
     InstantiateGovTokenError {},
 
+    #[error("Failed to instantiate staking contract: {reason}")]
+    InstantiateStakingError { reason: String },
+
+    #[error("Staking contract is administered by '{admin}', the DAO could not manage it")]
+    ForeignStakingAdmin { admin: String },
+
     #[error("Initial governance token balances must not be empty")]
     InitialBalancesError {},
 
@@ -19,6 +25,27 @@ pub enum ContractError {
     #[error("Not possible to reach required (passing) threshold")]
     UnreachableThreshold {},
 
+    #[error("Threshold scaling needs a non-zero total token supply")]
+    MissingTokenSupply {},
+
+    #[error("Multisig label '{label}' is already in use")]
+    MultisigLabelTaken { label: String },
+
+    #[error("Failed to instantiate multisig")]
+    InstantiateMultisigError {},
+
+    #[error("Error occurred while instantiating the sub-DAO")]
+    InstantiateSubDaoError {},
+
+    #[error(
+        "Proposal would leave {remaining} {denom} in the treasury, below the {reserve} reserve"
+    )]
+    ReserveViolation {
+        denom: String,
+        reserve: Uint128,
+        remaining: Uint128,
+    },
+
     #[error("Invalid voting / deposit period")]
     InvalidPeriod {},
 
@@ -52,6 +79,9 @@ pub enum ContractError {
     #[error("Total staked amount is too low")]
     LackOfStakes {},
 
+    #[error("Proposal no longer passes against the total stake at its voting start")]
+    QuorumNotMet {},
+
     #[error("Cannot deposit to non-pended proposals")]
     WrongDepositStatus {},
 
@@ -67,12 +97,57 @@ pub enum ContractError {
     #[error("Deposit already claimed")]
     DepositAlreadyClaimed {},
 
+    #[error("Minimum proposal deposit cannot exceed the proposal deposit")]
+    InvalidDepositParams {},
+
+    #[error("No deposit to withdraw")]
+    NoDeposit {},
+
     #[error("Got a submessage reply with unknown id: {id}")]
     UnknownReplyId { id: u64 },
 
     #[error("Request size ({size}) is above limit of ({max})")]
     OversizedRequest { size: u64, max: u64 },
 
-    #[error("DAO is paused")]
-    Paused {},
+    #[error("Default query limit must be non-zero and not above the max query limit")]
+    InvalidQueryLimit {},
+
+    #[error("Event namespace must be non-empty and cannot contain '/'")]
+    InvalidEventNamespace {},
+
+    #[error("Bootstrap quorum cannot exceed the configured quorum")]
+    InvalidBootstrapQuorum {},
+
+    #[error("Changing {field} needs a proposal threshold of at least {required_threshold} and a quorum of at least {required_quorum}, got {actual_threshold} and {actual_quorum}")]
+    InsufficientThresholdForField {
+        field: String,
+        required_threshold: Decimal,
+        required_quorum: Decimal,
+        actual_threshold: Decimal,
+        actual_quorum: Decimal,
+    },
+
+    #[error("Invalid proposal content: {reason}")]
+    InvalidProposalContent { reason: String },
+
+    #[error("DAO is paused until {expiration}")]
+    PausedUntil { expiration: Expiration },
+
+    #[error("No execution delay configured")]
+    NoExecutionDelay {},
+
+    #[error("Execution delay has elapsed")]
+    ExecutionDelayElapsed {},
+
+    #[error("No execution window configured")]
+    NoExecutionWindow {},
+
+    #[error("Execution window has elapsed")]
+    ExecutionWindowElapsed {},
+
+    #[error("Vote rationale is {length} bytes long, the limit is {max}")]
+    RationaleTooLong { length: u64, max: u64 },
+
+    #[error("{address} cannot be registered as a cw20 treasury token")]
+    InvalidTreasuryToken { address: String },
 }
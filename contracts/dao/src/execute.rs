@@ -1,28 +1,52 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::Display;
 use std::ops::Add;
 
 use cosmwasm_std::{
-    coins, Addr, BankMsg, BlockInfo, Empty, Env, MessageInfo, StdError, StdResult, Storage, Uint128,
+    coins, to_binary, Addr, BankMsg, Binary, BlockInfo, Decimal, Empty, Env, GovMsg, MessageInfo,
+    Order, ReplyOn, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
-use cw20::Denom;
+use cw20::{Cw20QueryMsg, Cw20ReceiveMsg, Denom, TokenInfoResponse};
 use cw3::{Status, Vote};
-use cw_utils::{may_pay, Expiration};
+use cw_storage_plus::{Bound, KeyDeserialize, Map, PrimaryKey};
+use cw_utils::{may_pay, Duration, Expiration};
 
-use crate::helpers::{duration_to_expiry, get_total_staked_supply, get_voting_power_at_height};
-use crate::msg::ProposeMsg;
+use crate::contract::{
+    CONFISCATION_REPLY_ID, INSTANTIATE_MULTISIG_REPLY_ID, INSTANTIATE_SUB_DAO_REPLY_ID,
+    NOTIFIER_REPLY_ID, PROPOSAL_MSGS_REPLY_ID,
+};
+use crate::helpers::{
+    check_reserves, duration_to_expiry, get_and_check_limit, get_staked_balance,
+    get_total_staked_at_height, get_total_staked_supply, get_voting_power_at_height, is_valid_link,
+    participation_bonus, passes_rechecked_quorum, ParticipationBonus,
+};
+use crate::msg::{
+    Cw3FixedMultisigInstantiateMsg, Cw3Voter, ExecuteMsg, IndexSweep, NotifierMsg, ProposeMsg,
+    ProposeSubDaoMsg,
+};
+use crate::proposal::BlockTime;
 use crate::state::{
-    next_id, Ballot, Config, Proposal, Votes, BALLOTS, CONFIG, DAO_PAUSED, DEPOSITS, GOV_TOKEN,
-    IDX_DEPOSITS_BY_DEPOSITOR, IDX_PROPS_BY_PROPOSER, IDX_PROPS_BY_STATUS, PROPOSALS,
-    STAKING_CONTRACT, TREASURY_TOKENS,
+    next_id, Ballot, Config, ConfiscationPolicy, Proposal, Threshold, Votes, BALLOTS,
+    CALLING_PROPOSAL_ID, CATEGORIES, CONFIG, CONFIG_HISTORY, CONFIG_HISTORY_LIMIT, CONFIG_VERSION,
+    CONFISCATED_TOTAL, COSPONSORS, DAO_PAUSED, DAO_TOTAL_PROPOSALS_EXECUTED,
+    DAO_TOTAL_PROPOSALS_REJECTED, DEPOSITS, DEPOSIT_SUMMARIES, ESCROWED_DEPOSITS, GOV_STATS_CACHE,
+    GOV_TOKEN, IDX_DEPOSITS_BY_DEPOSITOR, IDX_PROPS_BY_CATEGORY, IDX_PROPS_BY_DEPOSIT,
+    IDX_PROPS_BY_PROPOSER, IDX_PROPS_BY_STATUS, IDX_PROPS_BY_UPDATE_HEIGHT, NOTIFIED_EXPIRED,
+    PENDING_MULTISIG_LABEL, PROPOSALS, STAKING_CONTRACT, SUB_MULTISIGS, TALLY_HISTORY,
+    TREASURY_TOKENS, UNNOTIFIED_OPEN,
 };
 use crate::ContractError;
 
-use super::{DepsMut, Response, MAX_LIMIT};
+use super::{
+    CosmosMsg, DepsMut, Response, SubMsg, AUTO_REAP_LIMIT, MAX_LIMIT, MAX_RATIONALE_LENGTH,
+};
 
 fn check_paused(storage: &dyn Storage, block: &BlockInfo) -> Result<(), ContractError> {
     let paused = DAO_PAUSED.may_load(storage)?;
     if let Some(expiration) = paused {
         if !expiration.is_expired(block) {
-            return Err(ContractError::Paused {});
+            return Err(ContractError::PausedUntil { expiration });
         }
     }
 
@@ -40,6 +64,31 @@ fn check_status(origin_status: &Status, desired_status: Status) -> Result<(), Co
     Ok(())
 }
 
+fn check_deposit_size(cfg: &Config, received: Uint128) -> Result<(), ContractError> {
+    match cfg.max_single_deposit {
+        Some(max) if received > max => Err(ContractError::OversizedRequest {
+            size: u64::try_from(received.u128()).unwrap_or(u64::MAX),
+            max: u64::try_from(max.u128()).unwrap_or(u64::MAX),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Whether `prop_id` has collected deposits from enough distinct addresses to open
+fn has_enough_depositors(storage: &dyn Storage, cfg: &Config, prop_id: u64) -> bool {
+    match cfg.min_distinct_depositors {
+        Some(min) => {
+            let depositors = DEPOSITS
+                .prefix(prop_id)
+                .keys(storage, None, None, Order::Ascending)
+                .take(min as usize)
+                .count();
+            depositors >= min as usize
+        }
+        None => true,
+    }
+}
+
 fn create_proposal(
     storage: &mut dyn Storage,
     prop_id: u64,
@@ -49,31 +98,71 @@ fn create_proposal(
     PROPOSALS.save(storage, prop_id, proposal)?;
     IDX_PROPS_BY_STATUS.save(storage, (proposal.status as u8, prop_id), &Empty {})?;
     IDX_PROPS_BY_PROPOSER.save(storage, (proposer.clone(), prop_id), &Empty {})?;
+    IDX_PROPS_BY_UPDATE_HEIGHT.save(storage, (proposal.last_updated_height, prop_id), &Empty {})?;
+    IDX_PROPS_BY_DEPOSIT.save(storage, (proposal.total_deposit.u128(), prop_id), &Empty {})?;
+    if let Some(category) = &proposal.category {
+        IDX_PROPS_BY_CATEGORY.save(storage, (category.clone(), prop_id), &Empty {})?;
+    }
+    if proposal.status == Status::Open {
+        UNNOTIFIED_OPEN.save(storage, prop_id, &Empty {})?;
+    }
 
     Ok(())
 }
 
+/// Moves the proposal in the deposit index once its `total_deposit` changed from `before`
+fn reindex_deposit(
+    storage: &mut dyn Storage,
+    prop_id: u64,
+    before: Uint128,
+    proposal: &Proposal,
+) -> StdResult<()> {
+    IDX_PROPS_BY_DEPOSIT.remove(storage, (before.u128(), prop_id));
+    IDX_PROPS_BY_DEPOSIT.save(storage, (proposal.total_deposit.u128(), prop_id), &Empty {})
+}
+
 fn create_deposit(
     storage: &mut dyn Storage,
     prop_id: u64,
     depositor: &Addr,
     amount: &Uint128,
+    denom: &str,
 ) -> StdResult<()> {
     // deposit
     let mut deposit = DEPOSITS
         .may_load(storage, (prop_id, depositor.clone()))?
         .unwrap_or_default();
-    if deposit.amount.is_zero() {
+    let is_new = deposit.amount.is_zero();
+    if is_new {
         IDX_DEPOSITS_BY_DEPOSITOR.save(storage, (depositor.clone(), prop_id), &Empty {})?;
+        deposit.denom = denom.to_string();
     }
 
     deposit.amount = deposit.amount.checked_add(*amount)?;
 
     DEPOSITS.save(storage, (prop_id, depositor.clone()), &deposit)?;
+    DEPOSIT_SUMMARIES.update(storage, prop_id, |summary| -> StdResult<_> {
+        let mut summary = summary.unwrap_or_default();
+        summary.total_amount = summary.total_amount.checked_add(*amount)?;
+        summary.depositor_count += is_new as u64;
+        Ok(summary)
+    })?;
+    ESCROWED_DEPOSITS.update(storage, &deposit.denom, |escrowed| -> StdResult<_> {
+        Ok(escrowed.unwrap_or_default().checked_add(*amount)?)
+    })?;
 
     Ok(())
 }
 
+/// Takes deposits leaving the treasury, or confiscated into it, out of `ESCROWED_DEPOSITS`
+fn release_escrow(storage: &mut dyn Storage, denom: &str, amount: Uint128) -> StdResult<()> {
+    // deposits made before the escrow was tracked are only counted once migrated
+    let escrowed = ESCROWED_DEPOSITS
+        .may_load(storage, denom)?
+        .unwrap_or_default();
+    ESCROWED_DEPOSITS.save(storage, denom, &escrowed.saturating_sub(amount))
+}
+
 fn make_deposit_claimable(
     storage: &mut dyn Storage,
     prop_id: u64,
@@ -89,17 +178,32 @@ fn make_deposit_claimable(
     Ok(())
 }
 
+/// Moves the proposal to `height` in the update index. Persisting the proposal is up to the caller
+fn touch_proposal(
+    storage: &mut dyn Storage,
+    prop_id: u64,
+    proposal: &mut Proposal,
+    height: u64,
+) -> StdResult<()> {
+    IDX_PROPS_BY_UPDATE_HEIGHT.remove(storage, (proposal.last_updated_height, prop_id));
+    proposal.last_updated_height = height;
+    IDX_PROPS_BY_UPDATE_HEIGHT.save(storage, (height, prop_id), &Empty {})
+}
+
 fn update_proposal_status(
     storage: &mut dyn Storage,
     prop_id: u64,
     proposal: &mut Proposal,
     desired: Status,
+    height: u64,
 ) -> StdResult<()> {
     let before = proposal.status;
     proposal.status = desired;
+    touch_proposal(storage, prop_id, proposal, height)?;
     PROPOSALS.update(storage, prop_id, |prop| {
         if let Some(mut prop) = prop {
             prop.status = desired;
+            prop.last_updated_height = height;
             Ok(prop)
         } else {
             Err(StdError::not_found("proposal"))
@@ -107,10 +211,137 @@ fn update_proposal_status(
     })?;
     IDX_PROPS_BY_STATUS.remove(storage, (before as u8, prop_id));
     IDX_PROPS_BY_STATUS.save(storage, (desired as u8, prop_id), &Empty {})?;
+    if desired == Status::Open {
+        UNNOTIFIED_OPEN.save(storage, prop_id, &Empty {})?;
+    } else {
+        UNNOTIFIED_OPEN.remove(storage, prop_id);
+    }
 
     Ok(())
 }
 
+fn record_stats(storage: &mut dyn Storage, proposal: &Proposal, executed: bool) -> StdResult<()> {
+    let mut stats = GOV_STATS_CACHE.may_load(storage)?.unwrap_or_default();
+    stats.record(proposal, executed);
+    GOV_STATS_CACHE.save(storage, &stats)?;
+
+    let counter = if executed {
+        &DAO_TOTAL_PROPOSALS_EXECUTED
+    } else {
+        &DAO_TOTAL_PROPOSALS_REJECTED
+    };
+    let count = counter.may_load(storage)?.unwrap_or_default();
+    counter.save(storage, &(count + 1))
+}
+
+/// Adds a confiscated proposal deposit to `CONFISCATED_TOTAL`, in the denoms its deposits were
+/// paid in. Under `ConfiscationPolicy::RewardStakers` also returns the `Fund`s paying them to
+/// the stakers
+fn record_confiscation(storage: &mut dyn Storage, prop_id: u64) -> StdResult<Vec<SubMsg>> {
+    let mut confiscated = BTreeMap::<String, Uint128>::new();
+    for item in DEPOSITS
+        .prefix(prop_id)
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (_, deposit) = item?;
+        *confiscated.entry(deposit.denom).or_default() += deposit.amount;
+    }
+
+    let policy = CONFIG.load(storage)?.confiscation_policy;
+    let mut msgs = vec![];
+    for (denom, amount) in confiscated {
+        CONFISCATED_TOTAL.update(storage, &denom, |total| -> StdResult<Uint128> {
+            Ok(total.unwrap_or_default().checked_add(amount)?)
+        })?;
+        release_escrow(storage, &denom, amount)?;
+
+        if policy != ConfiscationPolicy::RewardStakers || amount.is_zero() {
+            continue;
+        }
+        // unboosted, so confiscations don't drain the reward reserve. If the staking contract
+        // refuses them, the reply keeps the deposit in the treasury instead
+        msgs.push(SubMsg::reply_on_error(
+            WasmMsg::Execute {
+                contract_addr: STAKING_CONTRACT.load(storage)?.to_string(),
+                msg: to_binary(&ion_stake::msg::ExecuteMsg::FundUnboosted {})?,
+                funds: coins(amount.u128(), denom),
+            },
+            CONFISCATION_REPLY_ID,
+        ));
+    }
+    Ok(msgs)
+}
+
+/// Rejects expired pending proposals (deposits are confiscated).
+/// Only the oldest `limit` pending proposals are inspected to bound the work.
+fn reap_expired_pending(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    limit: usize,
+) -> StdResult<(u64, Vec<SubMsg>)> {
+    let pending = IDX_PROPS_BY_STATUS
+        .prefix(Status::Pending as u8)
+        .keys(storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut reaped = 0;
+    let mut msgs = vec![];
+    for prop_id in pending {
+        let mut prop = PROPOSALS.load(storage, prop_id)?;
+        if prop.current_status(block) == Status::Rejected {
+            update_proposal_status(storage, prop_id, &mut prop, Status::Rejected, block.height)?;
+            record_stats(storage, &prop, false)?;
+            msgs.extend(record_confiscation(storage, prop_id)?);
+            reaped += 1;
+        }
+    }
+
+    Ok((reaped, msgs))
+}
+
+/// Tells `Config::notifiers` about open proposals whose voting period is over, once per
+/// proposal. Only the oldest `limit` open proposals not announced yet are inspected, announced
+/// ones leave `UNNOTIFIED_OPEN` so they are never scanned again.
+fn notify_expired(
+    storage: &mut dyn Storage,
+    cfg: &Config,
+    block: &BlockInfo,
+    limit: usize,
+) -> StdResult<Vec<SubMsg>> {
+    if cfg.notifiers.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let open = UNNOTIFIED_OPEN
+        .keys(storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut msgs = vec![];
+    for prop_id in open {
+        let prop = PROPOSALS.load(storage, prop_id)?;
+        if !prop.vote_ends_at.is_expired(block) {
+            continue;
+        }
+
+        UNNOTIFIED_OPEN.remove(storage, prop_id);
+        NOTIFIED_EXPIRED.save(storage, prop_id, &Empty {})?;
+        for notifier in &cfg.notifiers {
+            msgs.push(SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: notifier.to_string(),
+                    msg: to_binary(&NotifierMsg::ProposalExpired { id: prop_id })?,
+                    funds: vec![],
+                },
+                NOTIFIER_REPLY_ID,
+            ));
+        }
+    }
+
+    Ok(msgs)
+}
+
 pub fn propose(
     deps: DepsMut,
     env: Env,
@@ -126,12 +357,73 @@ pub fn propose(
     if received < cfg.proposal_min_deposit {
         return Err(ContractError::Unauthorized {});
     }
+    check_deposit_size(&cfg, received)?;
+
+    if let Some(category) = &propose_msg.category {
+        if !CATEGORIES.has(deps.storage, category) {
+            return Err(ContractError::InvalidProposalContent {
+                reason: format!("unknown category '{}'", category),
+            });
+        }
+    }
+
+    if cfg.require_valid_link && !is_valid_link(&propose_msg.link) {
+        return Err(ContractError::InvalidProposalContent {
+            reason: format!("invalid link '{}'", propose_msg.link),
+        });
+    }
+
+    let msgs = match propose_msg.cosmos_gov_vote {
+        Some(gov_msg) => {
+            let GovMsg::Vote { proposal_id, .. } = &gov_msg;
+            if *proposal_id == 0 {
+                return Err(ContractError::InvalidProposalContent {
+                    reason: "gov proposal id must be greater than 0".to_string(),
+                });
+            }
+            if !propose_msg.msgs.is_empty() {
+                return Err(ContractError::InvalidProposalContent {
+                    reason: "msgs must be empty when cosmos_gov_vote is set".to_string(),
+                });
+            }
+            vec![CosmosMsg::Gov(gov_msg)]
+        }
+        None => propose_msg.msgs,
+    };
 
     // Get total supply
     let total_supply = get_total_staked_supply(deps.as_ref())?;
     if total_supply.is_zero() {
         return Err(ContractError::LackOfStakes {});
     }
+    let threshold = match propose_msg.parent_proposal {
+        Some(parent_id) => {
+            PROPOSALS
+                .may_load(deps.storage, parent_id)?
+                .ok_or_else(|| ContractError::InvalidProposalContent {
+                    reason: format!("parent proposal {} does not exist", parent_id),
+                })?
+                .threshold
+        }
+        None if cfg.auto_scale_threshold => cfg.threshold.scaled(
+            total_supply,
+            cfg.total_token_supply,
+            cfg.threshold_scaling_coefficient,
+        ),
+        None => cfg.threshold.clone(),
+    };
+    // young DAOs get a relaxed quorum, amendments keep their parent's
+    let threshold = match &cfg.bootstrap {
+        Some(bootstrap)
+            if propose_msg.parent_proposal.is_none() && !bootstrap.until.is_expired(&env.block) =>
+        {
+            Threshold {
+                quorum: bootstrap.quorum,
+                ..threshold
+            }
+        }
+        _ => threshold,
+    };
 
     // Create a proposal
     let mut prop = Proposal {
@@ -140,7 +432,7 @@ pub fn propose(
         link: propose_msg.link,
         description: propose_msg.description,
         proposer: info.sender.clone(),
-        msgs: propose_msg.msgs,
+        msgs,
         status: Status::Pending,
 
         // time
@@ -151,19 +443,50 @@ pub fn propose(
             &env.block.clone().into(),
             &cfg.deposit_period.add(cfg.voting_period)?,
         ), // set it to maximum
+        eligibility_height: None,
+        executed_at: None,
+        rejected_at: None,
+        last_updated_height: env.block.height,
 
         // voting
         votes: Votes::default(),
-        threshold: cfg.threshold,
+        voter_count: 0,
+        cosponsor_count: 0,
+        threshold,
         total_weight: total_supply,
         total_deposit: received, // initial deposit = received
         deposit_base_amount: cfg.proposal_deposit,
         deposit_claimable: false,
+        awaiting_depositors: false,
+        category: propose_msg.category,
     };
 
     let mut resp = Response::new();
-    if received >= cfg.proposal_deposit {
-        prop.activate_voting_period(env.block.into(), &cfg.voting_period);
+    if cfg.auto_reap {
+        let (reaped, msgs) = reap_expired_pending(deps.storage, &env.block, AUTO_REAP_LIMIT)?;
+        if reaped > 0 {
+            resp = resp
+                .add_submessages(msgs)
+                .add_attribute("reaped", reaped.to_string());
+        }
+    }
+
+    let id = next_id(deps.storage)?;
+    create_deposit(deps.storage, id, &info.sender, &received, &gov_token)?;
+
+    if received >= cfg.proposal_deposit && !has_enough_depositors(deps.storage, &cfg, id) {
+        prop.awaiting_depositors = true;
+    } else if received >= cfg.proposal_deposit {
+        prop.activate_voting_period(
+            env.block.clone().into(),
+            &cfg.voting_period,
+            cfg.voting_stake_age,
+        );
+        prop.total_weight = get_total_staked_at_height(
+            deps.querier,
+            STAKING_CONTRACT.load(deps.storage)?,
+            prop.eligibility_height(),
+        )?;
 
         // refund exceeded amount
         let gap = received - cfg.proposal_deposit;
@@ -175,8 +498,6 @@ pub fn propose(
         }
     }
 
-    let id = next_id(deps.storage)?;
-    create_deposit(deps.storage, id, &info.sender, &received)?;
     create_proposal(deps.storage, id, &info.sender, &prop)?;
 
     Ok(resp
@@ -202,6 +523,7 @@ pub fn deposit(
     if received.is_zero() {
         return Err(ContractError::Unauthorized {});
     }
+    check_deposit_size(&cfg, received)?;
 
     let mut resp = Response::new()
         .add_attribute("action", "deposit")
@@ -210,22 +532,55 @@ pub fn deposit(
         .add_attribute("proposal_id", prop_id.to_string());
 
     let mut prop = PROPOSALS.load(deps.storage, prop_id)?;
+    if cfg.allow_deposit_top_up && prop.status == Status::Open {
+        // top-up: credit the depositor without touching the voting period
+        if prop.vote_ends_at.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+        create_deposit(deps.storage, prop_id, &info.sender, &received, &gov_token)?;
+        prop.total_deposit += received;
+        reindex_deposit(deps.storage, prop_id, prop.total_deposit - received, &prop)?;
+        touch_proposal(deps.storage, prop_id, &mut prop, env.block.height)?;
+        PROPOSALS.save(deps.storage, prop_id, &prop)?;
+
+        return Ok(resp.add_attribute("result", "top_up"));
+    }
+
     check_status(&prop.status, Status::Pending)?;
     if prop.deposit_ends_at.is_expired(&env.block) {
         Err(ContractError::Expired {})
     } else {
-        create_deposit(deps.storage, prop_id, &info.sender, &received)?;
+        create_deposit(deps.storage, prop_id, &info.sender, &received, &gov_token)?;
 
         prop.total_deposit += received;
-        if prop.total_deposit >= cfg.proposal_deposit {
+        reindex_deposit(deps.storage, prop_id, prop.total_deposit - received, &prop)?;
+        prop.awaiting_depositors = prop.total_deposit >= cfg.proposal_deposit
+            && !has_enough_depositors(deps.storage, &cfg, prop_id);
+        if prop.total_deposit >= cfg.proposal_deposit && !prop.awaiting_depositors {
             // open
-            update_proposal_status(deps.storage, prop_id, &mut prop, Status::Open)?;
-            prop.activate_voting_period(env.block.into(), &cfg.voting_period);
+            update_proposal_status(
+                deps.storage,
+                prop_id,
+                &mut prop,
+                Status::Open,
+                env.block.height,
+            )?;
+            prop.activate_voting_period(
+                env.block.clone().into(),
+                &cfg.voting_period,
+                cfg.voting_stake_age,
+            );
+            prop.total_weight = get_total_staked_at_height(
+                deps.querier,
+                STAKING_CONTRACT.load(deps.storage)?,
+                prop.eligibility_height(),
+            )?;
             PROPOSALS.save(deps.storage, prop_id, &prop)?;
 
-            // refund exceeded amount
+            // refund exceeded amount. With a depositor minimum the excess may come from
+            // earlier depositors, so it stays in and is returned with the deposits instead
             let gap = prop.total_deposit - cfg.proposal_deposit;
-            if gap > Uint128::zero() {
+            if gap > Uint128::zero() && cfg.min_distinct_depositors.is_none() {
                 resp = resp.add_message(BankMsg::Send {
                     to_address: info.sender.to_string(),
                     amount: coins(gap.u128(), gov_token),
@@ -235,12 +590,64 @@ pub fn deposit(
             Ok(resp.add_attribute("result", "open"))
         } else {
             // pending = prevent default
+            touch_proposal(deps.storage, prop_id, &mut prop, env.block.height)?;
             PROPOSALS.save(deps.storage, prop_id, &prop)?;
             Ok(resp.add_attribute("result", "pending"))
         }
     }
 }
 
+pub fn withdraw_deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    prop_id: u64,
+) -> Result<Response, ContractError> {
+    check_paused(deps.storage, &env.block)?;
+
+    let mut prop = PROPOSALS.load(deps.storage, prop_id)?;
+    check_status(&prop.current_status(&env.block), Status::Pending)?;
+
+    let deposit = DEPOSITS
+        .may_load(deps.storage, (prop_id, info.sender.clone()))?
+        .unwrap_or_default();
+    if deposit.amount.is_zero() {
+        return Err(ContractError::NoDeposit {});
+    }
+
+    DEPOSITS.remove(deps.storage, (prop_id, info.sender.clone()));
+    IDX_DEPOSITS_BY_DEPOSITOR.remove(deps.storage, (info.sender.clone(), prop_id));
+    DEPOSIT_SUMMARIES.update(deps.storage, prop_id, |summary| -> StdResult<_> {
+        let mut summary = summary.unwrap_or_default();
+        summary.total_amount = summary.total_amount.checked_sub(deposit.amount)?;
+        summary.depositor_count = summary.depositor_count.saturating_sub(1);
+        Ok(summary)
+    })?;
+    release_escrow(deps.storage, &deposit.denom, deposit.amount)?;
+    prop.total_deposit = prop
+        .total_deposit
+        .checked_sub(deposit.amount)
+        .map_err(StdError::overflow)?;
+    reindex_deposit(
+        deps.storage,
+        prop_id,
+        prop.total_deposit + deposit.amount,
+        &prop,
+    )?;
+    touch_proposal(deps.storage, prop_id, &mut prop, env.block.height)?;
+    PROPOSALS.save(deps.storage, prop_id, &prop)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(deposit.amount.u128(), &deposit.denom),
+        })
+        .add_attribute("action", "withdraw_deposit")
+        .add_attribute("sender", info.sender)
+        .add_attribute("amount", deposit.amount)
+        .add_attribute("proposal_id", prop_id.to_string()))
+}
+
 pub fn claim_deposit(
     deps: DepsMut,
     env: Env,
@@ -261,13 +668,17 @@ pub fn claim_deposit(
     deposit.claimed = true;
 
     DEPOSITS.save(deps.storage, (prop_id, info.sender.clone()), &deposit)?;
-
-    let gov_token = GOV_TOKEN.load(deps.storage)?;
+    DEPOSIT_SUMMARIES.update(deps.storage, prop_id, |summary| -> StdResult<_> {
+        let mut summary = summary.unwrap_or_default();
+        summary.claimed_count += 1;
+        Ok(summary)
+    })?;
+    release_escrow(deps.storage, &deposit.denom, deposit.amount)?;
 
     Ok(Response::new()
         .add_message(BankMsg::Send {
             to_address: info.sender.to_string(),
-            amount: coins(deposit.amount.u128(), gov_token),
+            amount: coins(deposit.amount.u128(), deposit.denom),
         })
         .add_attribute("action", "claim_deposit")
         .add_attribute("sender", info.sender.to_string())
@@ -275,15 +686,60 @@ pub fn claim_deposit(
         .add_attribute("amount", deposit.amount))
 }
 
+pub fn cosponsor(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    prop_id: u64,
+) -> Result<Response, ContractError> {
+    check_paused(deps.storage, &env.block)?;
+
+    let mut prop = PROPOSALS.load(deps.storage, prop_id)?;
+    let status = prop.current_status(&env.block);
+    if !matches!(status, Status::Pending | Status::Open) {
+        return Err(ContractError::InvalidProposalStatus {
+            current: format!("{:?}", status),
+            desired: "pending | open".to_string(),
+        });
+    }
+    if prop.proposer == info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if get_staked_balance(deps.as_ref(), info.sender.clone())?.is_zero() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !COSPONSORS.has(deps.storage, (prop_id, &info.sender)) {
+        COSPONSORS.save(deps.storage, (prop_id, &info.sender), &Empty {})?;
+        prop.cosponsor_count += 1;
+        PROPOSALS.save(deps.storage, prop_id, &prop)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "cosponsor")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", prop_id.to_string()))
+}
+
 pub fn vote(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     prop_id: u64,
     vote: Vote,
+    rationale: Option<String>,
 ) -> Result<Response, ContractError> {
     check_paused(deps.storage, &env.block)?;
 
+    if let Some(rationale) = &rationale {
+        if rationale.len() > MAX_RATIONALE_LENGTH {
+            return Err(ContractError::RationaleTooLong {
+                length: rationale.len() as u64,
+                max: MAX_RATIONALE_LENGTH as u64,
+            });
+        }
+    }
+
     // Ensure proposal exists and can be voted on
     let mut prop = PROPOSALS.load(deps.storage, prop_id)?;
     check_status(&prop.status, Status::Open)?;
@@ -291,20 +747,25 @@ pub fn vote(
         return Err(ContractError::Expired {});
     }
 
-    // Get voter balance at proposal start
-    let vote_power = get_voting_power_at_height(
-        deps.querier,
-        STAKING_CONTRACT.load(deps.storage)?,
-        info.sender.clone(),
-        prop.vote_starts_at.height,
-    )?;
+    // Get voter balance at proposal start. It can't change afterwards, so a re-vote
+    // reuses the weight of the previous ballot instead of asking the staking contract again
+    let ballot = BALLOTS.may_load(deps.storage, (prop_id, &info.sender))?;
+    let vote_power = match &ballot {
+        Some(ballot) => ballot.weight,
+        None => get_voting_power_at_height(
+            deps.querier,
+            STAKING_CONTRACT.load(deps.storage)?,
+            info.sender.clone(),
+            prop.eligibility_height(),
+        )?,
+    };
     if vote_power.is_zero() {
         return Err(ContractError::Unauthorized {});
     }
 
-    let ballot = BALLOTS.may_load(deps.storage, (prop_id, &info.sender))?;
-    if let Some(ballot) = ballot {
-        prop.votes.revoke(ballot.vote, ballot.weight);
+    match ballot {
+        Some(ballot) => prop.votes.revoke(ballot.vote, ballot.weight),
+        None => prop.voter_count += 1,
     }
     prop.votes.submit(vote, vote_power);
 
@@ -314,11 +775,20 @@ pub fn vote(
         &Ballot {
             weight: vote_power,
             vote,
+            rationale,
         },
     )?;
+    touch_proposal(deps.storage, prop_id, &mut prop, env.block.height)?;
     PROPOSALS.save(deps.storage, prop_id, &prop)?;
 
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.record_tally_history {
+        TALLY_HISTORY.save(deps.storage, (prop_id, env.block.height), &prop.votes)?;
+    }
+    let notifications = notify_expired(deps.storage, &cfg, &env.block, AUTO_REAP_LIMIT)?;
+
     Ok(Response::new()
+        .add_submessages(notifications)
         .add_attribute("action", "vote")
         .add_attribute("sender", info.sender)
         .add_attribute("vote", format!("{:?}", vote))
@@ -333,21 +803,113 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     check_paused(deps.storage, &env.block)?;
 
+    let cfg = CONFIG.load(deps.storage)?;
     let mut prop = PROPOSALS.load(deps.storage, prop_id)?;
-    if !prop.vote_ends_at.is_expired(&env.block) {
+    if !cfg.execute_permission.allows(&info.sender, &prop.proposer) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let executable_at = prop.executable_at(cfg.execution_delay)?;
+    if !executable_at.is_expired(&env.block) {
         return Err(ContractError::NotExpired {});
     }
+    if let Some(window) = cfg.execution_window {
+        if (executable_at + window)?.is_expired(&env.block) {
+            return Err(ContractError::ExecutionWindowElapsed {});
+        }
+    }
 
     check_status(&prop.current_status(&env.block), Status::Passed)?;
-    update_proposal_status(deps.storage, prop_id, &mut prop, Status::Executed)?;
+    if cfg.recheck_quorum_on_execute && !passes_rechecked_quorum(deps.as_ref(), &prop)? {
+        return Err(ContractError::QuorumNotMet {});
+    }
+    check_reserves(
+        deps.as_ref(),
+        &env.contract.address,
+        &cfg.reserves,
+        &prop.msgs,
+    )?;
+    // reward well-attended proposals, the bonus leaves the treasury along with the proposal
+    let bonus = participation_bonus(deps.as_ref(), &env.contract.address, &cfg, &prop)?;
+    prop.executed_at = Some(env.block.clone().into());
+    let mut msgs: Vec<SubMsg> = prop.msgs.iter().cloned().map(SubMsg::new).collect();
+    if let Some(last) = msgs.last_mut() {
+        // a failure reverts the saved id along with everything else, only success needs a reply
+        last.id = PROPOSAL_MSGS_REPLY_ID;
+        last.reply_on = ReplyOn::Success;
+        CALLING_PROPOSAL_ID.save(deps.storage, &prop_id)?;
+    }
+    PROPOSALS.save(deps.storage, prop_id, &prop)?;
+    update_proposal_status(
+        deps.storage,
+        prop_id,
+        &mut prop,
+        Status::Executed,
+        env.block.height,
+    )?;
     make_deposit_claimable(deps.storage, prop_id, &mut prop)?;
     prop.update_status(&env.block);
+    record_stats(deps.storage, &prop, true)?;
+
+    let notifications = notify_expired(deps.storage, &cfg, &env.block, AUTO_REAP_LIMIT)?;
 
     // Dispatch all proposed messages
-    Ok(Response::new()
-        .add_messages(prop.msgs)
+    let mut resp = Response::new()
+        .add_submessages(msgs)
+        .add_submessages(notifications)
         .add_attribute("action", "execute")
         .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", prop_id.to_string());
+
+    match bonus {
+        ParticipationBonus::NotEarned => {}
+        ParticipationBonus::Unfunded(amount) => {
+            resp = resp.add_attribute("participation_bonus_unfunded", amount);
+        }
+        ParticipationBonus::Paid(amount, msg) => {
+            resp = resp
+                .add_message(msg)
+                .add_attribute("participation_bonus", amount);
+        }
+    }
+
+    Ok(resp)
+}
+
+pub fn expire_execution(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    prop_id: u64,
+) -> Result<Response, ContractError> {
+    check_paused(deps.storage, &env.block)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let window = cfg
+        .execution_window
+        .ok_or(ContractError::NoExecutionWindow {})?;
+
+    let mut prop = PROPOSALS.load(deps.storage, prop_id)?;
+    check_status(&prop.current_status(&env.block), Status::Passed)?;
+    if !(prop.executable_at(cfg.execution_delay)? + window)?.is_expired(&env.block) {
+        return Err(ContractError::NotExpired {});
+    }
+
+    prop.rejected_at = Some(env.block.clone().into());
+    PROPOSALS.save(deps.storage, prop_id, &prop)?;
+    update_proposal_status(
+        deps.storage,
+        prop_id,
+        &mut prop,
+        Status::Rejected,
+        env.block.height,
+    )?;
+    // the proposal did pass, so the proposer gets the deposit back
+    make_deposit_claimable(deps.storage, prop_id, &mut prop)?;
+    record_stats(deps.storage, &prop, false)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "expire_execution")
+        .add_attribute("sender", info.sender)
         .add_attribute("proposal_id", prop_id.to_string()))
 }
 
@@ -383,67 +945,601 @@ pub fn close(
         }
     }
 
-    let prev_status = prop.status;
     check_status(&prop.current_status(&env.block), Status::Rejected)?;
-    update_proposal_status(deps.storage, prop_id, &mut prop, Status::Rejected)?;
-    prop.update_status(&env.block);
+    let (result, confiscation) = reject_expired(deps.storage, &env.block, prop_id, &mut prop)?;
 
-    let mut resp = Response::new()
+    Ok(Response::new()
+        .add_submessages(confiscation)
         .add_attribute("action", "close")
         .add_attribute("sender", info.sender.to_string())
-        .add_attribute("proposal_id", prop_id.to_string());
+        .add_attribute("proposal_id", prop_id.to_string())
+        .add_attribute("result", result))
+}
+
+/// Persists the rejection of a timed out proposal and settles its deposit: refunded if it
+/// was voted down, confiscated if it never opened or was vetoed
+fn reject_expired(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    prop_id: u64,
+    prop: &mut Proposal,
+) -> StdResult<(&'static str, Vec<SubMsg>)> {
+    let prev_status = prop.status;
+    update_proposal_status(storage, prop_id, prop, Status::Rejected, block.height)?;
+    prop.update_status(block);
+    record_stats(storage, prop, false)?;
 
     if prev_status == Status::Open && !prop.is_vetoed() {
-        make_deposit_claimable(deps.storage, prop_id, &mut prop)?;
-        resp = resp.add_attribute("result", "refund");
+        make_deposit_claimable(storage, prop_id, prop)?;
+        Ok(("refund", vec![]))
     } else {
-        resp = resp.add_attribute("result", "confiscate")
+        Ok(("confiscate", record_confiscation(storage, prop_id)?))
     }
-
-    Ok(resp)
 }
 
-pub fn pause_dao(
+/// Persists the status the given proposals have reached by timing, moving them in the
+/// status index. Timed out proposals are settled the same way `close` would
+pub fn refresh_status(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    expiration: Expiration,
+    proposal_ids: Vec<u64>,
 ) -> Result<Response, ContractError> {
-    // Only contract can call this method
-    if env.contract.address != info.sender {
-        return Err(ContractError::Unauthorized {});
+    check_paused(deps.storage, &env.block)?;
+
+    if proposal_ids.len() > MAX_LIMIT as usize {
+        return Err(ContractError::OversizedRequest {
+            size: proposal_ids.len() as u64,
+            max: MAX_LIMIT as u64,
+        });
     }
 
-    DAO_PAUSED.save(deps.storage, &expiration)?;
+    let mut refreshed = 0u64;
+    let mut msgs = vec![];
+    for prop_id in proposal_ids {
+        let mut prop = PROPOSALS.load(deps.storage, prop_id)?;
+        let status = prop.current_status(&env.block);
+        if status == prop.status {
+            continue;
+        }
+
+        if status == Status::Rejected {
+            let (_, confiscation) = reject_expired(deps.storage, &env.block, prop_id, &mut prop)?;
+            msgs.extend(confiscation);
+        } else {
+            update_proposal_status(deps.storage, prop_id, &mut prop, status, env.block.height)?;
+        }
+        refreshed += 1;
+    }
 
     Ok(Response::new()
-        .add_attribute("action", "pause_dao")
-        .add_attribute("expiration", expiration.to_string()))
+        .add_submessages(msgs)
+        .add_attribute("action", "refresh_status")
+        .add_attribute("refreshed", refreshed.to_string()))
 }
 
-pub fn update_config(
+pub fn force_close(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    update_config_msg: Config,
+    prop_id: u64,
 ) -> Result<Response, ContractError> {
     // Only contract can call this method
     if env.contract.address != info.sender {
         return Err(ContractError::Unauthorized {});
     }
 
-    update_config_msg.threshold.validate()?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut prop = PROPOSALS.load(deps.storage, prop_id)?;
 
-    CONFIG.save(deps.storage, &update_config_msg)?;
+    match prop.status {
+        Status::Open | Status::Passed => {}
+        _ => {
+            return Err(ContractError::InvalidProposalStatus {
+                current: format!("{:?}", prop.status),
+                desired: "open | passed".to_string(),
+            })
+        }
+    }
 
-    Ok(Response::new()
-        .add_attribute("action", "update_config")
-        .add_attribute("sender", info.sender))
-}
+    let grace_ends_at = (prop.vote_ends_at + cfg.force_close_grace_period)?;
+    if !grace_ends_at.is_expired(&env.block) {
+        return Err(ContractError::NotExpired {});
+    }
 
-pub fn update_staking_contract(
-    deps: DepsMut,
-    env: Env,
+    prop.rejected_at = Some(env.block.clone().into());
+    PROPOSALS.save(deps.storage, prop_id, &prop)?;
+    update_proposal_status(
+        deps.storage,
+        prop_id,
+        &mut prop,
+        Status::Rejected,
+        env.block.height,
+    )?;
+    record_stats(deps.storage, &prop, false)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "force_close")
+        .add_attribute("proposal_id", prop_id.to_string());
+
+    // same deposit policy as `close` on an open proposal
+    if !prop.is_vetoed() {
+        make_deposit_claimable(deps.storage, prop_id, &mut prop)?;
+        resp = resp.add_attribute("result", "refund");
+    } else {
+        resp = resp
+            .add_submessages(record_confiscation(deps.storage, prop_id)?)
+            .add_attribute("result", "confiscate")
+    }
+
+    Ok(resp)
+}
+
+pub fn veto_execution(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    prop_id: u64,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let delay = cfg
+        .execution_delay
+        .ok_or(ContractError::NoExecutionDelay {})?;
+
+    let mut prop = PROPOSALS.load(deps.storage, prop_id)?;
+    check_status(&prop.current_status(&env.block), Status::Passed)?;
+
+    // once the timelock is over, the proposal belongs to whoever executes it first
+    if (prop.vote_ends_at + delay)?.is_expired(&env.block) {
+        return Err(ContractError::ExecutionDelayElapsed {});
+    }
+
+    prop.rejected_at = Some(env.block.clone().into());
+    PROPOSALS.save(deps.storage, prop_id, &prop)?;
+    update_proposal_status(
+        deps.storage,
+        prop_id,
+        &mut prop,
+        Status::Rejected,
+        env.block.height,
+    )?;
+    // the proposal did pass, so the proposer gets the deposit back
+    make_deposit_claimable(deps.storage, prop_id, &mut prop)?;
+    record_stats(deps.storage, &prop, false)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "veto_execution")
+        .add_attribute("proposal_id", prop_id.to_string()))
+}
+
+/// Rewrites the status / proposer / depositor indexes for a page of proposals.
+/// Stale status entries are dropped; stale update height, deposit and category entries are
+/// left to `sweep_stale_index`, orphaned proposer / depositor entries are left as is.
+pub fn reconcile_indexes(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+    let props = PROPOSALS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let statuses = [
+        Status::Pending,
+        Status::Open,
+        Status::Rejected,
+        Status::Passed,
+        Status::Executed,
+    ];
+    for (prop_id, prop) in props.iter() {
+        for status in statuses.iter().filter(|s| **s != prop.status) {
+            IDX_PROPS_BY_STATUS.remove(deps.storage, (*status as u8, *prop_id));
+        }
+        IDX_PROPS_BY_STATUS.save(deps.storage, (prop.status as u8, *prop_id), &Empty {})?;
+        IDX_PROPS_BY_PROPOSER.save(deps.storage, (prop.proposer.clone(), *prop_id), &Empty {})?;
+        IDX_PROPS_BY_UPDATE_HEIGHT.save(
+            deps.storage,
+            (prop.last_updated_height, *prop_id),
+            &Empty {},
+        )?;
+        IDX_PROPS_BY_DEPOSIT.save(
+            deps.storage,
+            (prop.total_deposit.u128(), *prop_id),
+            &Empty {},
+        )?;
+        if let Some(category) = &prop.category {
+            IDX_PROPS_BY_CATEGORY.save(deps.storage, (category.clone(), *prop_id), &Empty {})?;
+        }
+
+        let depositors = DEPOSITS
+            .prefix(*prop_id)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for depositor in depositors {
+            IDX_DEPOSITS_BY_DEPOSITOR.save(deps.storage, (depositor, *prop_id), &Empty {})?;
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "reconcile_indexes")
+        .add_attribute("reconciled", props.len().to_string())
+        .add_attribute(
+            "last_proposal_id",
+            props
+                .last()
+                .map(|(id, _)| id.to_string())
+                .unwrap_or_else(|| "none".to_string()),
+        ))
+}
+
+/// Drops the stale entries among a page of keys of a secondary index. A stale entry is keyed
+/// by an outdated value, so it can't be looked up by proposal id: the index itself is paged.
+pub fn sweep_stale_index(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    index: IndexSweep,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+    let (name, sweep) = match index {
+        IndexSweep::UpdateHeight { start_after } => (
+            "update_height",
+            sweep_index(
+                deps.storage,
+                IDX_PROPS_BY_UPDATE_HEIGHT,
+                start_after,
+                limit,
+                |prop, height| prop.last_updated_height == *height,
+            )?,
+        ),
+        IndexSweep::Deposit { start_after } => (
+            "deposit",
+            sweep_index(
+                deps.storage,
+                IDX_PROPS_BY_DEPOSIT,
+                start_after.map(|(deposit, id)| (deposit.u128(), id)),
+                limit,
+                |prop, deposit| prop.total_deposit.u128() == *deposit,
+            )?,
+        ),
+        IndexSweep::Category { start_after } => (
+            "category",
+            sweep_index(
+                deps.storage,
+                IDX_PROPS_BY_CATEGORY,
+                start_after,
+                limit,
+                |prop, category| prop.category.as_ref() == Some(category),
+            )?,
+        ),
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "sweep_stale_index")
+        .add_attribute("index", name)
+        .add_attribute("scanned", sweep.scanned.to_string())
+        .add_attribute("removed", sweep.removed.to_string())
+        .add_attribute(
+            "last_key",
+            sweep.last_key.unwrap_or_else(|| "none".to_string()),
+        ))
+}
+
+struct IndexSweepPage {
+    scanned: usize,
+    removed: usize,
+    /// `value,proposal_id` of the last key scanned, `None` once the index is exhausted
+    last_key: Option<String>,
+}
+
+/// Removes the entries among `limit` keys of `index` after `start_after` whose proposal is
+/// gone or no longer has the indexed value
+fn sweep_index<V>(
+    storage: &mut dyn Storage,
+    index: Map<'static, (V, u64), Empty>,
+    start_after: Option<(V, u64)>,
+    limit: usize,
+    is_current: impl Fn(&Proposal, &V) -> bool,
+) -> StdResult<IndexSweepPage>
+where
+    V: Display + 'static,
+    (V, u64): PrimaryKey<'static> + KeyDeserialize<Output = (V, u64)>,
+{
+    let keys = index
+        .keys(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let scanned = keys.len();
+    let last_key = match keys.last() {
+        Some((value, id)) if scanned == limit => Some(format!("{},{}", value, id)),
+        _ => None,
+    };
+    let mut removed = 0;
+    for (value, id) in keys {
+        let current = PROPOSALS
+            .may_load(storage, id)?
+            .is_some_and(|prop| is_current(&prop, &value));
+        if !current {
+            index.remove(storage, (value, id));
+            removed += 1;
+        }
+    }
+    Ok(IndexSweepPage {
+        scanned,
+        removed,
+        last_key,
+    })
+}
+
+/// Deploys a cw3-fixed-multisig with the DAO as wasm admin. The fixed multisig has no
+/// membership updates; the DAO rotates members by migrating the contract through a proposal.
+#[allow(clippy::too_many_arguments)]
+pub fn instantiate_multisig(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    code_id: u64,
+    label: String,
+    members: Vec<(Addr, u64)>,
+    required_weight: u64,
+    max_voting_period: Duration,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if SUB_MULTISIGS.has(deps.storage, label.clone()) {
+        return Err(ContractError::MultisigLabelTaken { label });
+    }
+
+    let total_weight: u64 = members.iter().map(|(_, weight)| weight).sum();
+    if required_weight == 0 {
+        return Err(ContractError::ZeroThreshold {});
+    }
+    if required_weight > total_weight {
+        return Err(ContractError::UnreachableThreshold {});
+    }
+
+    let voters = members
+        .into_iter()
+        .map(|(addr, weight)| {
+            Ok(Cw3Voter {
+                addr: deps.api.addr_validate(addr.as_str())?.to_string(),
+                weight,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    PENDING_MULTISIG_LABEL.save(deps.storage, &label)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate_multisig")
+        .add_attribute("label", label.clone())
+        .add_submessage(SubMsg::reply_on_success(
+            WasmMsg::Instantiate {
+                admin: Some(env.contract.address.to_string()),
+                code_id,
+                msg: to_binary(&Cw3FixedMultisigInstantiateMsg {
+                    voters,
+                    threshold: cw_utils::Threshold::AbsoluteCount {
+                        weight: required_weight,
+                    },
+                    max_voting_period,
+                })?,
+                funds: vec![],
+                label,
+            },
+            INSTANTIATE_MULTISIG_REPLY_ID,
+        )))
+}
+
+/// Same as `propose`, with a single message asking this DAO to deploy the sub-DAO so
+/// its address can be recorded from the instantiate reply
+pub fn propose_sub_dao(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ProposeSubDaoMsg,
+) -> Result<Response, ContractError> {
+    let instantiate = WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::InstantiateSubDao {
+            code_id: msg.code_id,
+            label: msg.label,
+            msg: to_binary(&msg.instantiate_msg)?,
+        })?,
+        funds: vec![],
+    };
+
+    propose(
+        deps,
+        env,
+        info,
+        ProposeMsg {
+            title: msg.title,
+            link: msg.link,
+            description: msg.description,
+            msgs: vec![instantiate.into()],
+            cosmos_gov_vote: None,
+            parent_proposal: None,
+            category: msg.category,
+        },
+    )
+}
+
+/// Deploys a sub-DAO with the DAO as wasm admin, see `ProposeSubDao`
+pub fn instantiate_sub_dao(
+    _deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    code_id: u64,
+    label: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate_sub_dao")
+        .add_attribute("label", label.clone())
+        .add_submessage(SubMsg::reply_on_success(
+            WasmMsg::Instantiate {
+                admin: Some(env.contract.address.to_string()),
+                code_id,
+                msg,
+                funds: vec![],
+                label,
+            },
+            INSTANTIATE_SUB_DAO_REPLY_ID,
+        )))
+}
+
+pub fn pause_dao(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    expiration: Expiration,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    DAO_PAUSED.save(deps.storage, &expiration)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "pause_dao")
+        .add_attribute("expiration", expiration.to_string()))
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    update_config_msg: Config,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    update_config_msg.threshold.validate()?;
+    update_config_msg.validate(deps.api)?;
+
+    replace_config(deps.storage, &update_config_msg, env.block.into())?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("sender", info.sender))
+}
+
+pub fn update_deposit_params(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_deposit: Option<Uint128>,
+    proposal_min_deposit: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    if let Some(proposal_deposit) = proposal_deposit {
+        cfg.proposal_deposit = proposal_deposit;
+    }
+    if let Some(proposal_min_deposit) = proposal_min_deposit {
+        cfg.proposal_min_deposit = proposal_min_deposit;
+    }
+    if cfg.proposal_min_deposit > cfg.proposal_deposit {
+        return Err(ContractError::InvalidDepositParams {});
+    }
+    cfg.validate(deps.api)?;
+
+    replace_config(deps.storage, &cfg, env.block.into())?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_deposit_params")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_deposit", cfg.proposal_deposit)
+        .add_attribute("proposal_min_deposit", cfg.proposal_min_deposit))
+}
+
+/// Saves `config`, keeping the replaced one around and dropping the oldest entry once full.
+/// Restricted fields (see `Config::field_thresholds`) need a strict enough calling proposal
+fn replace_config(
+    storage: &mut dyn Storage,
+    config: &Config,
+    block: BlockTime,
+) -> Result<(), ContractError> {
+    let previous = CONFIG.load(storage)?;
+    let actual = match CALLING_PROPOSAL_ID.may_load(storage)? {
+        Some(id) => PROPOSALS.load(storage, id)?.threshold,
+        None => Threshold {
+            threshold: Decimal::zero(),
+            quorum: Decimal::zero(),
+            ..Default::default()
+        },
+    };
+    if let Some((field, required)) = previous.unmet_field_threshold(config, &actual) {
+        return Err(ContractError::InsufficientThresholdForField {
+            field: format!("{:?}", field),
+            required_threshold: required.threshold,
+            required_quorum: required.quorum,
+            actual_threshold: actual.threshold,
+            actual_quorum: actual.quorum,
+        });
+    }
+
+    let version = CONFIG_VERSION.may_load(storage)?.unwrap_or_default() + 1;
+    CONFIG_HISTORY.save(storage, version, &(previous, block))?;
+    if version > CONFIG_HISTORY_LIMIT {
+        CONFIG_HISTORY.remove(storage, version - CONFIG_HISTORY_LIMIT);
+    }
+    CONFIG_VERSION.save(storage, &version)?;
+
+    Ok(CONFIG.save(storage, config)?)
+}
+
+pub fn update_staking_contract(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     new_staking_contract: Addr,
 ) -> Result<Response, ContractError> {
@@ -461,6 +1557,100 @@ pub fn update_staking_contract(
         .add_attribute("new_staking_contract", new_staking_contract))
 }
 
+pub fn upgrade_contract(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract: Addr,
+    new_code_id: u64,
+    migrate_msg: Binary,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let contract = deps.api.addr_validate(contract.as_str())?;
+
+    Ok(Response::new()
+        .add_message(WasmMsg::Migrate {
+            contract_addr: contract.to_string(),
+            new_code_id,
+            msg: migrate_msg,
+        })
+        .add_attribute("action", "upgrade_contract")
+        .add_attribute("contract", contract)
+        .add_attribute("new_code_id", new_code_id.to_string()))
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // the sender is the cw20 contract itself
+    if CONFIG.load(deps.storage)?.auto_register_cw20 {
+        deps.querier
+            .query_wasm_smart::<TokenInfoResponse>(&info.sender, &Cw20QueryMsg::TokenInfo {})
+            .map_err(|_| ContractError::InvalidCw20 {
+                addr: info.sender.to_string(),
+            })?;
+        let staking_contract = STAKING_CONTRACT.load(deps.storage)?;
+        add_treasury_token(
+            deps.storage,
+            &env,
+            &staking_contract,
+            &Denom::Cw20(info.sender.clone()),
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "receive_cw20")
+        .add_attribute("token", info.sender)
+        .add_attribute("sender", wrapper.sender)
+        .add_attribute("amount", wrapper.amount))
+}
+
+/// Registers `token` in `TREASURY_TOKENS`, for both `UpdateTokenList` and `Receive`.
+/// New tokens are rejected once the list holds `Config::max_treasury_tokens`
+fn add_treasury_token(
+    storage: &mut dyn Storage,
+    env: &Env,
+    staking_contract: &Addr,
+    token: &Denom,
+) -> Result<(), ContractError> {
+    let key = match token {
+        // Neither the DAO itself nor its staking contract are cw20 tokens
+        Denom::Cw20(cw20_addr)
+            if cw20_addr == &env.contract.address || cw20_addr == staking_contract =>
+        {
+            return Err(ContractError::InvalidTreasuryToken {
+                address: cw20_addr.to_string(),
+            });
+        }
+        Denom::Native(native_denom) => ("native", native_denom.as_str()),
+        Denom::Cw20(cw20_addr) => ("cw20", cw20_addr.as_str()),
+    };
+    if TREASURY_TOKENS.has(storage, key) {
+        return Ok(());
+    }
+
+    let max = CONFIG.load(storage)?.max_treasury_tokens;
+    let size = TREASURY_TOKENS
+        .keys(storage, None, None, Order::Ascending)
+        .count()
+        + 1;
+    if size > max as usize {
+        return Err(ContractError::OversizedRequest {
+            size: size as u64,
+            max: max as u64,
+        });
+    }
+
+    TREASURY_TOKENS.save(storage, key, &Empty {})?;
+    Ok(())
+}
+
 pub fn update_token_list(
     deps: DepsMut,
     env: Env,
@@ -482,17 +1672,7 @@ pub fn update_token_list(
         });
     }
 
-    for token in &to_add {
-        match token {
-            Denom::Native(native_denom) => {
-                TREASURY_TOKENS.save(deps.storage, ("native", native_denom.as_str()), &Empty {})?
-            }
-            Denom::Cw20(cw20_addr) => {
-                TREASURY_TOKENS.save(deps.storage, ("cw20", cw20_addr.as_str()), &Empty {})?
-            }
-        }
-    }
-
+    // removals go first so a full list can still swap tokens
     for token in &to_remove {
         match token {
             Denom::Native(native_denom) => {
@@ -504,16 +1684,102 @@ pub fn update_token_list(
         }
     }
 
+    let staking_contract = STAKING_CONTRACT.load(deps.storage)?;
+    for token in &to_add {
+        add_treasury_token(deps.storage, &env, &staking_contract, token)?;
+    }
+
     Ok(Response::new().add_attribute("action", "update_cw20_token_list"))
 }
 
+pub fn update_categories(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to_add: Vec<String>,
+    to_remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    // Only contract can call this method
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if to_add.len() + to_remove.len() > MAX_LIMIT as usize {
+        return Err(ContractError::OversizedRequest {
+            size: (to_add.len() + to_remove.len()) as u64,
+            max: MAX_LIMIT as u64,
+        });
+    }
+
+    for category in &to_add {
+        CATEGORIES.save(deps.storage, category, &Empty {})?;
+    }
+    // proposals keep a removed category, it just can't be used anymore
+    for category in &to_remove {
+        CATEGORIES.remove(deps.storage, category);
+    }
+
+    Ok(Response::new().add_attribute("action", "update_categories"))
+}
+
 #[cfg(test)]
 mod test {
-    use crate::state::Deposit;
+    use crate::state::{Deposit, ExecutePermission};
     use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Timestamp;
 
     use super::*;
 
+    fn mock_config() -> Config {
+        use cosmwasm_std::Decimal;
+        use cw_utils::Duration;
+
+        use crate::threshold::Threshold;
+
+        Config {
+            name: "dao".to_string(),
+            description: "desc".to_string(),
+            threshold: Threshold {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+                veto_threshold: Decimal::percent(33),
+                min_voters: None,
+                strict: false,
+            },
+            voting_period: Duration::Height(15),
+            deposit_period: Duration::Height(10),
+            proposal_deposit: Uint128::new(100),
+            proposal_min_deposit: Uint128::new(10),
+            high_participation_bonus: None,
+            auto_reap: false,
+            require_valid_link: false,
+            allow_deposit_top_up: false,
+            force_close_grace_period: Duration::Height(15),
+            max_query_limit: 30,
+            default_query_limit: 10,
+            execute_permission: ExecutePermission::Anyone,
+            execution_delay: None,
+            max_single_deposit: None,
+            min_distinct_depositors: None,
+            auto_scale_threshold: false,
+            total_token_supply: Uint128::zero(),
+            threshold_scaling_coefficient: Decimal::zero(),
+            bootstrap: None,
+            notifiers: vec![],
+            gov_token_decimals: None,
+            event_namespace: None,
+            field_thresholds: vec![],
+            auto_register_cw20: false,
+            execution_window: None,
+            recheck_quorum_on_execute: false,
+            voting_stake_age: 0,
+            reserves: vec![],
+            confiscation_policy: Default::default(),
+            record_tally_history: false,
+            max_treasury_tokens: crate::DEFAULT_MAX_TREASURY_TOKENS,
+        }
+    }
+
     #[test]
     fn check_paused() {
         let mut storage = MockStorage::new();
@@ -541,7 +1807,37 @@ mod test {
             },
         )
         .unwrap_err();
-        assert_eq!(err, ContractError::Paused {})
+        assert_eq!(
+            err,
+            ContractError::PausedUntil {
+                expiration: Expiration::AtHeight(10)
+            }
+        );
+
+        let until = Timestamp::from_seconds(1_000);
+        DAO_PAUSED
+            .save(&mut storage, &Expiration::AtTime(until))
+            .unwrap();
+
+        let err = super::check_paused(
+            &storage,
+            &BlockInfo {
+                height: 11,
+                time: Timestamp::from_seconds(999),
+                chain_id: "mock_chain".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::PausedUntil {
+                expiration: Expiration::AtTime(until)
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "DAO is paused until expiration time: 1000.000000000"
+        );
     }
 
     #[test]
@@ -590,21 +1886,25 @@ mod test {
         let depositor = Addr::unchecked("depositor");
 
         // initial
-        super::create_deposit(&mut storage, 1, &depositor, &Uint128::from(10u128)).unwrap();
+        super::create_deposit(&mut storage, 1, &depositor, &Uint128::from(10u128), "denom")
+            .unwrap();
         assert_eq!(
             DEPOSITS.load(&storage, (1, depositor.clone())).unwrap(),
             Deposit {
                 amount: Uint128::from(10u128),
+                denom: "denom".to_string(),
                 claimed: false
             },
         );
         assert!(IDX_DEPOSITS_BY_DEPOSITOR.has(&storage, (depositor.clone(), 1)));
 
-        super::create_deposit(&mut storage, 1, &depositor, &Uint128::from(10u128)).unwrap();
+        super::create_deposit(&mut storage, 1, &depositor, &Uint128::from(10u128), "denom")
+            .unwrap();
         assert_eq!(
             DEPOSITS.load(&storage, (1, depositor.clone())).unwrap(),
             Deposit {
                 amount: Uint128::from(20u128),
+                denom: "denom".to_string(),
                 claimed: false
             },
         );
@@ -643,9 +1943,324 @@ mod test {
         super::create_proposal(&mut storage, 1, &proposer, &proposal).unwrap();
 
         proposal.proposer = Addr::unchecked("abuser");
-        super::update_proposal_status(&mut storage, 1, &mut proposal, Status::Passed).unwrap();
+        super::update_proposal_status(&mut storage, 1, &mut proposal, Status::Passed, 10).unwrap();
 
         assert_eq!(PROPOSALS.load(&storage, 1).unwrap().status, Status::Passed);
         assert_eq!(PROPOSALS.load(&storage, 1).unwrap().proposer, proposer);
+        assert_eq!(PROPOSALS.load(&storage, 1).unwrap().last_updated_height, 10);
+        assert!(IDX_PROPS_BY_UPDATE_HEIGHT.has(&storage, (10, 1)));
+        assert!(!IDX_PROPS_BY_UPDATE_HEIGHT.has(&storage, (0, 1)));
+    }
+
+    #[test]
+    fn reconcile_indexes() {
+        use std::marker::PhantomData;
+
+        use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier};
+        use cosmwasm_std::OwnedDeps;
+        use osmo_bindings::OsmosisQuery;
+
+        use crate::msg::{DepositsQueryOption, IndexSweep, ProposalsQueryOption};
+        use crate::query;
+
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: MockQuerier::<OsmosisQuery>::new(&[]),
+            custom_query_type: PhantomData,
+        };
+        let env = mock_env();
+
+        CONFIG.save(&mut deps.storage, &mock_config()).unwrap();
+
+        let proposer = Addr::unchecked("proposer");
+        let depositor = Addr::unchecked("depositor");
+        for id in 1..=3 {
+            let proposal = Proposal {
+                proposer: proposer.clone(),
+                status: Status::Open,
+                ..Default::default()
+            };
+            super::create_proposal(&mut deps.storage, id, &proposer, &proposal).unwrap();
+            super::create_deposit(
+                &mut deps.storage,
+                id,
+                &depositor,
+                &Uint128::new(10),
+                "denom",
+            )
+            .unwrap();
+        }
+
+        // corrupt indexes
+        IDX_PROPS_BY_STATUS.remove(&mut deps.storage, (Status::Open as u8, 2));
+        IDX_PROPS_BY_STATUS
+            .save(&mut deps.storage, (Status::Rejected as u8, 2), &Empty {})
+            .unwrap();
+        IDX_PROPS_BY_PROPOSER.remove(&mut deps.storage, (proposer.clone(), 3));
+        IDX_DEPOSITS_BY_DEPOSITOR.remove(&mut deps.storage, (depositor.clone(), 1));
+        IDX_PROPS_BY_UPDATE_HEIGHT
+            .save(&mut deps.storage, (99, 1), &Empty {})
+            .unwrap();
+        IDX_PROPS_BY_DEPOSIT
+            .save(&mut deps.storage, (500, 2), &Empty {})
+            .unwrap();
+        IDX_PROPS_BY_CATEGORY
+            .save(&mut deps.storage, ("grants".to_string(), 3), &Empty {})
+            .unwrap();
+
+        let find_by_status = |deps: &OwnedDeps<_, _, _, OsmosisQuery>, status| {
+            query::proposals(
+                deps.as_ref(),
+                env.clone(),
+                ProposalsQueryOption::FindByStatus {
+                    status,
+                    cursor: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+            .proposals
+            .into_iter()
+            .map(|p| p.id)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(find_by_status(&deps, Status::Open), vec![1, 3]);
+        assert_eq!(find_by_status(&deps, Status::Rejected), vec![2]);
+
+        // only the DAO itself can reconcile
+        let err = super::reconcile_indexes(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("stranger", &[]),
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // paginated
+        let dao = mock_info(env.contract.address.as_str(), &[]);
+        super::reconcile_indexes(deps.as_mut(), env.clone(), dao.clone(), None, Some(2)).unwrap();
+        super::reconcile_indexes(deps.as_mut(), env.clone(), dao.clone(), Some(2), Some(2))
+            .unwrap();
+
+        assert_eq!(find_by_status(&deps, Status::Open), vec![1, 2, 3]);
+        assert!(find_by_status(&deps, Status::Rejected).is_empty());
+
+        // stale entries are swept by paging over the index itself
+        let sweep = |deps: &mut OwnedDeps<_, _, _, OsmosisQuery>, info, index| {
+            super::sweep_stale_index(deps.as_mut(), env.clone(), info, index, Some(2))
+        };
+        let err = sweep(
+            &mut deps,
+            mock_info("stranger", &[]),
+            IndexSweep::UpdateHeight { start_after: None },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let attr = |resp: &Response, key: &str| {
+            resp.attributes
+                .iter()
+                .find(|a| a.key == key)
+                .unwrap()
+                .value
+                .clone()
+        };
+        let resp = sweep(
+            &mut deps,
+            dao.clone(),
+            IndexSweep::UpdateHeight { start_after: None },
+        )
+        .unwrap();
+        assert_eq!(attr(&resp, "removed"), "0");
+        assert_eq!(attr(&resp, "last_key"), "0,2");
+        let resp = sweep(
+            &mut deps,
+            dao.clone(),
+            IndexSweep::UpdateHeight {
+                start_after: Some((0, 2)),
+            },
+        )
+        .unwrap();
+        assert_eq!(attr(&resp, "removed"), "1");
+        let resp = sweep(
+            &mut deps,
+            dao.clone(),
+            IndexSweep::Deposit { start_after: None },
+        )
+        .unwrap();
+        assert_eq!(attr(&resp, "last_key"), "0,2");
+        let resp = sweep(
+            &mut deps,
+            dao.clone(),
+            IndexSweep::Deposit {
+                start_after: Some((Uint128::zero(), 2)),
+            },
+        )
+        .unwrap();
+        assert_eq!(attr(&resp, "removed"), "1");
+
+        let resp = sweep(
+            &mut deps,
+            dao.clone(),
+            IndexSweep::Category { start_after: None },
+        )
+        .unwrap();
+        assert_eq!(attr(&resp, "removed"), "1");
+        assert_eq!(attr(&resp, "last_key"), "none");
+
+        // entries under outdated keys are gone, the current ones stay
+        assert!(!IDX_PROPS_BY_UPDATE_HEIGHT.has(&deps.storage, (99, 1)));
+        assert!(IDX_PROPS_BY_UPDATE_HEIGHT.has(&deps.storage, (0, 1)));
+        assert!(!IDX_PROPS_BY_DEPOSIT.has(&deps.storage, (500, 2)));
+        assert!(IDX_PROPS_BY_DEPOSIT.has(&deps.storage, (0, 2)));
+        assert!(!IDX_PROPS_BY_CATEGORY.has(&deps.storage, ("grants".to_string(), 3)));
+
+        let by_proposer = query::proposals(
+            deps.as_ref(),
+            env.clone(),
+            ProposalsQueryOption::FindByProposer {
+                proposer,
+                cursor: None,
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(by_proposer.proposals.len(), 3);
+
+        let by_depositor = query::deposits(
+            deps.as_ref(),
+            DepositsQueryOption::FindByDepositor {
+                depositor: depositor.to_string(),
+                start: None,
+            },
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(by_depositor.deposits.len(), 3);
+    }
+
+    #[test]
+    fn deposit_refunds_in_recorded_denom() {
+        use std::marker::PhantomData;
+
+        use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier};
+        use cosmwasm_std::OwnedDeps;
+        use osmo_bindings::OsmosisQuery;
+
+        use crate::msg::MigrateMsg;
+
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: MockQuerier::<OsmosisQuery>::new(&[]),
+            custom_query_type: PhantomData,
+        };
+        let proposer = Addr::unchecked("proposer");
+        let proposal = Proposal {
+            deposit_claimable: true,
+            ..Default::default()
+        };
+        super::create_proposal(&mut deps.storage, 1, &proposer, &proposal).unwrap();
+        super::create_deposit(&mut deps.storage, 1, &proposer, &Uint128::new(10), "old").unwrap();
+        // stored before denoms were tracked
+        DEPOSITS
+            .save(
+                &mut deps.storage,
+                (1, Addr::unchecked("legacy")),
+                &Deposit {
+                    amount: Uint128::new(5),
+                    denom: "".to_string(),
+                    claimed: false,
+                },
+            )
+            .unwrap();
+        GOV_TOKEN
+            .save(&mut deps.storage, &"old".to_string())
+            .unwrap();
+        crate::contract::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            DEPOSITS
+                .load(&deps.storage, (1, Addr::unchecked("legacy")))
+                .unwrap()
+                .denom,
+            "old"
+        );
+
+        // the gov token changes while the deposits are outstanding
+        GOV_TOKEN
+            .save(&mut deps.storage, &"new".to_string())
+            .unwrap();
+        for (depositor, amount) in [("proposer", 10), ("legacy", 5)] {
+            let resp =
+                super::claim_deposit(deps.as_mut(), mock_env(), mock_info(depositor, &[]), 1)
+                    .unwrap();
+            assert_eq!(
+                resp.messages[0].msg,
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: depositor.to_string(),
+                    amount: coins(amount, "old"),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn confiscation_in_recorded_denom() {
+        let mut storage = MockStorage::default();
+        CONFIG
+            .save(
+                &mut storage,
+                &Config {
+                    confiscation_policy: ConfiscationPolicy::RewardStakers,
+                    ..mock_config()
+                },
+            )
+            .unwrap();
+        STAKING_CONTRACT
+            .save(&mut storage, &Addr::unchecked("stake"))
+            .unwrap();
+        GOV_TOKEN.save(&mut storage, &"old".to_string()).unwrap();
+
+        let proposer = Addr::unchecked("proposer");
+        super::create_proposal(&mut storage, 1, &proposer, &Proposal::default()).unwrap();
+        super::create_deposit(&mut storage, 1, &proposer, &Uint128::new(10), "old").unwrap();
+
+        // the gov token changes while the deposit is outstanding
+        GOV_TOKEN.save(&mut storage, &"new".to_string()).unwrap();
+        super::create_deposit(
+            &mut storage,
+            1,
+            &Addr::unchecked("late"),
+            &Uint128::new(4),
+            "new",
+        )
+        .unwrap();
+
+        let msgs = super::record_confiscation(&mut storage, 1).unwrap();
+        let funds = msgs
+            .iter()
+            .map(|msg| match &msg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => funds.clone(),
+                msg => panic!("unexpected message {:?}", msg),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(funds, vec![coins(4, "new"), coins(10, "old")]);
+        for (denom, amount) in [("new", 4), ("old", 10)] {
+            assert_eq!(
+                CONFISCATED_TOTAL.load(&storage, denom).unwrap(),
+                Uint128::new(amount)
+            );
+            assert_eq!(
+                ESCROWED_DEPOSITS.load(&storage, denom).unwrap(),
+                Uint128::zero()
+            );
+        }
     }
 }
@@ -1,13 +1,13 @@
 use cosmwasm_std::{
-    to_binary, Addr, BlockInfo, CosmosMsg, Decimal, Env, MessageInfo, QuerierWrapper, StdError,
-    StdResult, Uint128, WasmMsg,
+    coins, from_binary, to_binary, Addr, BankMsg, BlockInfo, Coin, CosmosMsg, Env, MessageInfo,
+    QuerierWrapper, StdError, StdResult, Uint128, WasmMsg,
 };
-use cw20::Cw20ExecuteMsg;
+use cw20::{Cw20ExecuteMsg, Denom};
 use cw_utils::{Duration, Expiration};
 use osmo_bindings::{OsmosisMsg, OsmosisQuery};
 
 use crate::msg::ProposalResponse;
-use crate::state::{BlockTime, Proposal, STAKING_CONTRACT};
+use crate::state::{BlockTime, Config, Proposal, ESCROWED_DEPOSITS, GOV_TOKEN, STAKING_CONTRACT};
 use crate::ContractError;
 
 /// type aliases
@@ -16,6 +16,20 @@ pub type SubMsg = cosmwasm_std::SubMsg<OsmosisMsg>;
 pub type Deps<'a> = cosmwasm_std::Deps<'a, OsmosisQuery>;
 pub type DepsMut<'a> = cosmwasm_std::DepsMut<'a, OsmosisQuery>;
 
+/// Prefixes the `action` attribute with `namespace`, if any
+pub fn namespace_action(mut resp: Response, namespace: Option<&str>) -> Response {
+    if let Some(namespace) = namespace {
+        for attr in resp
+            .attributes
+            .iter_mut()
+            .filter(|attr| attr.key == "action")
+        {
+            attr.value = format!("{}/{}", namespace, attr.value);
+        }
+    }
+    resp
+}
+
 pub fn duration_to_expiry(block: &BlockTime, period: &Duration) -> Expiration {
     match period {
         Duration::Height(height) => Expiration::AtHeight(block.height + height),
@@ -23,6 +37,21 @@ pub fn duration_to_expiry(block: &BlockTime, period: &Duration) -> Expiration {
     }
 }
 
+/// Permissive uri check - accepts `http://`, `https://` and `ipfs://` with a non-empty host
+pub fn is_valid_link(link: &str) -> bool {
+    let rest = ["http://", "https://", "ipfs://"]
+        .iter()
+        .find_map(|scheme| link.strip_prefix(scheme));
+
+    match rest {
+        Some(rest) => {
+            let host = rest.split(['/', '?', '#']).next();
+            matches!(host, Some(host) if !host.is_empty() && !host.contains(char::is_whitespace))
+        }
+        None => false,
+    }
+}
+
 pub fn get_deposit_message(
     env: &Env,
     info: &MessageInfo,
@@ -81,6 +110,151 @@ pub fn get_config(deps: Deps) -> StdResult<ion_stake::msg::GetConfigResponse> {
     Ok(res)
 }
 
+/// Amount of `denom` the DAO pays out by dispatching `msgs`: bank sends, funds attached to
+/// wasm calls and cw20 transfers, sends and burns
+pub fn proposal_outflow(msgs: &[CosmosMsg<OsmosisMsg>], denom: &Denom) -> Uint128 {
+    let native = |coins: &[Coin], denom: &str| -> Uint128 {
+        coins
+            .iter()
+            .filter(|coin| coin.denom == denom)
+            .map(|coin| coin.amount)
+            .sum()
+    };
+
+    msgs.iter()
+        .map(|msg| match (msg, denom) {
+            (CosmosMsg::Bank(BankMsg::Send { amount, .. }), Denom::Native(denom)) => {
+                native(amount, denom)
+            }
+            (CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }), Denom::Native(denom))
+            | (CosmosMsg::Wasm(WasmMsg::Instantiate { funds, .. }), Denom::Native(denom)) => {
+                native(funds, denom)
+            }
+            (
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }),
+                Denom::Cw20(token),
+            ) if contract_addr == token.as_str() => match from_binary(msg) {
+                Ok(Cw20ExecuteMsg::Transfer { amount, .. })
+                | Ok(Cw20ExecuteMsg::Send { amount, .. })
+                | Ok(Cw20ExecuteMsg::Burn { amount }) => amount,
+                _ => Uint128::zero(),
+            },
+            _ => Uint128::zero(),
+        })
+        .sum()
+}
+
+/// Balance of `denom` held by `address`, native or cw20
+pub fn get_denom_balance(
+    querier: QuerierWrapper<OsmosisQuery>,
+    address: &Addr,
+    denom: &Denom,
+) -> StdResult<Uint128> {
+    match denom {
+        Denom::Native(denom) => Ok(querier.query_balance(address, denom)?.amount),
+        Denom::Cw20(token) => {
+            let balance: cw20::BalanceResponse = querier.query_wasm_smart(
+                token,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )?;
+            Ok(balance.balance)
+        }
+    }
+}
+
+/// Treasury balance of `denom` that isn't owed to depositors, see `ESCROWED_DEPOSITS`
+pub fn spendable_balance(deps: Deps, treasury: &Addr, denom: &str) -> StdResult<Uint128> {
+    let held = deps.querier.query_balance(treasury, denom)?.amount;
+    let escrowed = ESCROWED_DEPOSITS
+        .may_load(deps.storage, denom)?
+        .unwrap_or_default();
+    Ok(held.saturating_sub(escrowed))
+}
+
+/// `Config::high_participation_bonus` owed to the proposer of an executed proposal
+pub enum ParticipationBonus {
+    NotEarned,
+    /// Earned, but the treasury can't spare it after the proposal's own spends without dipping
+    /// into escrowed deposits or a reserve. Skipped, a reward must never block an execution
+    Unfunded(Uint128),
+    Paid(Uint128, CosmosMsg<OsmosisMsg>),
+}
+
+impl ParticipationBonus {
+    pub fn msgs(&self) -> Vec<CosmosMsg<OsmosisMsg>> {
+        match self {
+            ParticipationBonus::Paid(_, msg) => vec![msg.clone()],
+            _ => vec![],
+        }
+    }
+}
+
+/// Pays the bonus if the proposal's turnout earned it and the treasury can spare it
+pub fn participation_bonus(
+    deps: Deps,
+    treasury: &Addr,
+    cfg: &Config,
+    prop: &Proposal,
+) -> StdResult<ParticipationBonus> {
+    let bonus = match cfg.high_participation_bonus {
+        Some((quorum, bonus)) if !bonus.is_zero() && prop.quorum() >= quorum => bonus,
+        _ => return Ok(ParticipationBonus::NotEarned),
+    };
+    let denom = GOV_TOKEN.load(deps.storage)?;
+    let msg: CosmosMsg<OsmosisMsg> = BankMsg::Send {
+        to_address: prop.proposer.to_string(),
+        amount: coins(bonus.u128(), &denom),
+    }
+    .into();
+
+    let spent = proposal_outflow(&prop.msgs, &Denom::Native(denom.clone())) + bonus;
+    if spendable_balance(deps, treasury, &denom)? < spent {
+        return Ok(ParticipationBonus::Unfunded(bonus));
+    }
+    let outflow = [prop.msgs.as_slice(), std::slice::from_ref(&msg)].concat();
+    match check_reserves(deps, treasury, &cfg.reserves, &outflow) {
+        Ok(()) => Ok(ParticipationBonus::Paid(bonus, msg)),
+        Err(ContractError::Std(err)) => Err(err),
+        Err(_) => Ok(ParticipationBonus::Unfunded(bonus)),
+    }
+}
+
+/// Fails with `ReserveViolation` if executing `msgs` would leave less than a configured
+/// reserve in the treasury at `address`. Escrowed deposits don't count towards a reserve
+pub fn check_reserves(
+    deps: Deps,
+    address: &Addr,
+    reserves: &[(Denom, Uint128)],
+    msgs: &[CosmosMsg<OsmosisMsg>],
+) -> Result<(), ContractError> {
+    for (denom, reserve) in reserves {
+        let outflow = proposal_outflow(msgs, denom);
+        if outflow.is_zero() {
+            continue;
+        }
+        let held = match denom {
+            Denom::Native(denom) => spendable_balance(deps, address, denom)?,
+            Denom::Cw20(_) => get_denom_balance(deps.querier, address, denom)?,
+        };
+        let remaining = held.saturating_sub(outflow);
+        if remaining < *reserve {
+            return Err(ContractError::ReserveViolation {
+                denom: match denom {
+                    Denom::Native(denom) => denom.clone(),
+                    Denom::Cw20(token) => token.to_string(),
+                },
+                reserve: *reserve,
+                remaining,
+            });
+        }
+    }
+    Ok(())
+}
+
 pub fn get_voting_power_at_height(
     querier: QuerierWrapper<OsmosisQuery>,
     staking_contract: Addr,
@@ -98,6 +272,35 @@ pub fn get_voting_power_at_height(
     Ok(balance.balance)
 }
 
+pub fn get_total_staked_at_height(
+    querier: QuerierWrapper<OsmosisQuery>,
+    staking_contract: Addr,
+    height: u64,
+) -> StdResult<Uint128> {
+    let total: ion_stake::msg::TotalStakedAtHeightResponse = querier.query_wasm_smart(
+        staking_contract,
+        &ion_stake::msg::QueryMsg::TotalStakedAtHeight {
+            height: Some(height),
+        },
+    )?;
+    Ok(total.total)
+}
+
+/// Whether `prop` still passes once `total_weight` is read back from the staking contract,
+/// for `Config::recheck_quorum_on_execute`
+pub fn passes_rechecked_quorum(deps: Deps, prop: &Proposal) -> StdResult<bool> {
+    let total_weight = get_total_staked_at_height(
+        deps.querier,
+        STAKING_CONTRACT.load(deps.storage)?,
+        prop.eligibility_height(),
+    )?;
+    let rechecked = Proposal {
+        total_weight,
+        ..prop.clone()
+    };
+    Ok(rechecked.is_passed())
+}
+
 pub fn proposal_to_response(
     block: &BlockInfo,
     id: u64,
@@ -106,11 +309,7 @@ pub fn proposal_to_response(
     let status = prop.current_status(block);
     let total_weight = prop.total_weight;
     let total_votes = prop.votes.total();
-    let quorum = if total_weight.is_zero() {
-        Decimal::zero()
-    } else {
-        Decimal::from_ratio(total_votes, total_weight)
-    };
+    let quorum = prop.quorum();
 
     ProposalResponse {
         id,
@@ -126,8 +325,12 @@ pub fn proposal_to_response(
         deposit_ends_at: prop.deposit_ends_at,
         vote_starts_at: prop.vote_starts_at,
         vote_ends_at: prop.vote_ends_at,
+        executed_at: prop.executed_at,
+        last_updated_height: prop.last_updated_height,
 
+        vote_ratios: prop.votes.ratios(),
         votes: prop.votes,
+        voter_count: prop.voter_count,
         quorum,
         threshold: prop.threshold,
         total_votes,
@@ -135,6 +338,8 @@ pub fn proposal_to_response(
         total_deposit: prop.total_deposit,
 
         deposit_claimable: prop.deposit_claimable,
+        category: prop.category,
+        cosponsor_count: prop.cosponsor_count,
     }
 }
 
@@ -12,10 +12,26 @@ pub type Deps<'a> = cosmwasm_std::Deps<'a, OsmosisQuery>;
 pub type DepsMut<'a> = cosmwasm_std::DepsMut<'a, OsmosisQuery>;
 pub type QuerierWrapper<'a> = cosmwasm_std::QuerierWrapper<'a, OsmosisQuery>;
 
-// Settings for pagination
+// Default settings for pagination (overridable per DAO through `Config`)
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
 
+// Maximum number of expired pending proposals inspected per `propose` call
+const AUTO_REAP_LIMIT: usize = 5;
+
+// Voters listed per option by `ProposalVotesBreakdown` (default, max)
+const DEFAULT_BREAKDOWN_TOP: u32 = 3;
+const MAX_BREAKDOWN_TOP: u32 = 5;
+
+// Proposals returned per `ByDeposit` page, whatever the configured query limit
+const MAX_BY_DEPOSIT_LIMIT: usize = 10;
+
+// Longest rationale a voter may attach to a ballot, in bytes
+const MAX_RATIONALE_LENGTH: usize = 512;
+
+// Treasury tokens a DAO may list unless it configures another cap
+const DEFAULT_MAX_TREASURY_TOKENS: u32 = 100;
+
 pub mod contract;
 mod error;
 mod execute;
@@ -24,6 +40,7 @@ pub mod msg;
 pub mod proposal;
 pub mod query;
 pub mod state;
+pub mod templates;
 pub mod threshold;
 
 #[cfg(test)]
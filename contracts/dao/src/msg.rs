@@ -1,15 +1,16 @@
 use std::fmt;
 
-use cosmwasm_std::{Addr, CosmosMsg, Decimal, Empty, Order, Uint128};
-use cw20::{Balance, Denom};
+use cosmwasm_std::{Addr, Binary, Coin, CosmosMsg, Decimal, Empty, GovMsg, Order, Uint128};
+use cw2::ContractVersion;
+use cw20::{Balance, Cw20ReceiveMsg, Denom};
 use cw3::{Status, Vote};
 use cw_utils::{Duration, Expiration};
-use osmo_bindings::OsmosisMsg;
+use osmo_bindings::{OsmosisMsg, SwapAmount};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::proposal::{BlockTime, Votes};
-use crate::state::Config;
+use crate::proposal::{BlockTime, VoteRatios, Votes};
+use crate::state::{Bootstrap, Config, ConfigFieldKey, ConfiscationPolicy, ExecutePermission};
 use crate::threshold::Threshold;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -30,6 +31,105 @@ pub struct InstantiateMsg {
     /// Deposit required to make a proposal
     pub proposal_deposit_amount: Uint128,
     pub proposal_deposit_min_amount: Uint128,
+
+    /// Bonus paid to the proposer when an executed proposal reached (quorum, bonus)
+    pub high_participation_bonus: Option<(Decimal, Uint128)>,
+
+    /// Reject expired pending proposals while submitting new ones
+    #[serde(default)]
+    pub auto_reap: bool,
+
+    /// Require proposal links to be http(s) or ipfs uris
+    #[serde(default)]
+    pub require_valid_link: bool,
+
+    /// Accept additional deposits on proposals that are already open
+    #[serde(default)]
+    pub allow_deposit_top_up: bool,
+
+    /// Wait after voting ends before `ForceClose` is allowed. Defaults to `voting_period`
+    pub force_close_grace_period: Option<Duration>,
+
+    /// Max page size for list queries. Defaults to 30
+    pub max_query_limit: Option<u32>,
+    /// Page size used when a list query omits `limit`. Defaults to 10
+    pub default_query_limit: Option<u32>,
+
+    /// Who may execute passed proposals. Defaults to anyone
+    #[serde(default)]
+    pub execute_permission: ExecutePermission,
+
+    /// Timelock after voting ends before a passed proposal can be executed
+    pub execution_delay: Option<Duration>,
+
+    /// Cap on a single deposit contribution, so no one can open a proposal alone
+    pub max_single_deposit: Option<Uint128>,
+
+    /// Distinct depositors required, besides the full deposit, to open voting
+    pub min_distinct_depositors: Option<u32>,
+
+    /// Raise the threshold of new proposals while little of `total_token_supply` is staked
+    #[serde(default)]
+    pub auto_scale_threshold: bool,
+    #[serde(default)]
+    pub total_token_supply: Uint128,
+    #[serde(default)]
+    pub threshold_scaling_coefficient: Decimal,
+
+    /// Quorum used instead of `threshold.quorum` by proposals created before `until`
+    pub bootstrap: Option<Bootstrap>,
+
+    /// Contracts told about open proposals whose voting period ended, see [NotifierMsg]
+    #[serde(default)]
+    pub notifiers: Vec<Addr>,
+
+    /// Decimals of the (native) gov token, only reported back by `GetConfig` for display
+    #[serde(default)]
+    pub gov_token_decimals: Option<u8>,
+
+    /// Prefix of every emitted `action` attribute, e.g. `mydao` gives `mydao/propose`
+    #[serde(default)]
+    pub event_namespace: Option<String>,
+
+    /// Minimum pass threshold and quorum of the proposal changing a given config field
+    #[serde(default)]
+    pub field_thresholds: Vec<(ConfigFieldKey, Threshold)>,
+
+    /// Register cw20 tokens in the treasury token list as they are sent to the DAO
+    #[serde(default)]
+    pub auto_register_cw20: bool,
+
+    /// Time a passed proposal stays executable after its execution delay, unlimited if unset
+    #[serde(default)]
+    pub execution_window: Option<Duration>,
+
+    /// Check a passed proposal against the staking total at its voting start before executing
+    #[serde(default)]
+    pub recheck_quorum_on_execute: bool,
+
+    /// Blocks voters must have staked before voting opens to get voting power
+    #[serde(default)]
+    pub voting_stake_age: u64,
+
+    /// Treasury balances proposals can't spend below
+    #[serde(default)]
+    pub reserves: Vec<(Denom, Uint128)>,
+
+    /// Keep confiscated deposits in the treasury or pay them out to stakers
+    #[serde(default)]
+    pub confiscation_policy: ConfiscationPolicy,
+
+    /// Record the tally of every block a proposal is voted in
+    #[serde(default)]
+    pub record_tally_history: bool,
+
+    /// Most tokens the treasury token list may hold
+    #[serde(default = "default_max_treasury_tokens")]
+    pub max_treasury_tokens: u32,
+}
+
+fn default_max_treasury_tokens() -> u32 {
+    crate::DEFAULT_MAX_TREASURY_TOKENS
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -52,12 +152,47 @@ pub struct ProposeMsg {
     pub link: String,
     pub description: String,
     pub msgs: Vec<CosmosMsg<OsmosisMsg>>,
+    /// Shortcut for mirroring an on-chain governance vote.
+    /// When set, `msgs` must be empty and is populated with this single message
+    #[serde(default)]
+    pub cosmos_gov_vote: Option<GovMsg>,
+    /// Proposal this one amends; its threshold is used instead of the configured one
+    #[serde(default)]
+    pub parent_proposal: Option<u64>,
+    /// Must be one of the categories added through `UpdateCategories`
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// Proposal deploying a sub-DAO, its only message is an `InstantiateSubDao` call to this DAO
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposeSubDaoMsg {
+    pub title: String,
+    pub link: String,
+    pub description: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Code of the sub-DAO, usually the one of this DAO
+    pub code_id: u64,
+    pub label: String,
+    pub instantiate_msg: InstantiateMsg,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct VoteMsg {
     pub proposal_id: u64,
     pub vote: Vote,
+    /// Why the voter voted this way, stored on the ballot
+    #[serde(default)]
+    pub rationale: Option<String>,
+}
+
+/// Sent to every `Config::notifiers` contract. A failing notifier is ignored
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifierMsg {
+    /// The voting period ended, but the proposal wasn't closed or executed yet
+    ProposalExpired { id: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -65,12 +200,22 @@ pub struct VoteMsg {
 pub enum ExecuteMsg {
     /// Makes a new proposal
     Propose(ProposeMsg),
+    /// Makes a new proposal deploying a sub-DAO, recorded in `SubDaos` once executed
+    ProposeSubDao(Box<ProposeSubDaoMsg>),
     Deposit {
         proposal_id: u64,
     },
     ClaimDeposit {
         proposal_id: u64,
     },
+    /// Back a pending or open proposal as a co-sponsor, only stakers can
+    Cosponsor {
+        proposal_id: u64,
+    },
+    /// Take back your deposit from a proposal that is still pending
+    WithdrawDeposit {
+        proposal_id: u64,
+    },
     /// Vote on an open proposal
     Vote(VoteMsg),
     /// Execute a passed proposal
@@ -81,22 +226,92 @@ pub enum ExecuteMsg {
     Close {
         proposal_id: u64,
     },
+    /// Reject a proposal stuck past its grace period (can only be called by DAO contract)
+    ForceClose {
+        proposal_id: u64,
+    },
+    /// Reject a passed proposal during its execution delay (can only be called by DAO contract)
+    VetoExecution {
+        proposal_id: u64,
+    },
+    /// Refund a passed proposal left unexecuted past its execution window, without running it
+    ExpireExecution {
+        proposal_id: u64,
+    },
+    /// Persist the current status of the given proposals, fixing the status index
+    RefreshStatus {
+        proposal_ids: Vec<u64>,
+    },
+    /// Rebuild secondary indexes from the primary maps (can only be called by DAO contract)
+    ReconcileIndexes {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Drop a page of `index` entries keyed by a value their proposal no longer has, or of a
+    /// proposal that is gone (can only be called by DAO contract)
+    SweepStaleIndex {
+        index: IndexSweep,
+        limit: Option<u32>,
+    },
+    /// Deploy a cw3-fixed-multisig administered by the DAO (can only be called by DAO contract).
+    /// Its voters are fixed: changing them means migrating it or deploying a new one
+    InstantiateMultisig {
+        code_id: u64,
+        label: String,
+        members: Vec<(Addr, u64)>,
+        required_weight: u64,
+        max_voting_period: Duration,
+    },
+    /// Deploy a sub-DAO administered by the DAO (can only be called by DAO contract)
+    InstantiateSubDao {
+        code_id: u64,
+        label: String,
+        msg: Binary,
+    },
     /// Pauses DAO governance (can only be called by DAO contract)
     PauseDAO {
         expiration: Expiration,
     },
     /// Update DAO config (can only be called by DAO contract)
-    UpdateConfig(Config),
+    UpdateConfig(Box<Config>),
+    /// Update only the proposal deposit amounts (can only be called by DAO contract)
+    UpdateDepositParams {
+        proposal_deposit: Option<Uint128>,
+        proposal_min_deposit: Option<Uint128>,
+    },
+    /// cw20 `Send` hook, registers the token when `Config::auto_register_cw20` is on
+    Receive(Cw20ReceiveMsg),
     /// Updates token list
     UpdateTokenList {
         to_add: Vec<Denom>,
         to_remove: Vec<Denom>,
     },
+    /// Updates the proposal categories (can only be called by DAO contract)
+    UpdateCategories {
+        to_add: Vec<String>,
+        to_remove: Vec<String>,
+    },
     /// Update Staking Contract (can only be called by DAO contract)
     /// WARNING: this changes the contract controlling voting
     UpdateStakingContract {
         new_staking_contract: Addr,
     },
+    /// Migrate a contract administered by the DAO to new code (can only be called by DAO contract)
+    UpgradeContract {
+        contract: Addr,
+        new_code_id: u64,
+        migrate_msg: Binary,
+    },
+}
+
+/// Secondary index walked by `SweepStaleIndex`. `start_after` is the `last_key` attribute of
+/// the previous page
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexSweep {
+    UpdateHeight { start_after: Option<(u64, u64)> },
+    Deposit { start_after: Option<(Uint128, u64)> },
+    Category { start_after: Option<(String, u64)> },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -115,14 +330,44 @@ impl From<RangeOrder> for Order {
     }
 }
 
+/// Every option takes the `cursor` returned with the previous page, it replaces `start`
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ProposalsQueryOption {
-    FindByStatus { status: Status },
-    FindByProposer { proposer: Addr },
-    Everything {},
+    FindByStatus {
+        status: Status,
+        #[serde(default)]
+        cursor: Option<ProposalCursor>,
+    },
+    FindByProposer {
+        proposer: Addr,
+        #[serde(default)]
+        cursor: Option<ProposalCursor>,
+    },
+    FindByCategory {
+        category: String,
+        #[serde(default)]
+        cursor: Option<ProposalCursor>,
+    },
+    Everything {
+        #[serde(default)]
+        cursor: Option<ProposalCursor>,
+    },
+    /// Ranked by total deposit, ties by id. `order` is ignored and `start` is a proposal id.
+    /// Each deposit change rewrites the index entry, so pages hold at most 10 proposals.
+    /// A cursor keeps its place even if the deposit of the last proposal seen changed since
+    ByDeposit {
+        descending: bool,
+        #[serde(default)]
+        cursor: Option<ProposalCursor>,
+    },
 }
 
+/// Position of the last proposal of a page in the index being listed. Opaque, only meant to
+/// be passed back with the same query option
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProposalCursor(pub Binary);
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum DepositsQueryOption {
@@ -208,6 +453,21 @@ pub enum QueryMsg {
     /// ```
     Proposal { proposal_id: u64 },
 
+    /// # ProposalMaybe
+    ///
+    /// Returns Option<[ProposalResponse]>, `null` when no proposal has that id
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "proposal_maybe": {
+    ///     "proposal_id": 1
+    ///   }
+    /// }
+    /// ```
+    ProposalMaybe { proposal_id: u64 },
+
     /// # Proposals
     ///
     /// Returns [ProposalsResponse]
@@ -221,6 +481,7 @@ pub enum QueryMsg {
     ///       "find_by_status": { "status": "pending" | .. | "executed" }
     ///         | "find_by_proposer": { "proposer": "osmo1deadbeef" }
     ///         | "everything": {}
+    ///         | "by_deposit": { "descending": true }
     ///     },
     ///     "start"?: 10,
     ///     "limit": 30 | 10,
@@ -235,6 +496,93 @@ pub enum QueryMsg {
         order: Option<RangeOrder>,
     },
 
+    /// # AllProposalsStatusMap
+    ///
+    /// Only the id and current status of each proposal, for clients that just need to badge
+    /// them. Paged like [QueryMsg::Proposals] over every proposal.  
+    /// Returns [ProposalStatusMapResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "all_proposals_status_map": {
+    ///     "start"?: 10,
+    ///     "limit": 30 | 10,
+    ///     "order": "asc" | "desc"
+    ///   }
+    /// }
+    /// ```
+    AllProposalsStatusMap {
+        start: Option<u64>,
+        limit: Option<u32>,
+        order: Option<RangeOrder>,
+    },
+
+    /// # IndexDrift
+    ///
+    /// Proposals whose stored status (and so their `find_by_status` entry) lags behind
+    /// their current status, e.g. an open proposal whose voting period ended. `Close` or
+    /// `Execute` settles them. Stored pending proposals are listed before open ones.  
+    /// Returns [IndexDriftResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "index_drift": {
+    ///     "limit": 30 | 10
+    ///   }
+    /// }
+    /// ```
+    IndexDrift { limit: Option<u32> },
+
+    /// # Audit
+    ///
+    /// Recomputes the storage invariants from scratch and lists what breaks them, for a page
+    /// of proposals and their deposits. Meant for off-chain monitoring, continue from the
+    /// returned `last_id` until it comes back empty.  
+    /// Returns [AuditResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "audit": {
+    ///     "start_after": 10,
+    ///     "limit": 30
+    ///   }
+    /// }
+    /// ```
+    Audit {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// # ProposalsUpdatedAfter
+    ///
+    /// Proposals changed after `height`, oldest change first. Meant for indexers polling
+    /// for updates.  
+    /// A full page returns a `next_cursor`, pass it back to resume within the same height.  
+    /// Returns [ProposalsResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "proposals_updated_after": {
+    ///     "height": 12345,
+    ///     "cursor": null,
+    ///     "limit": 30 | 10
+    ///   }
+    /// }
+    /// ```
+    ProposalsUpdatedAfter {
+        height: u64,
+        cursor: Option<ProposalCursor>,
+        limit: Option<u32>,
+    },
+
     /// # ProposalCount
     ///
     /// Returns the number of proposals in the DAO (u64)
@@ -287,6 +635,24 @@ pub enum QueryMsg {
         order: Option<RangeOrder>,
     },
 
+    /// # ProposalVotesBreakdown
+    ///
+    /// Heaviest voters for each vote option. Only the first `max_query_limit` ballots are
+    /// scanned, `capped` tells whether some were left out.  
+    /// Returns [VotesBreakdownResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "proposal_votes_breakdown": {
+    ///     "proposal_id": 1,
+    ///     "top": 5 | 3
+    ///   }
+    /// }
+    /// ```
+    ProposalVotesBreakdown { proposal_id: u64, top: Option<u32> },
+
     /// # Deposit
     ///
     /// Queries single deposit info by proposal id & address of depositor.  
@@ -309,6 +675,7 @@ pub enum QueryMsg {
     /// Queries multiple deposits info by
     /// 1. proposal id
     /// 2. depositor address
+    ///
     /// Returns [DepositsResponse]
     ///
     /// ## Example
@@ -339,6 +706,377 @@ pub enum QueryMsg {
         limit: Option<u32>,
         order: Option<RangeOrder>,
     },
+
+    /// # TopDepositors
+    ///
+    /// Largest depositors of a proposal, biggest first. Only the first `max_query_limit`
+    /// deposits are scanned, `capped` tells whether some were left out.  
+    /// Returns [TopDepositorsResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "top_depositors": {
+    ///     "proposal_id": 1,
+    ///     "limit": 30 | 10
+    ///   }
+    /// }
+    /// ```
+    TopDepositors {
+        proposal_id: u64,
+        limit: Option<u32>,
+    },
+
+    /// # ClaimableDeposits
+    ///
+    /// Deposits of `depositor` that can be claimed back right now, as `(proposal_id, amount)`.  
+    /// Returns [ClaimableDepositsResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "claimable_deposits": {
+    ///     "depositor": "osmo1deadbeef"
+    ///   }
+    /// }
+    /// ```
+    ClaimableDeposits { depositor: String },
+
+    /// # ProposalDepositSummary
+    ///
+    /// Totals over the deposits of a proposal, without listing them.  
+    /// Returns [ProposalDepositSummaryResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "proposal_deposit_summary": {
+    ///     "proposal_id": 1
+    ///   }
+    /// }
+    /// ```
+    ProposalDepositSummary { proposal_id: u64 },
+
+    /// # EligibilityHeight
+    ///
+    /// Height at which voters must already have held their stake to vote on a proposal.  
+    /// Returns [EligibilityHeightResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "eligibility_height": {
+    ///     "proposal_id": 1
+    ///   }
+    /// }
+    /// ```
+    EligibilityHeight { proposal_id: u64 },
+
+    /// # ProposalParticipationByHeight
+    ///
+    /// Turnout of a proposal after each block it was voted in, oldest first. Empty unless
+    /// `Config.record_tally_history` is set.  
+    /// Returns [ProposalParticipationResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "proposal_participation_by_height": {
+    ///     "proposal_id": 1,
+    ///     "start_after": 12345,
+    ///     "limit": 10
+    ///   }
+    /// }
+    /// ```
+    ProposalParticipationByHeight {
+        proposal_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// # DepositShortfall
+    ///
+    /// Queries how much deposit is still required to open a pending proposal.  
+    /// Returns [DepositShortfallResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "deposit_shortfall": {
+    ///     "proposal_id": 1
+    ///   }
+    /// }
+    /// ```
+    DepositShortfall { proposal_id: u64 },
+
+    /// # SelfStake
+    ///
+    /// Queries the DAO's own staked balance in the staking contract.  
+    /// Returns [ion_stake::msg::StakedBalanceAtHeightResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "self_stake": {}
+    /// }
+    /// ```
+    SelfStake {},
+
+    /// # GovernanceStats
+    ///
+    /// Aggregated metrics over executed & rejected proposals.  
+    /// Returns [GovernanceStatsResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "governance_stats": {}
+    /// }
+    /// ```
+    GovernanceStats {},
+
+    /// # DaoStats
+    ///
+    /// When the DAO was created and how many proposals it executed / rejected so far.  
+    /// Returns [DaoStatsResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "dao_stats": {}
+    /// }
+    /// ```
+    DaoStats {},
+
+    /// # ConfiscatedTotals
+    ///
+    /// Proposal deposits confiscated so far, per denom.  
+    /// Returns [ConfiscatedTotalsResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "confiscated_totals": {}
+    /// }
+    /// ```
+    ConfiscatedTotals {},
+
+    /// # ProposalStatusAt
+    ///
+    /// Status of a proposal as of a historical block height.  
+    /// Block time is interpolated between proposal submission and the current block.  
+    /// Returns [ProposalStatusAtResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "proposal_status_at": {
+    ///     "proposal_id": 1,
+    ///     "height": 12345
+    ///   }
+    /// }
+    /// ```
+    ProposalStatusAt { proposal_id: u64, height: u64 },
+
+    /// # ProposalTimeline
+    ///
+    /// Milestones of a proposal in chronological order: `created`, `opened`,
+    /// `passed` / `rejected` and `executed`.  
+    /// The outcome is dated at the end of the voting (or deposit) period, the missing half of
+    /// that block is interpolated like in `ProposalStatusAt`. Rejections by `ForceClose` or
+    /// `VetoExecution` are dated at the block they happened in.  
+    /// Returns [ProposalTimelineResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "proposal_timeline": {
+    ///     "proposal_id": 1
+    ///   }
+    /// }
+    /// ```
+    ProposalTimeline { proposal_id: u64 },
+
+    /// # VotingPowers
+    ///
+    /// Voting power of each address at the proposal's voting start height.  
+    /// Returns [ion_stake::msg::StakedBalancesAtHeightResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "voting_powers": {
+    ///     "proposal_id": 1,
+    ///     "addresses": ["osmo1deadbeef"]
+    ///   }
+    /// }
+    /// ```
+    VotingPowers {
+        proposal_id: u64,
+        addresses: Vec<String>,
+    },
+
+    /// # ListMultisigs
+    ///
+    /// Multisigs deployed by the DAO through `InstantiateMultisig`, by label.  
+    /// Returns [ListMultisigsResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "list_multisigs": {
+    ///     "start_after": "ops",
+    ///     "limit": 10
+    ///   }
+    /// }
+    /// ```
+    ListMultisigs {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// # Cosponsors
+    ///
+    /// Co-sponsors of a proposal, by address.  
+    /// Returns [CosponsorsResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "cosponsors": {
+    ///     "proposal_id": 1,
+    ///     "start_after": "osmo1deadbeef",
+    ///     "limit": 10
+    ///   }
+    /// }
+    /// ```
+    Cosponsors {
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// # SubDaos
+    ///
+    /// Sub-DAOs deployed through `ProposeSubDao`, by address.  
+    /// Returns [SubDaosResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "sub_daos": {
+    ///     "start_after": "osmo1deadbeef",
+    ///     "limit": 10
+    ///   }
+    /// }
+    /// ```
+    SubDaos {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// # ExpirationStatus
+    ///
+    /// Compares an expiration against the current block.  
+    /// Returns [ExpirationStatusResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "expiration_status": {
+    ///     "expiration": {
+    ///       "at_height": 12345
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    ExpirationStatus { expiration: Expiration },
+
+    /// # Version
+    ///
+    /// cw2 contract versions of the DAO and its staking contract.  
+    /// Returns [VersionResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "version": {}
+    /// }
+    /// ```
+    Version {},
+
+    /// # ConfigHistory
+    ///
+    /// Configs replaced by `UpdateConfig`, oldest first. Only the last 100 are kept.  
+    /// Returns [ConfigHistoryResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "config_history": {
+    ///     "start_after": 3,
+    ///     "limit": 10
+    ///   }
+    /// }
+    /// ```
+    ConfigHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// # SimulateOsmosisExecution
+    ///
+    /// Estimates the cost of executing a proposal. Osmosis swaps are priced with
+    /// `EstimateSwap`, every message is charged a flat gas estimate.  
+    /// Returns [SimulateResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "simulate_osmosis_execution": {
+    ///     "proposal_id": 1
+    ///   }
+    /// }
+    /// ```
+    SimulateOsmosisExecution { proposal_id: u64 },
+
+    /// # SimulateExecution
+    ///
+    /// Checks the conditions `Execute` enforces (pause, status, execution delay, treasury
+    /// balance for bank sends) without dispatching anything. Failures of the dispatched
+    /// messages themselves are not detected.  
+    /// Returns [SimulateExecutionResponse]
+    ///
+    /// ## Example
+    ///
+    /// ```json
+    /// {
+    ///   "simulate_execution": {
+    ///     "proposal_id": 1
+    ///   }
+    /// }
+    /// ```
+    SimulateExecution { proposal_id: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -346,6 +1084,7 @@ pub struct ConfigResponse {
     pub config: Config,
     pub gov_token: String,
     pub staking_contract: Addr,
+    pub created_at: BlockTime,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -381,9 +1120,13 @@ where
     pub deposit_ends_at: Expiration,
     pub vote_starts_at: BlockTime,
     pub vote_ends_at: Expiration,
+    pub executed_at: Option<BlockTime>,
+    pub last_updated_height: u64,
 
     // vote
     pub votes: Votes,
+    pub vote_ratios: VoteRatios,
+    pub voter_count: u64,
     pub quorum: Decimal,
     pub threshold: Threshold,
     pub total_votes: Uint128,
@@ -391,6 +1134,57 @@ where
     pub total_deposit: Uint128,
 
     pub deposit_claimable: bool,
+    pub category: Option<String>,
+    pub cosponsor_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct IndexDriftEntry {
+    pub id: u64,
+    pub stored: Status,
+    pub current: Status,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct IndexDriftResponse {
+    pub proposals: Vec<IndexDriftEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DepositMismatch {
+    pub id: u64,
+    pub total_deposit: Uint128,
+    /// sum of the proposal's `DEPOSITS`, claimed ones included
+    pub deposited: Uint128,
+}
+
+/// A status index entry without a proposal stored with that status, or a proposal whose
+/// stored status isn't indexed
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StatusIndexMismatch {
+    pub id: u64,
+    pub indexed: Option<Status>,
+    pub stored: Option<Status>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AuditResponse {
+    /// no invariant is broken within the page
+    pub healthy: bool,
+    pub proposal_count: u64,
+    pub max_proposal_id: u64,
+    /// `proposal_count` equals `max_proposal_id`
+    pub proposal_count_matches: bool,
+    pub deposit_mismatches: Vec<DepositMismatch>,
+    pub status_index_mismatches: Vec<StatusIndexMismatch>,
+    /// last proposal id checked, `None` past the last page
+    pub last_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProposalStatusMapResponse {
+    /// (proposal id, current status)
+    pub statuses: Vec<(u64, Status)>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -399,6 +1193,9 @@ where
     T: Clone + fmt::Debug + PartialEq + JsonSchema,
 {
     pub proposals: Vec<ProposalResponse<T>>,
+    /// Set when the page is full, resumes the listing right after it
+    #[serde(default)]
+    pub next_cursor: Option<ProposalCursor>,
 }
 
 /// Returns the vote (opinion as well as weight counted) as well as
@@ -408,6 +1205,18 @@ pub struct VoteInfo {
     pub voter: String,
     pub vote: Vote,
     pub weight: Uint128,
+    pub rationale: Option<String>,
+}
+
+/// Voters of each option, sorted by weight (heaviest first)
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct VotesBreakdownResponse {
+    pub yes: Vec<VoteInfo>,
+    pub no: Vec<VoteInfo>,
+    pub abstain: Vec<VoteInfo>,
+    pub veto: Vec<VoteInfo>,
+    /// There were more ballots than could be scanned
+    pub capped: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -425,20 +1234,203 @@ pub struct DepositResponse {
     pub proposal_id: u64,
     pub depositor: String,
     pub amount: Uint128,
+    pub denom: String,
     pub claimed: bool,
 }
 
+/// Deposits sorted by amount (biggest first)
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TopDepositorsResponse {
+    pub depositors: Vec<DepositResponse>,
+    /// There were more deposits than could be scanned
+    pub capped: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct DepositsResponse {
     pub deposits: Vec<DepositResponse>,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClaimableDepositsResponse {
+    /// `(proposal_id, amount)`, in proposal order
+    pub deposits: Vec<(u64, Uint128)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProposalDepositSummaryResponse {
+    pub proposal_id: u64,
+    pub total_amount: Uint128,
+    pub depositor_count: u64,
+    /// deposits can be claimed back
+    pub claimable: bool,
+    pub claimed_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ParticipationPoint {
+    pub height: u64,
+    pub total_votes: Uint128,
+    /// `total_votes` over the proposal's `total_weight`
+    pub turnout: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProposalParticipationResponse {
+    pub proposal_id: u64,
+    pub total_weight: Uint128,
+    pub points: Vec<ParticipationPoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EligibilityHeightResponse {
+    pub proposal_id: u64,
+    /// `None` until voting opens
+    pub height: Option<u64>,
+    /// current `Config.voting_stake_age`, the height itself is fixed when voting opens
+    pub voting_stake_age: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DepositShortfallResponse {
+    pub proposal_id: u64,
+    pub total_deposit: Uint128,
+    /// deposit required to open voting (snapshot of `Config.proposal_deposit`)
+    pub required: Uint128,
+    /// zero once the proposal has left the deposit period
+    pub shortfall: Uint128,
+    pub deposit_ends_at: Expiration,
+    pub expired: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfiscatedTotalsResponse {
+    pub totals: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DaoStatsResponse {
+    pub created_at: BlockTime,
+    pub total_executed: u64,
+    pub total_rejected: u64,
+    pub total_proposals: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct GovernanceStatsResponse {
+    pub total_proposals: u64,
+    /// average quorum the proposals required (`threshold.quorum`)
+    pub avg_quorum: Decimal,
+    /// average participation reached (`total_votes / total_weight`)
+    pub avg_voter_turnout: Decimal,
+    pub execution_success_rate: Decimal,
+    pub avg_deposit_fill_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProposalStatusAtResponse {
+    pub proposal_id: u64,
+    pub height: u64,
+    pub status: Status,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProposalTimelineResponse {
+    pub proposal_id: u64,
+    pub events: Vec<(String, BlockTime)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ExpirationStatusResponse {
+    pub is_expired: bool,
+    /// Set for `at_height` expirations
+    pub blocks_remaining: Option<u64>,
+    /// Set for `at_time` expirations
+    pub seconds_remaining: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MultisigInfo {
+    pub label: String,
+    pub address: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ListMultisigsResponse {
+    pub multisigs: Vec<MultisigInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CosponsorsResponse {
+    pub cosponsors: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SubDaosResponse {
+    pub sub_daos: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MsgSimResult {
+    pub estimated_gas: u64,
+    /// Set for Osmosis swaps: the expected output (exact in) or input (exact out)
+    pub swap_estimate: Option<SwapAmount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SimulateResponse {
+    pub estimated_gas: u64,
+    /// One entry per proposal message, in order
+    pub messages: Vec<MsgSimResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SimulateExecutionResponse {
+    pub will_succeed: bool,
+    /// Why `Execute` would currently be rejected, empty if `will_succeed`
+    pub blocking_reasons: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigHistoryEntry {
+    pub version: u64,
+    /// The config that was in place before this update
+    pub config: Config,
+    pub changed_at: BlockTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigHistoryResponse {
+    pub entries: Vec<ConfigHistoryEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VersionResponse {
+    pub dao: ContractVersion,
+    pub staking: ContractVersion,
+}
+
+/// Mirror of cw3-fixed-multisig's `Voter`
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Cw3Voter {
+    pub addr: String,
+    pub weight: u64,
+}
+
+/// Mirror of cw3-fixed-multisig's `InstantiateMsg`
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Cw3FixedMultisigInstantiateMsg {
+    pub voters: Vec<Cw3Voter>,
+    pub threshold: cw_utils::Threshold,
+    pub max_voting_period: Duration,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct MigrateMsg {}
 
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::to_vec;
+    use cosmwasm_std::{from_slice, to_vec};
 
     use super::*;
 
@@ -455,9 +1447,18 @@ mod tests {
         let msg = ExecuteMsg::Vote(VoteMsg {
             proposal_id: 17,
             vote: Vote::No,
+            rationale: None,
         });
         let encoded = to_vec(&msg).unwrap();
         let json = String::from_utf8_lossy(&encoded).to_string();
-        assert_eq!(r#"{"vote":{"proposal_id":17,"vote":"no"}}"#, json.as_str());
+        assert_eq!(
+            r#"{"vote":{"proposal_id":17,"vote":"no","rationale":null}}"#,
+            json.as_str()
+        );
+
+        // the rationale is optional on the wire
+        let decoded: ExecuteMsg =
+            from_slice(br#"{"vote":{"proposal_id":17,"vote":"no"}}"#).unwrap();
+        assert_eq!(decoded, msg);
     }
 }
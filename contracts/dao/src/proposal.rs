@@ -1,4 +1,4 @@
-use cosmwasm_std::{Addr, BlockInfo, CosmosMsg, Decimal, Timestamp, Uint128};
+use cosmwasm_std::{Addr, BlockInfo, CosmosMsg, Decimal, StdResult, Timestamp, Uint128};
 use cw3::{Status, Vote};
 use cw_utils::{Duration, Expiration};
 use osmo_bindings::OsmosisMsg;
@@ -21,6 +21,15 @@ pub struct Votes {
     pub veto: Uint128,
 }
 
+// share of the total votes for each option
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct VoteRatios {
+    pub yes: Decimal,
+    pub no: Decimal,
+    pub abstain: Decimal,
+    pub veto: Decimal,
+}
+
 impl Votes {
     /// sum of all votes
     pub fn total(&self) -> Uint128 {
@@ -46,6 +55,21 @@ impl Votes {
         }
     }
 
+    /// share of each option in the total, all zero while nobody voted
+    pub fn ratios(&self) -> VoteRatios {
+        let total = self.total();
+        if total.is_zero() {
+            return VoteRatios::default();
+        }
+
+        VoteRatios {
+            yes: Decimal::from_ratio(self.yes, total),
+            no: Decimal::from_ratio(self.no, total),
+            abstain: Decimal::from_ratio(self.abstain, total),
+            veto: Decimal::from_ratio(self.veto, total),
+        }
+    }
+
     pub fn revoke(&mut self, vote: Vote, weight: Uint128) {
         match vote {
             Vote::Yes => self.yes = self.yes.checked_sub(weight).unwrap(),
@@ -91,6 +115,18 @@ pub struct Proposal {
     pub deposit_ends_at: Expiration,
     pub vote_starts_at: BlockTime,
     pub vote_ends_at: Expiration,
+    /// Height voting power is read at, fixed from `Config::voting_stake_age` when voting opens
+    #[serde(default)]
+    pub eligibility_height: Option<u64>,
+    /// Set once the proposal got executed
+    #[serde(default)]
+    pub executed_at: Option<BlockTime>,
+    /// Set once the proposal got rejected by `ForceClose` or `VetoExecution`
+    #[serde(default)]
+    pub rejected_at: Option<BlockTime>,
+    /// Height of the latest change (creation, deposit, vote or status update)
+    #[serde(default)]
+    pub last_updated_height: u64,
 
     /// Pass requirements
     pub threshold: Threshold,
@@ -98,10 +134,22 @@ pub struct Proposal {
     pub total_weight: Uint128,
     /// summary of existing votes
     pub votes: Votes,
+    /// number of distinct addresses that voted
+    #[serde(default)]
+    pub voter_count: u64,
+    /// number of distinct addresses listed in `COSPONSORS`
+    #[serde(default)]
+    pub cosponsor_count: u64,
     /// Amount of the native governance token required for voting
     pub total_deposit: Uint128,
     pub deposit_base_amount: Uint128,
     pub deposit_claimable: bool,
+    /// full deposit reached, but not from `Config.min_distinct_depositors` addresses yet
+    #[serde(default)]
+    pub awaiting_depositors: bool,
+    /// one of the governance maintained `CATEGORIES`
+    #[serde(default)]
+    pub category: Option<String>,
 }
 
 impl Default for Proposal {
@@ -117,19 +165,48 @@ impl Default for Proposal {
             deposit_ends_at: Default::default(),
             vote_starts_at: Default::default(),
             vote_ends_at: Default::default(),
+            eligibility_height: None,
+            executed_at: None,
+            rejected_at: None,
+            last_updated_height: 0,
             threshold: Default::default(),
             total_weight: Default::default(),
             votes: Default::default(),
+            voter_count: 0,
+            cosponsor_count: 0,
             total_deposit: Default::default(),
             deposit_base_amount: Default::default(),
             deposit_claimable: false,
+            awaiting_depositors: false,
+            category: None,
         }
     }
 }
 
 impl Proposal {
-    pub fn activate_voting_period(&mut self, block_time: BlockTime, voting_period: &Duration) {
+    /// Height voting power is read at, proposals opened before it was recorded use
+    /// `vote_starts_at`
+    pub fn eligibility_height(&self) -> u64 {
+        self.eligibility_height
+            .unwrap_or(self.vote_starts_at.height)
+    }
+
+    /// End of the execution delay, or of the voting period without one
+    pub fn executable_at(&self, execution_delay: Option<Duration>) -> StdResult<Expiration> {
+        match execution_delay {
+            Some(delay) => self.vote_ends_at + delay,
+            None => Ok(self.vote_ends_at),
+        }
+    }
+
+    pub fn activate_voting_period(
+        &mut self,
+        block_time: BlockTime,
+        voting_period: &Duration,
+        stake_age: u64,
+    ) {
         self.status = Status::Open;
+        self.eligibility_height = Some(block_time.height.saturating_sub(stake_age));
         self.vote_starts_at = block_time;
         self.vote_ends_at = duration_to_expiry(&self.vote_starts_at, voting_period);
     }
@@ -143,7 +220,7 @@ impl Proposal {
             // if pending, check if voting is opened or timed out
             Status::Pending => {
                 // check total deposit amount exceeds deposit base amount
-                if self.deposit_base_amount <= self.total_deposit {
+                if self.deposit_base_amount <= self.total_deposit && !self.awaiting_depositors {
                     status = Status::Open;
                 } else if self.deposit_ends_at.is_expired(block) {
                     // if not and deposit period ended, change proposal status to rejected.
@@ -152,14 +229,12 @@ impl Proposal {
             }
 
             // if open, check if voting is passed or timed out
-            Status::Open => {
-                // check voting period has ended
-                if self.vote_ends_at.is_expired(block) {
-                    if self.is_passed() {
-                        status = Status::Passed;
-                    } else {
-                        status = Status::Rejected;
-                    }
+            // (only once the voting period has ended)
+            Status::Open if self.vote_ends_at.is_expired(block) => {
+                if self.is_passed() {
+                    status = Status::Passed;
+                } else {
+                    status = Status::Rejected;
                 }
             }
             _ => {} // do nothing
@@ -168,12 +243,47 @@ impl Proposal {
         status
     }
 
+    /// status_at reconstructs the status as of a (possibly historical) block from the stored
+    /// expirations, `executed_at` and `rejected_at`.
+    pub fn status_at(&self, block: &BlockInfo) -> Status {
+        // vote_starts_at is only set once the deposit is filled
+        let activated = self.vote_starts_at != BlockTime::default();
+
+        if matches!(&self.executed_at, Some(executed_at) if block.height >= executed_at.height) {
+            Status::Executed
+        } else if matches!(&self.rejected_at, Some(rejected_at) if block.height >= rejected_at.height)
+        {
+            Status::Rejected
+        } else if activated && block.height >= self.vote_starts_at.height {
+            if !self.vote_ends_at.is_expired(block) {
+                Status::Open
+            } else if self.is_passed() {
+                Status::Passed
+            } else {
+                Status::Rejected
+            }
+        } else if !activated && self.deposit_ends_at.is_expired(block) {
+            Status::Rejected
+        } else {
+            Status::Pending
+        }
+    }
+
     /// update_status sets the status of the proposal to current_status.
     /// (designed for handler logic)
     pub fn update_status(&mut self, block: &BlockInfo) {
         self.status = self.current_status(block);
     }
 
+    /// ratio of votes to the total weight
+    pub fn quorum(&self) -> Decimal {
+        if self.total_weight.is_zero() {
+            Decimal::zero()
+        } else {
+            Decimal::from_ratio(self.votes.total(), self.total_weight)
+        }
+    }
+
     // returns true if this proposal is sure to pass (even before expiration if no future
     // sequence of possible votes can cause it to fail)
     pub fn is_passed(&self) -> bool {
@@ -181,9 +291,19 @@ impl Proposal {
         if self.votes.total() < votes_needed(self.total_weight, self.threshold.quorum) {
             return false;
         }
+        // and enough distinct voters, so a single whale can't pass it alone
+        if let Some(min_voters) = self.threshold.min_voters {
+            if self.voter_count < min_voters {
+                return false;
+            }
+        }
         // remove abstain to calculate opinions
         let opinions = self.votes.total() - self.votes.abstain;
-        let passed = self.votes.yes >= votes_needed(opinions, self.threshold.threshold);
+        let passed = if self.threshold.strict {
+            self.votes.yes > votes_at(opinions, self.threshold.threshold)
+        } else {
+            self.votes.yes >= votes_needed(opinions, self.threshold.threshold)
+        };
         let vetoed = self.is_vetoed();
 
         !vetoed && passed
@@ -200,7 +320,14 @@ impl Proposal {
 fn votes_needed(weight: Uint128, percentage: Decimal) -> Uint128 {
     let applied = percentage * Uint128::from(PRECISION_FACTOR * weight.u128());
     // Divide by PRECISION_FACTOR, rounding up to the nearest integer
-    Uint128::from((applied.u128() + PRECISION_FACTOR - 1) / PRECISION_FACTOR)
+    Uint128::from(applied.u128().div_ceil(PRECISION_FACTOR))
+}
+
+// exact (rounded down) number of votes matching the percentage, for strict comparisons:
+// more than 7 votes are above 50% of 15, more than 5 are above 50% of 10
+fn votes_at(weight: Uint128, percentage: Decimal) -> Uint128 {
+    let applied = percentage * Uint128::from(PRECISION_FACTOR * weight.u128());
+    Uint128::from(applied.u128() / PRECISION_FACTOR)
 }
 
 #[cfg(test)]
@@ -208,7 +335,7 @@ mod test {
     use std::ops::Add;
 
     use cosmwasm_std::testing::mock_env;
-    use cosmwasm_std::Env;
+    use cosmwasm_std::{from_slice, to_vec, Env};
 
     use super::*;
 
@@ -227,6 +354,35 @@ mod test {
         assert_eq!(votes.abstain, Uint128::new(40));
     }
 
+    #[test]
+    fn vote_ratios() {
+        assert_eq!(Votes::default().ratios(), VoteRatios::default());
+
+        let mut votes = Votes::new(Uint128::new(50));
+        votes.submit(Vote::No, Uint128::new(25));
+        votes.submit(Vote::Abstain, Uint128::new(15));
+        votes.submit(Vote::Veto, Uint128::new(10));
+        assert_eq!(
+            votes.ratios(),
+            VoteRatios {
+                yes: Decimal::percent(50),
+                no: Decimal::percent(25),
+                abstain: Decimal::percent(15),
+                veto: Decimal::percent(10),
+            }
+        );
+
+        let votes = Votes {
+            yes: Uint128::new(1),
+            no: Uint128::new(2),
+            ..Default::default()
+        };
+        let ratios = votes.ratios();
+        assert_eq!(ratios.yes, Decimal::from_ratio(1u128, 3u128));
+        assert_eq!(ratios.no, Decimal::from_ratio(2u128, 3u128));
+        assert_eq!(ratios.abstain, Decimal::zero());
+    }
+
     #[test]
     // we ensure this rounds up (as it calculates needed votes)
     fn votes_needed_rounds_properly() {
@@ -256,6 +412,24 @@ mod test {
         );
     }
 
+    #[test]
+    fn executed_at_defaults_to_none() {
+        let prop = Proposal {
+            executed_at: Some(mock_env().block.into()),
+            ..Default::default()
+        };
+        let json = String::from_utf8(to_vec(&prop).unwrap()).unwrap();
+        assert!(json.contains(r#""executed_at":{"#));
+
+        // proposals stored before `executed_at` existed
+        let legacy = String::from_utf8(to_vec(&Proposal::default()).unwrap())
+            .unwrap()
+            .replace(r#","executed_at":null"#, "");
+        assert!(!legacy.contains("executed_at"));
+        let prop: Proposal = from_slice(legacy.as_bytes()).unwrap();
+        assert_eq!(prop.executed_at, None);
+    }
+
     mod pending {
         use super::*;
 
@@ -396,6 +570,8 @@ mod test {
                 threshold: Decimal::percent(50),
                 quorum: Decimal::percent(40),
                 veto_threshold: Decimal::percent(33),
+                min_voters: None,
+                strict: false,
             };
 
             let env = mock_env();
@@ -434,6 +610,8 @@ mod test {
                 threshold: Decimal::percent(50),
                 quorum: Decimal::percent(40),
                 veto_threshold: Decimal::percent(33),
+                min_voters: None,
+                strict: false,
             };
 
             let env = mock_env();
@@ -500,6 +678,53 @@ mod test {
             assert_vetoed(&env, suite(&env, &quorum, &reject, weight, true));
         }
 
+        #[test]
+        fn strict_threshold_boundary() {
+            let inclusive = Threshold {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(10),
+                veto_threshold: Decimal::percent(33),
+                min_voters: None,
+                strict: false,
+            };
+            let strict = Threshold {
+                strict: true,
+                ..inclusive.clone()
+            };
+
+            let env = mock_env();
+            let votes = |yes: u128, no: u128| Votes {
+                yes: Uint128::new(yes),
+                no: Uint128::new(no),
+                abstain: Uint128::zero(),
+                veto: Uint128::zero(),
+            };
+
+            // exactly 50% yes
+            assert_passed(
+                &env,
+                suite(&env, &inclusive, &votes(5, 5), Uint128::new(10), true),
+            );
+            assert_rejected(
+                &env,
+                suite(&env, &strict, &votes(5, 5), Uint128::new(10), true),
+            );
+
+            // just above 50%
+            assert_passed(
+                &env,
+                suite(&env, &strict, &votes(6, 4), Uint128::new(10), true),
+            );
+            assert_passed(
+                &env,
+                suite(&env, &strict, &votes(8, 7), Uint128::new(15), true),
+            );
+            assert_rejected(
+                &env,
+                suite(&env, &strict, &votes(7, 8), Uint128::new(15), true),
+            );
+        }
+
         #[test]
         fn quorum_edge_cases() {
             // when we pass absolute threshold (everyone else voting no, we pass), but still don't hit quorum
@@ -507,6 +732,8 @@ mod test {
                 threshold: Decimal::percent(60),
                 quorum: Decimal::percent(80),
                 veto_threshold: Decimal::percent(33),
+                min_voters: None,
+                strict: false,
             };
 
             let env = mock_env();
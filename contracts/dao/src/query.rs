@@ -1,21 +1,49 @@
-use cosmwasm_std::{Addr, Env, Order, StdError, StdResult, Uint128};
+use std::cmp::Reverse;
+
+use cosmwasm_std::{
+    coin, from_binary, to_binary, Addr, BankMsg, BlockInfo, Decimal, Env, Order, StdError,
+    StdResult, Uint128,
+};
+use cw2::{get_contract_version, CONTRACT};
 use cw20::{Balance, BalanceResponse, Cw20CoinVerified, Cw20QueryMsg, Denom};
+use cw3::{Status, Vote};
 use cw_storage_plus::Bound;
-use cw_utils::{maybe_addr, NativeBalance};
-use osmo_bindings::OsmosisMsg;
+use cw_utils::{maybe_addr, Expiration, NativeBalance};
+use osmo_bindings::{EstimatePriceResponse, OsmosisMsg, OsmosisQuery};
+use serde::{Deserialize, Serialize};
 
-use crate::helpers::{get_and_check_limit, proposal_to_response};
+use crate::helpers::{
+    check_reserves, get_and_check_limit, participation_bonus, passes_rechecked_quorum,
+    proposal_to_response,
+};
 use crate::msg::{
-    ConfigResponse, DepositResponse, DepositsQueryOption, DepositsResponse, ProposalResponse,
-    ProposalsQueryOption, ProposalsResponse, RangeOrder, TokenBalancesResponse, TokenListResponse,
-    VoteInfo, VoteResponse, VotesResponse,
+    AuditResponse, ClaimableDepositsResponse, ConfigHistoryEntry, ConfigHistoryResponse,
+    ConfigResponse, ConfiscatedTotalsResponse, CosponsorsResponse, DaoStatsResponse,
+    DepositMismatch, DepositResponse, DepositShortfallResponse, DepositsQueryOption,
+    DepositsResponse, EligibilityHeightResponse, ExpirationStatusResponse, GovernanceStatsResponse,
+    IndexDriftEntry, IndexDriftResponse, ListMultisigsResponse, MsgSimResult, MultisigInfo,
+    ParticipationPoint, ProposalCursor, ProposalDepositSummaryResponse,
+    ProposalParticipationResponse, ProposalResponse, ProposalStatusAtResponse,
+    ProposalStatusMapResponse, ProposalTimelineResponse, ProposalsQueryOption, ProposalsResponse,
+    RangeOrder, SimulateExecutionResponse, SimulateResponse, StatusIndexMismatch, SubDaosResponse,
+    TokenBalancesResponse, TokenListResponse, TopDepositorsResponse, VersionResponse, VoteInfo,
+    VoteResponse, VotesBreakdownResponse, VotesResponse,
 };
 use crate::state::{
-    parse_id, BALLOTS, CONFIG, DEPOSITS, GOV_TOKEN, IDX_DEPOSITS_BY_DEPOSITOR,
-    IDX_PROPS_BY_PROPOSER, IDX_PROPS_BY_STATUS, PROPOSALS, PROPOSAL_COUNT, STAKING_CONTRACT,
-    TREASURY_TOKENS,
+    parse_id, BlockTime, BALLOTS, CONFIG, CONFIG_HISTORY, CONFISCATED_TOTAL, COSPONSORS,
+    DAO_CREATED_AT, DAO_PAUSED, DAO_TOTAL_PROPOSALS_EXECUTED, DAO_TOTAL_PROPOSALS_REJECTED,
+    DEPOSITS, DEPOSIT_SUMMARIES, GOV_STATS_CACHE, GOV_TOKEN, IDX_DEPOSITS_BY_DEPOSITOR,
+    IDX_PROPS_BY_CATEGORY, IDX_PROPS_BY_DEPOSIT, IDX_PROPS_BY_PROPOSER, IDX_PROPS_BY_STATUS,
+    IDX_PROPS_BY_UPDATE_HEIGHT, PROPOSALS, PROPOSAL_COUNT, STAKING_CONTRACT, SUB_DAOS,
+    SUB_MULTISIGS, TALLY_HISTORY, TREASURY_TOKENS,
 };
-use crate::{Deps, QuerierWrapper, DEFAULT_LIMIT, MAX_LIMIT};
+use crate::{
+    ContractError, CosmosMsg, Deps, QuerierWrapper, DEFAULT_BREAKDOWN_TOP, MAX_BREAKDOWN_TOP,
+    MAX_BY_DEPOSIT_LIMIT,
+};
+
+/// Flat gas charged per proposal message by `SimulateOsmosisExecution`
+const DEFAULT_MSG_GAS: u64 = 200_000;
 
 fn query_balance_with_asset_type(
     querier: QuerierWrapper,
@@ -57,11 +85,14 @@ pub fn config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
     let gov_token = GOV_TOKEN.load(deps.storage)?;
     let staking_contract = STAKING_CONTRACT.load(deps.storage)?;
+    // not recorded by DAOs instantiated before it was tracked
+    let created_at = DAO_CREATED_AT.may_load(deps.storage)?.unwrap_or_default();
 
     Ok(ConfigResponse {
         config,
         gov_token,
         staking_contract,
+        created_at,
     })
 }
 
@@ -88,7 +119,8 @@ pub fn token_balances(
     limit: Option<u32>,
     order: Option<RangeOrder>,
 ) -> StdResult<TokenBalancesResponse> {
-    let limit = get_and_check_limit(limit, MAX_LIMIT, DEFAULT_LIMIT)? as usize;
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
     let order = order.unwrap_or(RangeOrder::Asc).into();
     let start = start.map(|v| match v {
         Denom::Native(denom) => ("native", denom),
@@ -129,6 +161,168 @@ pub fn proposal(deps: Deps, env: Env, id: u64) -> StdResult<ProposalResponse<Osm
     Ok(proposal_to_response(&env.block, id, prop))
 }
 
+pub fn proposal_maybe(
+    deps: Deps,
+    env: Env,
+    id: u64,
+) -> StdResult<Option<ProposalResponse<OsmosisMsg>>> {
+    let prop = PROPOSALS.may_load(deps.storage, id)?;
+    Ok(prop.map(|prop| proposal_to_response(&env.block, id, prop)))
+}
+
+pub fn all_proposals_status_map(
+    deps: Deps,
+    env: Env,
+    start: Option<u64>,
+    limit: Option<u32>,
+    order: Option<RangeOrder>,
+) -> StdResult<ProposalStatusMapResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+    let order = order.unwrap_or(RangeOrder::Asc).into();
+    let (min, max) = match order {
+        Order::Ascending => (start.map(Bound::exclusive), None),
+        Order::Descending => (None, start.map(Bound::exclusive)),
+    };
+
+    let statuses = PROPOSALS
+        .range(deps.storage, min, max, order)
+        .take(limit)
+        .map(|item| {
+            let (id, prop) = item?;
+            Ok((id, prop.current_status(&env.block)))
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ProposalStatusMapResponse { statuses })
+}
+
+pub fn index_drift(deps: Deps, env: Env, limit: Option<u32>) -> StdResult<IndexDriftResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+
+    // only pending and open proposals transition lazily
+    let proposals = [Status::Pending, Status::Open]
+        .iter()
+        .flat_map(|status| {
+            IDX_PROPS_BY_STATUS.prefix(*status as u8).keys(
+                deps.storage,
+                None,
+                None,
+                Order::Ascending,
+            )
+        })
+        .map(|id| -> StdResult<Option<IndexDriftEntry>> {
+            let id = id?;
+            let prop = PROPOSALS.load(deps.storage, id)?;
+            let current = prop.current_status(&env.block);
+            Ok((current != prop.status).then_some(IndexDriftEntry {
+                id,
+                stored: prop.status,
+                current,
+            }))
+        })
+        .filter_map(StdResult::transpose)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    Ok(IndexDriftResponse { proposals })
+}
+
+pub fn audit(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<AuditResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+    let proposal_count = PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    let max_proposal_id = PROPOSALS
+        .keys(deps.storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .unwrap_or_default();
+
+    let props = PROPOSALS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    // a full page ends at its last proposal, the last page also covers stray index entries
+    // above the highest proposal id
+    let last_id = props.last().map(|(id, _)| *id);
+    let max = last_id
+        .filter(|_| props.len() == limit)
+        .map(Bound::inclusive);
+
+    let mut deposit_mismatches = vec![];
+    let mut status_index_mismatches = vec![];
+    for (id, prop) in props {
+        let deposited = DEPOSITS
+            .prefix(id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .try_fold(Uint128::zero(), |sum, item| -> StdResult<_> {
+                Ok(sum.checked_add(item?.1.amount)?)
+            })?;
+        if deposited != prop.total_deposit {
+            deposit_mismatches.push(DepositMismatch {
+                id,
+                total_deposit: prop.total_deposit,
+                deposited,
+            });
+        }
+
+        if !IDX_PROPS_BY_STATUS.has(deps.storage, (prop.status as u8, id)) {
+            status_index_mismatches.push(StatusIndexMismatch {
+                id,
+                indexed: None,
+                stored: Some(prop.status),
+            });
+        }
+    }
+
+    let statuses = [
+        Status::Pending,
+        Status::Open,
+        Status::Rejected,
+        Status::Passed,
+        Status::Executed,
+    ];
+    for status in statuses {
+        for id in IDX_PROPS_BY_STATUS.prefix(status as u8).keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            max.clone(),
+            Order::Ascending,
+        ) {
+            let id = id?;
+            let stored = PROPOSALS
+                .may_load(deps.storage, id)?
+                .map(|prop| prop.status);
+            if stored != Some(status) {
+                status_index_mismatches.push(StatusIndexMismatch {
+                    id,
+                    indexed: Some(status),
+                    stored,
+                });
+            }
+        }
+    }
+
+    let proposal_count_matches = proposal_count == max_proposal_id;
+    Ok(AuditResponse {
+        healthy: proposal_count_matches
+            && deposit_mismatches.is_empty()
+            && status_index_mismatches.is_empty(),
+        proposal_count,
+        max_proposal_id,
+        proposal_count_matches,
+        deposit_mismatches,
+        status_index_mismatches,
+        last_id,
+    })
+}
+
 pub fn proposals(
     deps: Deps,
     env: Env,
@@ -137,16 +331,47 @@ pub fn proposals(
     limit: Option<u32>,
     order: Option<RangeOrder>,
 ) -> StdResult<ProposalsResponse<OsmosisMsg>> {
-    let limit = get_and_check_limit(limit, MAX_LIMIT, DEFAULT_LIMIT)? as usize;
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
     let order = order.unwrap_or(RangeOrder::Asc).into();
+
+    // `ByDeposit` keys vary per proposal, the other indexes share one key per query
+    let (cursor, key) = match &query {
+        ProposalsQueryOption::FindByStatus { status, cursor } => {
+            (cursor, Some(CursorKey::Status(*status as u8)))
+        }
+        ProposalsQueryOption::FindByProposer { proposer, cursor } => {
+            (cursor, Some(CursorKey::Proposer(proposer.clone())))
+        }
+        ProposalsQueryOption::FindByCategory { category, cursor } => {
+            (cursor, Some(CursorKey::Category(category.clone())))
+        }
+        ProposalsQueryOption::Everything { cursor } => (cursor, Some(CursorKey::Id)),
+        ProposalsQueryOption::ByDeposit { cursor, .. } => (cursor, None),
+    };
+    let cursor = cursor.as_ref().map(CursorState::decode).transpose()?;
+    let start = match (&cursor, &key) {
+        (None, _) => start,
+        (Some(cursor), Some(key)) if cursor.key == *key => Some(cursor.id),
+        (Some(cursor), None) if matches!(cursor.key, CursorKey::Deposit(_)) => Some(cursor.id),
+        _ => {
+            return Err(StdError::generic_err(
+                "Proposal cursor belongs to another query",
+            ))
+        }
+    };
+    let page_limit = match query {
+        ProposalsQueryOption::ByDeposit { .. } => limit.min(MAX_BY_DEPOSIT_LIMIT),
+        _ => limit,
+    };
     let (min, max) = match order {
         Order::Ascending => (start.map(Bound::exclusive), None),
         Order::Descending => (None, start.map(Bound::exclusive)),
     };
 
-    let props: StdResult<Vec<_>> = match query {
-        ProposalsQueryOption::FindByStatus { status } => IDX_PROPS_BY_STATUS
-            .prefix(status as u8)
+    let props: StdResult<Vec<_>> = match &query {
+        ProposalsQueryOption::FindByStatus { status, .. } => IDX_PROPS_BY_STATUS
+            .prefix(*status as u8)
             .range(deps.storage, min, max, order)
             .take(limit)
             .map(|item| {
@@ -158,8 +383,8 @@ pub fn proposals(
                 ))
             })
             .collect(),
-        ProposalsQueryOption::FindByProposer { proposer } => IDX_PROPS_BY_PROPOSER
-            .prefix(proposer)
+        ProposalsQueryOption::FindByProposer { proposer, .. } => IDX_PROPS_BY_PROPOSER
+            .prefix(proposer.clone())
             .range(deps.storage, min, max, order)
             .take(limit)
             .map(|item| {
@@ -171,7 +396,20 @@ pub fn proposals(
                 ))
             })
             .collect(),
-        ProposalsQueryOption::Everything {} => PROPOSALS
+        ProposalsQueryOption::FindByCategory { category, .. } => IDX_PROPS_BY_CATEGORY
+            .prefix(category.clone())
+            .range(deps.storage, min, max, order)
+            .take(limit)
+            .map(|item| {
+                let (k, _) = item?;
+                Ok(proposal_to_response(
+                    &env.block,
+                    k,
+                    PROPOSALS.load(deps.storage, k)?,
+                ))
+            })
+            .collect(),
+        ProposalsQueryOption::Everything { .. } => PROPOSALS
             .range_raw(deps.storage, min, max, order)
             .take(limit)
             .map(|item| {
@@ -183,9 +421,144 @@ pub fn proposals(
                 ))
             })
             .collect(),
+        ProposalsQueryOption::ByDeposit { descending, .. } => {
+            // a cursor remembers the deposit, a plain `start` ranks by the current one
+            let start = match cursor {
+                Some(CursorState {
+                    key: CursorKey::Deposit(deposit),
+                    id,
+                }) => Some((deposit.u128(), id)),
+                _ => start
+                    .map(|id| -> StdResult<_> {
+                        Ok((PROPOSALS.load(deps.storage, id)?.total_deposit.u128(), id))
+                    })
+                    .transpose()?,
+            };
+            let (min, max, order) = if *descending {
+                (None, start.map(Bound::exclusive), Order::Descending)
+            } else {
+                (start.map(Bound::exclusive), None, Order::Ascending)
+            };
+
+            IDX_PROPS_BY_DEPOSIT
+                .keys(deps.storage, min, max, order)
+                .take(page_limit)
+                .map(|key| {
+                    let (_, id) = key?;
+                    Ok(proposal_to_response(
+                        &env.block,
+                        id,
+                        PROPOSALS.load(deps.storage, id)?,
+                    ))
+                })
+                .collect()
+        }
+    };
+
+    let proposals = props?;
+    let next_cursor = match proposals.last() {
+        Some(last) if proposals.len() == page_limit => Some(
+            CursorState {
+                key: key.unwrap_or(CursorKey::Deposit(last.total_deposit)),
+                id: last.id,
+            }
+            .encode()?,
+        ),
+        _ => None,
     };
 
-    Ok(ProposalsResponse { proposals: props? })
+    Ok(ProposalsResponse {
+        proposals,
+        next_cursor,
+    })
+}
+
+/// What a [ProposalCursor] holds: the index key of the last proposal seen, next to its id
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+enum CursorKey {
+    Status(u8),
+    Proposer(Addr),
+    Category(String),
+    Id,
+    Deposit(Uint128),
+    UpdateHeight(u64),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct CursorState {
+    key: CursorKey,
+    id: u64,
+}
+
+impl CursorState {
+    fn decode(cursor: &ProposalCursor) -> StdResult<Self> {
+        from_binary(&cursor.0).map_err(|_| StdError::generic_err("Invalid proposal cursor"))
+    }
+
+    fn encode(&self) -> StdResult<ProposalCursor> {
+        Ok(ProposalCursor(to_binary(self)?))
+    }
+}
+
+pub fn proposals_updated_after(
+    deps: Deps,
+    env: Env,
+    height: u64,
+    cursor: Option<ProposalCursor>,
+    limit: Option<u32>,
+) -> StdResult<ProposalsResponse<OsmosisMsg>> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+
+    // several proposals may share a height, so a page resumes right after its last entry
+    let start = match cursor.as_ref().map(CursorState::decode).transpose()? {
+        None => (height, u64::MAX),
+        Some(CursorState {
+            key: CursorKey::UpdateHeight(height),
+            id,
+        }) => (height, id),
+        Some(_) => {
+            return Err(StdError::generic_err(
+                "Proposal cursor belongs to another query",
+            ))
+        }
+    };
+
+    let entries = IDX_PROPS_BY_UPDATE_HEIGHT
+        .keys(
+            deps.storage,
+            Some(Bound::exclusive(start)),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let next_cursor = match entries.last() {
+        Some((height, id)) if entries.len() == limit => Some(
+            CursorState {
+                key: CursorKey::UpdateHeight(*height),
+                id: *id,
+            }
+            .encode()?,
+        ),
+        _ => None,
+    };
+    let proposals = entries
+        .into_iter()
+        .map(|(_, id)| {
+            Ok(proposal_to_response(
+                &env.block,
+                id,
+                PROPOSALS.load(deps.storage, id)?,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ProposalsResponse {
+        proposals,
+        next_cursor,
+    })
 }
 
 pub fn proposal_count(deps: Deps) -> StdResult<u64> {
@@ -200,6 +573,7 @@ pub fn vote(deps: Deps, proposal_id: u64, voter: String) -> StdResult<VoteRespon
         voter,
         vote: b.vote,
         weight: b.weight,
+        rationale: b.rationale,
     });
     Ok(VoteResponse { vote })
 }
@@ -211,7 +585,8 @@ pub fn votes(
     limit: Option<u32>,
     order: Option<RangeOrder>,
 ) -> StdResult<VotesResponse> {
-    let limit = get_and_check_limit(limit, MAX_LIMIT, DEFAULT_LIMIT)? as usize;
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
     let order = order.unwrap_or(RangeOrder::Asc).into();
     let start = maybe_addr(deps.api, start)?;
     let (min, max) = match order {
@@ -229,6 +604,7 @@ pub fn votes(
                 voter: String::from_utf8(voter)?,
                 vote: ballot.vote,
                 weight: ballot.weight,
+                rationale: ballot.rationale,
             })
         })
         .collect();
@@ -236,6 +612,53 @@ pub fn votes(
     Ok(VotesResponse { votes: votes? })
 }
 
+pub fn votes_breakdown(
+    deps: Deps,
+    proposal_id: u64,
+    top: Option<u32>,
+) -> StdResult<VotesBreakdownResponse> {
+    let top = get_and_check_limit(top, MAX_BREAKDOWN_TOP, DEFAULT_BREAKDOWN_TOP)? as usize;
+    let cfg = CONFIG.load(deps.storage)?;
+    let scanned = cfg.max_query_limit as usize;
+
+    let ballots = BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(scanned + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut resp = VotesBreakdownResponse {
+        capped: ballots.len() > scanned,
+        ..Default::default()
+    };
+    for (voter, ballot) in ballots.into_iter().take(scanned) {
+        let bucket = match ballot.vote {
+            Vote::Yes => &mut resp.yes,
+            Vote::No => &mut resp.no,
+            Vote::Abstain => &mut resp.abstain,
+            Vote::Veto => &mut resp.veto,
+        };
+        bucket.push(VoteInfo {
+            voter: voter.to_string(),
+            vote: ballot.vote,
+            weight: ballot.weight,
+            rationale: ballot.rationale,
+        });
+    }
+    for bucket in [
+        &mut resp.yes,
+        &mut resp.no,
+        &mut resp.abstain,
+        &mut resp.veto,
+    ] {
+        // stable sort, ties stay in address order
+        bucket.sort_by_key(|info| Reverse(info.weight));
+        bucket.truncate(top);
+    }
+
+    Ok(resp)
+}
+
 pub fn deposit(deps: Deps, proposal_id: u64, depositor: String) -> StdResult<DepositResponse> {
     let depositor = deps.api.addr_validate(depositor.as_str())?;
     let deposit = DEPOSITS.load(deps.storage, (proposal_id, depositor.clone()))?;
@@ -244,17 +667,149 @@ pub fn deposit(deps: Deps, proposal_id: u64, depositor: String) -> StdResult<Dep
         proposal_id,
         depositor: depositor.to_string(),
         amount: deposit.amount,
+        denom: deposit.denom,
         claimed: deposit.claimed,
     })
 }
 
+pub fn top_depositors(
+    deps: Deps,
+    proposal_id: u64,
+    limit: Option<u32>,
+) -> StdResult<TopDepositorsResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+    let scanned = cfg.max_query_limit as usize;
+
+    let mut deposits = DEPOSITS
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(scanned + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let capped = deposits.len() > scanned;
+    deposits.truncate(scanned);
+
+    // stable sort, ties stay in address order
+    deposits.sort_by_key(|(_, deposit)| Reverse(deposit.amount));
+    let depositors = deposits
+        .into_iter()
+        .take(limit)
+        .map(|(depositor, deposit)| DepositResponse {
+            proposal_id,
+            depositor: depositor.to_string(),
+            amount: deposit.amount,
+            denom: deposit.denom,
+            claimed: deposit.claimed,
+        })
+        .collect();
+
+    Ok(TopDepositorsResponse { depositors, capped })
+}
+
+pub fn proposal_deposit_summary(
+    deps: Deps,
+    proposal_id: u64,
+) -> StdResult<ProposalDepositSummaryResponse> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let summary = DEPOSIT_SUMMARIES
+        .may_load(deps.storage, proposal_id)?
+        .unwrap_or_default();
+
+    Ok(ProposalDepositSummaryResponse {
+        proposal_id,
+        total_amount: summary.total_amount,
+        depositor_count: summary.depositor_count,
+        claimable: prop.deposit_claimable,
+        claimed_count: summary.claimed_count,
+    })
+}
+
+pub fn eligibility_height(deps: Deps, proposal_id: u64) -> StdResult<EligibilityHeightResponse> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let voting_stake_age = CONFIG.load(deps.storage)?.voting_stake_age;
+
+    Ok(EligibilityHeightResponse {
+        proposal_id,
+        height: (prop.status != Status::Pending).then(|| prop.eligibility_height()),
+        voting_stake_age,
+    })
+}
+
+pub fn proposal_participation_by_height(
+    deps: Deps,
+    proposal_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalParticipationResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+
+    let points = if cfg.record_tally_history {
+        TALLY_HISTORY
+            .prefix(proposal_id)
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| {
+                let (height, votes) = item?;
+                let total_votes = votes.total();
+                let turnout = if prop.total_weight.is_zero() {
+                    Decimal::zero()
+                } else {
+                    Decimal::from_ratio(total_votes, prop.total_weight)
+                };
+                Ok(ParticipationPoint {
+                    height,
+                    total_votes,
+                    turnout,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?
+    } else {
+        vec![]
+    };
+
+    Ok(ProposalParticipationResponse {
+        proposal_id,
+        total_weight: prop.total_weight,
+        points,
+    })
+}
+
+pub fn claimable_deposits(deps: Deps, depositor: String) -> StdResult<ClaimableDepositsResponse> {
+    let depositor = deps.api.addr_validate(&depositor)?;
+
+    let deposits = IDX_DEPOSITS_BY_DEPOSITOR
+        .prefix(depositor.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|proposal_id| {
+            let proposal_id = proposal_id?;
+            let deposit = DEPOSITS.load(deps.storage, (proposal_id, depositor.clone()))?;
+            if deposit.claimed || !PROPOSALS.load(deps.storage, proposal_id)?.deposit_claimable {
+                return Ok(None);
+            }
+
+            Ok(Some((proposal_id, deposit.amount)))
+        })
+        .filter_map(StdResult::transpose)
+        .collect::<StdResult<_>>()?;
+
+    Ok(ClaimableDepositsResponse { deposits })
+}
+
 pub fn deposits(
     deps: Deps,
     query: DepositsQueryOption,
     limit: Option<u32>,
     order: Option<RangeOrder>,
 ) -> StdResult<DepositsResponse> {
-    let limit = get_and_check_limit(limit, MAX_LIMIT, DEFAULT_LIMIT)? as usize;
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
     let order = order.unwrap_or(RangeOrder::Asc).into();
 
     let deposits: StdResult<Vec<_>> = match query {
@@ -275,6 +830,7 @@ pub fn deposits(
                         proposal_id,
                         depositor: depositor.to_string(),
                         amount: deposit.amount,
+                        denom: deposit.denom,
                         claimed: deposit.claimed,
                     })
                 })
@@ -299,6 +855,7 @@ pub fn deposits(
                         proposal_id,
                         depositor: depositor.to_string(),
                         amount: deposit.amount,
+                        denom: deposit.denom,
                         claimed: deposit.claimed,
                     })
                 })
@@ -327,6 +884,7 @@ pub fn deposits(
                         proposal_id,
                         depositor: depositor.to_string(),
                         amount: deposit.amount,
+                        denom: deposit.denom,
                         claimed: deposit.claimed,
                     })
                 })
@@ -338,3 +896,454 @@ pub fn deposits(
         deposits: deposits?,
     })
 }
+
+pub fn deposit_shortfall(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<DepositShortfallResponse> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+
+    let shortfall = if prop.current_status(&env.block) == Status::Pending {
+        prop.deposit_base_amount.saturating_sub(prop.total_deposit)
+    } else {
+        Uint128::zero()
+    };
+
+    Ok(DepositShortfallResponse {
+        proposal_id,
+        total_deposit: prop.total_deposit,
+        required: prop.deposit_base_amount,
+        shortfall,
+        deposit_ends_at: prop.deposit_ends_at,
+        expired: prop.deposit_ends_at.is_expired(&env.block),
+    })
+}
+
+pub fn self_stake(
+    deps: Deps,
+    env: Env,
+) -> StdResult<ion_stake::msg::StakedBalanceAtHeightResponse> {
+    let staking_contract = STAKING_CONTRACT.load(deps.storage)?;
+
+    deps.querier.query_wasm_smart(
+        staking_contract,
+        &ion_stake::msg::QueryMsg::StakedBalanceAtHeight {
+            address: env.contract.address.to_string(),
+            height: None,
+        },
+    )
+}
+
+pub fn proposal_status_at(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+    height: u64,
+) -> StdResult<ProposalStatusAtResponse> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let anchor = &prop.submitted_at;
+    if height < anchor.height {
+        return Err(StdError::generic_err(format!(
+            "proposal {} was not submitted at height {}",
+            proposal_id, height
+        )));
+    }
+
+    let estimated = estimate_block_time(anchor, &env.block, &Expiration::AtHeight(height));
+    let block = BlockInfo {
+        height,
+        time: estimated.time,
+        chain_id: env.block.chain_id,
+    };
+
+    Ok(ProposalStatusAtResponse {
+        proposal_id,
+        height,
+        status: prop.status_at(&block),
+    })
+}
+
+pub fn proposal_timeline(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<ProposalTimelineResponse> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    // vote_starts_at is only set once the deposit is filled
+    let activated = prop.vote_starts_at != BlockTime::default();
+
+    let mut events = vec![("created".to_string(), prop.submitted_at.clone())];
+    if activated {
+        events.push(("opened".to_string(), prop.vote_starts_at.clone()));
+    }
+
+    let outcome = match prop.current_status(&env.block) {
+        // rejected early, the tally may still have passed when voting ended
+        Status::Rejected if prop.rejected_at.is_some() => prop.is_passed().then_some("passed"),
+        Status::Passed | Status::Executed => Some("passed"),
+        Status::Rejected => Some("rejected"),
+        _ => None,
+    };
+    let ended_at = if activated {
+        prop.vote_ends_at
+    } else {
+        prop.deposit_ends_at
+    };
+    if let Some(outcome) = outcome {
+        if ended_at.is_expired(&env.block) {
+            let ended_at = estimate_block_time(&prop.submitted_at, &env.block, &ended_at);
+            events.push((outcome.to_string(), ended_at));
+        }
+    }
+    if let Some(rejected_at) = prop.rejected_at {
+        events.push(("rejected".to_string(), rejected_at));
+    }
+
+    if let Some(executed_at) = prop.executed_at {
+        events.push(("executed".to_string(), executed_at));
+    }
+
+    Ok(ProposalTimelineResponse {
+        proposal_id,
+        events,
+    })
+}
+
+/// Fills in the half of `expiry` that isn't stored, assuming the average block time since `anchor`
+fn estimate_block_time(anchor: &BlockTime, block: &BlockInfo, expiry: &Expiration) -> BlockTime {
+    let elapsed_blocks = block.height.saturating_sub(anchor.height) as u128;
+    let elapsed_nanos = block.time.nanos().saturating_sub(anchor.time.nanos()) as u128;
+
+    match *expiry {
+        Expiration::AtHeight(height) => {
+            let blocks = height.saturating_sub(anchor.height) as u128;
+            let nanos = elapsed_nanos * blocks / elapsed_blocks.max(1);
+            BlockTime {
+                height,
+                time: anchor.time.plus_nanos(nanos as u64),
+            }
+        }
+        Expiration::AtTime(time) => {
+            let nanos = time.nanos().saturating_sub(anchor.time.nanos()) as u128;
+            let blocks = elapsed_blocks * nanos / elapsed_nanos.max(1);
+            BlockTime {
+                height: anchor.height + blocks as u64,
+                time,
+            }
+        }
+        Expiration::Never {} => block.clone().into(),
+    }
+}
+
+pub fn voting_powers(
+    deps: Deps,
+    proposal_id: u64,
+    addresses: Vec<String>,
+) -> StdResult<ion_stake::msg::StakedBalancesAtHeightResponse> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    if prop.vote_starts_at.height == 0 {
+        return Err(StdError::generic_err(
+            "Voting has not started on this proposal",
+        ));
+    }
+    let staking_contract = STAKING_CONTRACT.load(deps.storage)?;
+
+    deps.querier.query_wasm_smart(
+        staking_contract,
+        &ion_stake::msg::QueryMsg::StakedBalancesAtHeight {
+            addresses,
+            height: Some(prop.eligibility_height()),
+        },
+    )
+}
+
+pub fn confiscated_totals(deps: Deps) -> StdResult<ConfiscatedTotalsResponse> {
+    let totals = CONFISCATED_TOTAL
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, amount) = item?;
+            Ok(coin(amount.u128(), denom))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ConfiscatedTotalsResponse { totals })
+}
+
+pub fn dao_stats(deps: Deps) -> StdResult<DaoStatsResponse> {
+    Ok(DaoStatsResponse {
+        created_at: DAO_CREATED_AT.may_load(deps.storage)?.unwrap_or_default(),
+        total_executed: DAO_TOTAL_PROPOSALS_EXECUTED
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        total_rejected: DAO_TOTAL_PROPOSALS_REJECTED
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        total_proposals: PROPOSAL_COUNT.load(deps.storage)?,
+    })
+}
+
+pub fn governance_stats(deps: Deps) -> StdResult<GovernanceStatsResponse> {
+    let stats = GOV_STATS_CACHE.may_load(deps.storage)?.unwrap_or_default();
+    let total_proposals = PROPOSAL_COUNT.load(deps.storage)?;
+
+    let finalized = stats.finalized();
+    let avg = |sum: Decimal| {
+        if finalized == 0 {
+            Decimal::zero()
+        } else {
+            sum * Decimal::from_ratio(1u128, finalized)
+        }
+    };
+
+    Ok(GovernanceStatsResponse {
+        total_proposals,
+        avg_quorum: avg(stats.quorum_sum),
+        avg_voter_turnout: avg(stats.turnout_sum),
+        execution_success_rate: if finalized == 0 {
+            Decimal::zero()
+        } else {
+            Decimal::from_ratio(stats.executed, finalized)
+        },
+        avg_deposit_fill_rate: avg(stats.deposit_fill_sum),
+    })
+}
+
+pub fn config_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ConfigHistoryResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+
+    let entries = CONFIG_HISTORY
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (version, (config, changed_at)) = item?;
+            Ok(ConfigHistoryEntry {
+                version,
+                config,
+                changed_at,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ConfigHistoryResponse { entries })
+}
+
+pub fn sub_daos(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SubDaosResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+    let start = maybe_addr(deps.api, start_after)?;
+
+    let sub_daos = SUB_DAOS
+        .keys(
+            deps.storage,
+            start.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SubDaosResponse { sub_daos })
+}
+
+pub fn list_multisigs(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListMultisigsResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+    let multisigs = SUB_MULTISIGS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (label, address) = item?;
+            Ok(MultisigInfo { label, address })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ListMultisigsResponse { multisigs })
+}
+
+pub fn cosponsors(
+    deps: Deps,
+    proposal_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<CosponsorsResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let limit = get_and_check_limit(limit, cfg.max_query_limit, cfg.default_query_limit)? as usize;
+    let start = maybe_addr(deps.api, start_after)?;
+
+    let cosponsors = COSPONSORS
+        .prefix(proposal_id)
+        .keys(
+            deps.storage,
+            start.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CosponsorsResponse { cosponsors })
+}
+
+pub fn version(deps: Deps) -> StdResult<VersionResponse> {
+    let staking_contract = STAKING_CONTRACT.load(deps.storage)?;
+
+    Ok(VersionResponse {
+        dao: get_contract_version(deps.storage)?,
+        staking: CONTRACT.query(&deps.querier, staking_contract)?,
+    })
+}
+
+pub fn simulate_osmosis_execution(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<SimulateResponse> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+
+    let messages = prop
+        .msgs
+        .into_iter()
+        .map(|msg| {
+            let swap_estimate = match msg {
+                CosmosMsg::Custom(OsmosisMsg::Swap {
+                    first,
+                    route,
+                    amount,
+                }) => {
+                    let query = OsmosisQuery::EstimateSwap {
+                        sender: env.contract.address.to_string(),
+                        first,
+                        route,
+                        amount: amount.discard_limit(),
+                    };
+                    let res: EstimatePriceResponse = deps.querier.query(&query.into())?;
+                    Some(res.amount)
+                }
+                _ => None,
+            };
+
+            Ok(MsgSimResult {
+                estimated_gas: DEFAULT_MSG_GAS,
+                swap_estimate,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SimulateResponse {
+        estimated_gas: messages.iter().map(|m| m.estimated_gas).sum(),
+        messages,
+    })
+}
+
+pub fn simulate_execution(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<SimulateExecutionResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let mut blocking_reasons = vec![];
+
+    if let Some(expiration) = DAO_PAUSED.may_load(deps.storage)? {
+        if !expiration.is_expired(&env.block) {
+            blocking_reasons.push(ContractError::PausedUntil { expiration }.to_string());
+        }
+    }
+
+    let executable_at = prop.executable_at(cfg.execution_delay)?;
+    if !executable_at.is_expired(&env.block) {
+        blocking_reasons.push(ContractError::NotExpired {}.to_string());
+    }
+    if let Some(window) = cfg.execution_window {
+        if (executable_at + window)?.is_expired(&env.block) {
+            blocking_reasons.push(ContractError::ExecutionWindowElapsed {}.to_string());
+        }
+    }
+
+    let status = prop.current_status(&env.block);
+    if status != Status::Passed {
+        blocking_reasons.push(
+            ContractError::InvalidProposalStatus {
+                current: format!("{:?}", status),
+                desired: format!("{:?}", Status::Passed),
+            }
+            .to_string(),
+        );
+    }
+    if cfg.recheck_quorum_on_execute && !passes_rechecked_quorum(deps, &prop)? {
+        blocking_reasons.push(ContractError::QuorumNotMet {}.to_string());
+    }
+    // everything the DAO itself has to pay out on execution
+    let bonus = participation_bonus(deps, &env.contract.address, &cfg, &prop)?;
+    let outflow = [prop.msgs, bonus.msgs()].concat();
+    match check_reserves(deps, &env.contract.address, &cfg.reserves, &outflow) {
+        Ok(()) => {}
+        Err(ContractError::Std(err)) => return Err(err),
+        Err(err) => blocking_reasons.push(err.to_string()),
+    }
+
+    let mut spends = NativeBalance::default();
+    for msg in &outflow {
+        if let CosmosMsg::Bank(BankMsg::Send { amount, .. }) = msg {
+            spends += NativeBalance(amount.clone());
+        }
+    }
+    spends.normalize();
+    for spend in spends.into_vec() {
+        let held = deps
+            .querier
+            .query_balance(&env.contract.address, &spend.denom)?;
+        if held.amount < spend.amount {
+            blocking_reasons.push(format!(
+                "Insufficient treasury balance: {} required, {} held",
+                spend, held
+            ));
+        }
+    }
+
+    Ok(SimulateExecutionResponse {
+        will_succeed: blocking_reasons.is_empty(),
+        blocking_reasons,
+    })
+}
+
+pub fn expiration_status(env: Env, expiration: Expiration) -> ExpirationStatusResponse {
+    let (blocks_remaining, seconds_remaining) = match expiration {
+        Expiration::AtHeight(height) => (Some(height.saturating_sub(env.block.height)), None),
+        Expiration::AtTime(time) => (
+            None,
+            Some(time.seconds().saturating_sub(env.block.time.seconds())),
+        ),
+        Expiration::Never {} => (None, None),
+    };
+
+    ExpirationStatusResponse {
+        is_expired: expiration.is_expired(&env.block),
+        blocks_remaining,
+        seconds_remaining,
+    }
+}
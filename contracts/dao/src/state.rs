@@ -1,7 +1,8 @@
 use std::convert::TryInto;
 
 use crate::ContractError;
-use cosmwasm_std::{Addr, Empty, StdError, StdResult, Storage, Uint128};
+use cosmwasm_std::{Addr, Api, Decimal, Empty, StdError, StdResult, Storage, Uint128};
+use cw20::Denom;
 use cw3::Vote;
 use cw_storage_plus::{Item, Map};
 use cw_utils::{Duration, Expiration};
@@ -9,6 +10,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub use crate::proposal::{BlockTime, Proposal, Votes};
+use crate::threshold::valid_percentage;
 pub use crate::threshold::Threshold;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -20,10 +22,376 @@ pub struct Config {
     pub deposit_period: Duration,
     pub proposal_deposit: Uint128,
     pub proposal_min_deposit: Uint128,
+    /// (quorum, bonus) - proposer of an executed proposal whose participation
+    /// reached the quorum receives the bonus from the treasury
+    pub high_participation_bonus: Option<(Decimal, Uint128)>,
+    /// close a few expired pending proposals on each new proposal
+    #[serde(default)]
+    pub auto_reap: bool,
+    /// reject proposals whose link isn't an http(s) / ipfs uri
+    #[serde(default)]
+    pub require_valid_link: bool,
+    /// accept additional deposits on proposals that are already open
+    #[serde(default)]
+    pub allow_deposit_top_up: bool,
+    /// wait after `vote_ends_at` before a stuck proposal can be force-closed
+    #[serde(default = "default_force_close_grace_period")]
+    pub force_close_grace_period: Duration,
+    /// pagination settings for list queries
+    #[serde(default = "default_max_query_limit")]
+    pub max_query_limit: u32,
+    #[serde(default = "default_default_query_limit")]
+    pub default_query_limit: u32,
+    /// who may trigger the execution of a passed proposal
+    #[serde(default)]
+    pub execute_permission: ExecutePermission,
+    /// timelock after `vote_ends_at` during which a passed proposal can still be vetoed
+    pub execution_delay: Option<Duration>,
+    /// largest amount a single `propose` / `deposit` call may contribute
+    pub max_single_deposit: Option<Uint128>,
+    /// depositors a proposal needs, on top of the full deposit, before voting opens
+    pub min_distinct_depositors: Option<u32>,
+    /// raise the threshold of new proposals while little of the supply is staked,
+    /// by `threshold_scaling_coefficient * (1 - staked / total_token_supply)`
+    #[serde(default)]
+    pub auto_scale_threshold: bool,
+    /// set manually, only used by `auto_scale_threshold`
+    #[serde(default)]
+    pub total_token_supply: Uint128,
+    #[serde(default)]
+    pub threshold_scaling_coefficient: Decimal,
+    /// relaxed quorum for proposals created while the DAO is young
+    pub bootstrap: Option<Bootstrap>,
+    /// contracts sent a `NotifierMsg::ProposalExpired` once an open proposal's voting
+    /// period is over, by the next `vote` / `execute`
+    #[serde(default)]
+    pub notifiers: Vec<Addr>,
+    /// display only: decimals of the gov token, so clients can format deposits and balances
+    #[serde(default)]
+    pub gov_token_decimals: Option<u8>,
+    /// prefixed to every `action` attribute (`<namespace>/propose`), so indexers shared by
+    /// several DAOs can tell them apart
+    #[serde(default)]
+    pub event_namespace: Option<String>,
+    /// `threshold` and `quorum` a proposal needs to change a given field, every changed field's
+    /// must be met. The other `Threshold` fields are ignored. Fields not listed follow the
+    /// usual rules
+    #[serde(default)]
+    pub field_thresholds: Vec<(ConfigFieldKey, Threshold)>,
+    /// add cw20 tokens sent to the DAO (`Receive`) to the treasury token list
+    #[serde(default)]
+    pub auto_register_cw20: bool,
+    /// how long a passed proposal stays executable once its execution delay is over, after
+    /// that it can only be expired (`ExpireExecution`), refunding the deposit
+    #[serde(default)]
+    pub execution_window: Option<Duration>,
+    /// re-query the total stake at the eligibility height before executing and make sure the
+    /// proposal still passes against it
+    #[serde(default)]
+    pub recheck_quorum_on_execute: bool,
+    /// blocks a voter must have held their stake before voting opens, voting power is read
+    /// that many blocks before `vote_starts_at`
+    #[serde(default)]
+    pub voting_stake_age: u64,
+    /// treasury balance a proposal may not spend below, per denom. Deposits held by the DAO
+    /// don't count towards it
+    #[serde(default)]
+    pub reserves: Vec<(Denom, Uint128)>,
+    /// where the deposits of rejected proposals go when they are confiscated
+    #[serde(default)]
+    pub confiscation_policy: ConfiscationPolicy,
+    /// keep the tally of every block a proposal was voted in, see `TALLY_HISTORY`
+    #[serde(default)]
+    pub record_tally_history: bool,
+    /// most tokens `TREASURY_TOKENS` may hold, bounds the cost of the treasury balance queries
+    #[serde(default = "default_max_treasury_tokens")]
+    pub max_treasury_tokens: u32,
+}
+
+fn default_max_treasury_tokens() -> u32 {
+    crate::DEFAULT_MAX_TREASURY_TOKENS
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigFieldKey {
+    Name,
+    Description,
+    Threshold,
+    VotingPeriod,
+    DepositPeriod,
+    ProposalDeposit,
+    ProposalMinDeposit,
+    HighParticipationBonus,
+    AutoReap,
+    RequireValidLink,
+    AllowDepositTopUp,
+    ForceCloseGracePeriod,
+    MaxQueryLimit,
+    DefaultQueryLimit,
+    ExecutePermission,
+    ExecutionDelay,
+    MaxSingleDeposit,
+    MinDistinctDepositors,
+    AutoScaleThreshold,
+    TotalTokenSupply,
+    ThresholdScalingCoefficient,
+    Bootstrap,
+    Notifiers,
+    GovTokenDecimals,
+    EventNamespace,
+    FieldThresholds,
+    AutoRegisterCw20,
+    ExecutionWindow,
+    RecheckQuorumOnExecute,
+    VotingStakeAge,
+    Reserves,
+    ConfiscationPolicy,
+    RecordTallyHistory,
+    MaxTreasuryTokens,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Bootstrap {
+    pub until: Expiration,
+    pub quorum: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutePermission {
+    #[default]
+    Anyone,
+    ProposerOnly,
+    Allowlist(Vec<Addr>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfiscationPolicy {
+    /// keep confiscated deposits in the DAO treasury
+    #[default]
+    Treasury,
+    /// hand confiscated deposits to the staking contract as unboosted rewards
+    /// (`FundUnboosted`), they stay in the treasury if it refuses them
+    RewardStakers,
+}
+
+impl ExecutePermission {
+    pub fn allows(&self, sender: &Addr, proposer: &Addr) -> bool {
+        match self {
+            ExecutePermission::Anyone => true,
+            ExecutePermission::ProposerOnly => sender == proposer,
+            ExecutePermission::Allowlist(addrs) => addrs.contains(sender),
+        }
+    }
+}
+
+/// only seen between a code upgrade and its `migrate`, which sets the voting period instead
+/// (the `instantiate` default) so the unit always matches
+fn default_force_close_grace_period() -> Duration {
+    Duration::Height(0)
+}
+
+fn default_max_query_limit() -> u32 {
+    crate::MAX_LIMIT
+}
+
+fn default_default_query_limit() -> u32 {
+    crate::DEFAULT_LIMIT
 }
 
 impl Config {
-    pub fn validate(&self) -> Result<(), ContractError> {
+    /// Fields that differ in `other`
+    pub fn changed_fields(&self, other: &Config) -> Vec<ConfigFieldKey> {
+        use ConfigFieldKey::*;
+
+        [
+            (self.name != other.name, Name),
+            (self.description != other.description, Description),
+            (self.threshold != other.threshold, Threshold),
+            (self.voting_period != other.voting_period, VotingPeriod),
+            (self.deposit_period != other.deposit_period, DepositPeriod),
+            (
+                self.proposal_deposit != other.proposal_deposit,
+                ProposalDeposit,
+            ),
+            (
+                self.proposal_min_deposit != other.proposal_min_deposit,
+                ProposalMinDeposit,
+            ),
+            (
+                self.high_participation_bonus != other.high_participation_bonus,
+                HighParticipationBonus,
+            ),
+            (self.auto_reap != other.auto_reap, AutoReap),
+            (
+                self.require_valid_link != other.require_valid_link,
+                RequireValidLink,
+            ),
+            (
+                self.allow_deposit_top_up != other.allow_deposit_top_up,
+                AllowDepositTopUp,
+            ),
+            (
+                self.force_close_grace_period != other.force_close_grace_period,
+                ForceCloseGracePeriod,
+            ),
+            (self.max_query_limit != other.max_query_limit, MaxQueryLimit),
+            (
+                self.default_query_limit != other.default_query_limit,
+                DefaultQueryLimit,
+            ),
+            (
+                self.execute_permission != other.execute_permission,
+                ExecutePermission,
+            ),
+            (
+                self.execution_delay != other.execution_delay,
+                ExecutionDelay,
+            ),
+            (
+                self.max_single_deposit != other.max_single_deposit,
+                MaxSingleDeposit,
+            ),
+            (
+                self.min_distinct_depositors != other.min_distinct_depositors,
+                MinDistinctDepositors,
+            ),
+            (
+                self.auto_scale_threshold != other.auto_scale_threshold,
+                AutoScaleThreshold,
+            ),
+            (
+                self.total_token_supply != other.total_token_supply,
+                TotalTokenSupply,
+            ),
+            (
+                self.threshold_scaling_coefficient != other.threshold_scaling_coefficient,
+                ThresholdScalingCoefficient,
+            ),
+            (self.bootstrap != other.bootstrap, Bootstrap),
+            (self.notifiers != other.notifiers, Notifiers),
+            (
+                self.gov_token_decimals != other.gov_token_decimals,
+                GovTokenDecimals,
+            ),
+            (
+                self.event_namespace != other.event_namespace,
+                EventNamespace,
+            ),
+            (
+                self.field_thresholds != other.field_thresholds,
+                FieldThresholds,
+            ),
+            (
+                self.auto_register_cw20 != other.auto_register_cw20,
+                AutoRegisterCw20,
+            ),
+            (
+                self.execution_window != other.execution_window,
+                ExecutionWindow,
+            ),
+            (
+                self.recheck_quorum_on_execute != other.recheck_quorum_on_execute,
+                RecheckQuorumOnExecute,
+            ),
+            (
+                self.voting_stake_age != other.voting_stake_age,
+                VotingStakeAge,
+            ),
+            (self.reserves != other.reserves, Reserves),
+            (
+                self.confiscation_policy != other.confiscation_policy,
+                ConfiscationPolicy,
+            ),
+            (
+                self.record_tally_history != other.record_tally_history,
+                RecordTallyHistory,
+            ),
+            (
+                self.max_treasury_tokens != other.max_treasury_tokens,
+                MaxTreasuryTokens,
+            ),
+        ]
+        .iter()
+        .filter_map(|&(changed, field)| changed.then_some(field))
+        .collect()
+    }
+
+    /// The most demanding changed field whose `field_thresholds` entry `actual` falls short of
+    pub fn unmet_field_threshold(
+        &self,
+        other: &Config,
+        actual: &Threshold,
+    ) -> Option<(ConfigFieldKey, Threshold)> {
+        self.changed_fields(other)
+            .into_iter()
+            .filter_map(|field| {
+                self.field_thresholds
+                    .iter()
+                    .find(|(key, _)| *key == field)
+                    .cloned()
+            })
+            .filter(|(_, required)| {
+                actual.threshold < required.threshold || actual.quorum < required.quorum
+            })
+            .max_by_key(|(_, required)| (required.threshold, required.quorum))
+    }
+
+    pub fn validate(&self, api: &dyn Api) -> Result<(), ContractError> {
+        if let ExecutePermission::Allowlist(allowlist) = &self.execute_permission {
+            for addr in allowlist {
+                api.addr_validate(addr.as_str())?;
+            }
+        }
+
+        for (_, required) in &self.field_thresholds {
+            required.validate()?;
+        }
+
+        for notifier in &self.notifiers {
+            api.addr_validate(notifier.as_str())?;
+        }
+
+        if let Some((quorum, _)) = &self.high_participation_bonus {
+            valid_percentage(quorum)?;
+        }
+
+        if self.default_query_limit == 0 || self.default_query_limit > self.max_query_limit {
+            return Err(ContractError::InvalidQueryLimit {});
+        }
+
+        if let Some(bootstrap) = &self.bootstrap {
+            valid_percentage(&bootstrap.quorum)?;
+            // bootstrap only ever relaxes the quorum
+            if bootstrap.quorum > self.threshold.quorum {
+                return Err(ContractError::InvalidBootstrapQuorum {});
+            }
+        }
+
+        if let Some(namespace) = &self.event_namespace {
+            if namespace.is_empty() || namespace.contains('/') {
+                return Err(ContractError::InvalidEventNamespace {});
+            }
+        }
+
+        if self.auto_scale_threshold && self.total_token_supply.is_zero() {
+            return Err(ContractError::MissingTokenSupply {});
+        }
+
+        // all are added to `vote_ends_at`, so they need the same unit as the voting period
+        let delays = std::iter::once(self.force_close_grace_period)
+            .chain(self.execution_delay)
+            .chain(self.execution_window);
+        for delay in delays {
+            match (self.voting_period, delay) {
+                (Duration::Height(_), Duration::Height(_))
+                | (Duration::Time(_), Duration::Time(_)) => {}
+                _ => return Err(ContractError::InvalidPeriod {}),
+            }
+        }
+
         match (self.voting_period, self.deposit_period) {
             (Duration::Height(voting_period_height), Duration::Height(deposit_period_height)) => {
                 if voting_period_height < deposit_period_height {
@@ -47,21 +415,87 @@ impl Config {
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct Deposit {
     pub amount: Uint128,
+    /// denom the deposit was paid in, refunds go out in the same denom
+    #[serde(default)]
+    pub denom: String,
     pub claimed: bool,
 }
 
+/// Running totals over the deposits of a proposal, kept in step with `DEPOSITS`
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct ProposalDepositSummary {
+    pub total_amount: Uint128,
+    pub depositor_count: u64,
+    pub claimed_count: u64,
+}
+
 // we cast a ballot with our chosen vote and a given weight
 // stored under the key that voted
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Ballot {
     pub weight: Uint128,
     pub vote: Vote,
+    #[serde(default)]
+    pub rationale: Option<String>,
+}
+
+/// Running sums over finalized (executed / rejected) proposals
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct GovernanceStatsCache {
+    pub executed: u64,
+    pub rejected: u64,
+    /// sum of the quorum each proposal required
+    pub quorum_sum: Decimal,
+    /// sum of `total_votes / total_weight`, the quorum each proposal actually reached
+    pub turnout_sum: Decimal,
+    pub deposit_fill_sum: Decimal,
+}
+
+impl GovernanceStatsCache {
+    pub fn record(&mut self, proposal: &Proposal, executed: bool) {
+        if executed {
+            self.executed += 1;
+        } else {
+            self.rejected += 1;
+        }
+
+        self.quorum_sum += proposal.threshold.quorum;
+        self.turnout_sum += proposal.quorum();
+        if !proposal.deposit_base_amount.is_zero() {
+            self.deposit_fill_sum +=
+                Decimal::from_ratio(proposal.total_deposit, proposal.deposit_base_amount);
+        }
+    }
+
+    pub fn finalized(&self) -> u64 {
+        self.executed + self.rejected
+    }
 }
 
 // Unique items
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
 pub const DAO_PAUSED: Item<Expiration> = Item::new("dao_paused");
+/// Proposal whose messages are being dispatched, set by `execute` right before and removed
+/// in the reply to its last message
+pub const CALLING_PROPOSAL_ID: Item<u64> = Item::new("calling_proposal_id");
+pub const GOV_STATS_CACHE: Item<GovernanceStatsCache> = Item::new("gov_stats_cache");
+pub const CONFIG_VERSION: Item<u64> = Item::new("config_version");
+pub const DAO_CREATED_AT: Item<BlockTime> = Item::new("dao_created_at");
+pub const DAO_TOTAL_PROPOSALS_EXECUTED: Item<u64> = Item::new("dao_total_proposals_executed");
+pub const DAO_TOTAL_PROPOSALS_REJECTED: Item<u64> = Item::new("dao_total_proposals_rejected");
+
+// Max number of replaced configs kept in `CONFIG_HISTORY`
+pub const CONFIG_HISTORY_LIMIT: u64 = 100;
+// version => config replaced by that update, and when it happened
+pub const CONFIG_HISTORY: Map<u64, (Config, BlockTime)> = Map::new("config_history");
+
+// label -> address of cw3 multisigs deployed (and administered) by the DAO
+pub const SUB_MULTISIGS: Map<String, Addr> = Map::new("sub_multisigs");
+// label of the multisig waiting for its instantiate reply
+pub const PENDING_MULTISIG_LABEL: Item<String> = Item::new("pending_multisig_label");
+// address -> Empty, sub-DAOs deployed by the DAO through `InstantiateSubDao`
+pub const SUB_DAOS: Map<&Addr, Empty> = Map::new("sub_daos");
 
 // Total weight and voters are queried from this contract
 pub const STAKING_CONTRACT: Item<Addr> = Item::new("staking_contract");
@@ -82,6 +516,18 @@ pub const IDX_DEPOSITS_BY_DEPOSITOR: Map<(Addr, u64), Empty> =
 pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
 pub const IDX_PROPS_BY_STATUS: Map<(u8, u64), Empty> = Map::new("idx_props_by_status");
 pub const IDX_PROPS_BY_PROPOSER: Map<(Addr, u64), Empty> = Map::new("idx_props_by_proposer");
+pub const IDX_PROPS_BY_UPDATE_HEIGHT: Map<(u64, u64), Empty> =
+    Map::new("idx_props_by_update_height"); // last_updated_height => proposal_id => Empty
+pub const IDX_PROPS_BY_DEPOSIT: Map<(u128, u64), Empty> = Map::new("idx_props_by_deposit"); // total_deposit => proposal_id => Empty
+pub const IDX_PROPS_BY_CATEGORY: Map<(String, u64), Empty> = Map::new("idx_props_by_category"); // category => proposal_id => Empty
+pub const CATEGORIES: Map<&str, Empty> = Map::new("categories"); // proposal categories allowed by governance
+pub const COSPONSORS: Map<(u64, &Addr), Empty> = Map::new("cosponsors"); // proposal_id => cosponsor => Empty
+pub const NOTIFIED_EXPIRED: Map<u64, Empty> = Map::new("notified_expired"); // proposal_id => Empty, notifiers already told
+pub const UNNOTIFIED_OPEN: Map<u64, Empty> = Map::new("unnotified_open"); // proposal_id => Empty, open and notifiers not told yet
+pub const DEPOSIT_SUMMARIES: Map<u64, ProposalDepositSummary> = Map::new("deposit_summaries");
+pub const TALLY_HISTORY: Map<(u64, u64), Votes> = Map::new("tally_history"); // (proposal_id, height) => tally at the end of that block's votes
+pub const ESCROWED_DEPOSITS: Map<&str, Uint128> = Map::new("escrowed_deposits"); // denom => deposits held for their depositors, not withdrawn, claimed or confiscated yet
+pub const CONFISCATED_TOTAL: Map<&str, Uint128> = Map::new("confiscated_total"); // denom => deposits confiscated so far
 pub const TREASURY_TOKENS: Map<(&str, &str), Empty> = Map::new("treasury_tokens"); // token_type => token_{denom / address} => Empty
 
 pub fn next_id(store: &mut dyn Storage) -> StdResult<u64> {
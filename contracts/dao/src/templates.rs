@@ -0,0 +1,74 @@
+//! Ready-made `ProposeMsg`s for the proposals DAOs submit over and over.
+//! The link is a placeholder the proposer is expected to replace before submitting.
+
+use cosmwasm_std::{coins, to_binary, Addr, BankMsg, StdResult, Uint128, WasmMsg};
+use cw_utils::{Duration, Expiration};
+
+use crate::msg::{ExecuteMsg, ProposeMsg};
+use crate::state::Config;
+
+pub const LINK_PLACEHOLDER: &str = "https://";
+
+fn propose_msg(title: String, description: String, msg: impl Into<crate::CosmosMsg>) -> ProposeMsg {
+    ProposeMsg {
+        title,
+        link: LINK_PLACEHOLDER.to_string(),
+        description,
+        msgs: vec![msg.into()],
+        cosmos_gov_vote: None,
+        parent_proposal: None,
+        category: None,
+    }
+}
+
+/// Sends `amount` of the native `denom` from the treasury to `recipient`
+pub fn treasury_transfer(recipient: &Addr, amount: Uint128, denom: &str) -> ProposeMsg {
+    propose_msg(
+        format!("Treasury transfer: {}{} to {}", amount, denom, recipient),
+        format!(
+            "Send {}{} from the DAO treasury to {}",
+            amount, denom, recipient
+        ),
+        BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), denom),
+        },
+    )
+}
+
+/// Replaces the voting period of `dao`, keeping the rest of its current `config`
+pub fn update_voting_period(
+    dao: &Addr,
+    config: Config,
+    voting_period: Duration,
+) -> StdResult<ProposeMsg> {
+    let description = format!(
+        "Change the voting period from {} to {}",
+        config.voting_period, voting_period
+    );
+    Ok(propose_msg(
+        format!("Update voting period to {}", voting_period),
+        description,
+        WasmMsg::Execute {
+            contract_addr: dao.to_string(),
+            msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(Config {
+                voting_period,
+                ..config
+            })))?,
+            funds: vec![],
+        },
+    ))
+}
+
+/// Pauses the governance of `dao` until `expiration`
+pub fn pause_dao(dao: &Addr, expiration: Expiration) -> StdResult<ProposeMsg> {
+    Ok(propose_msg(
+        format!("Pause DAO until {}", expiration),
+        format!("Reject proposals, deposits and votes until {}", expiration),
+        WasmMsg::Execute {
+            contract_addr: dao.to_string(),
+            msg: to_binary(&ExecuteMsg::PauseDAO { expiration })?,
+            funds: vec![],
+        },
+    ))
+}
@@ -0,0 +1,579 @@
+//! Proposal state machine, driven straight through the `execute` handlers.
+//!
+//! Pending --deposit--> Open --voting ends--> Passed --execute--> Executed
+//!    |                   |                      |
+//!    +------close--------+------close-----------+--force_close--> Rejected
+//!
+//! Passed is never written by a message, it is what `current_status` derives once a
+//! successful vote ends. Nothing leads back to Pending.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    coins, from_binary, to_binary, Addr, ContractResult, Decimal, Empty, Env, Order, OwnedDeps,
+    Reply, StdResult, SubMsgResponse, SubMsgResult, SystemError, SystemResult, Uint128, WasmQuery,
+};
+use cw3::{Status, Vote};
+use cw_utils::{Duration, Expiration};
+use osmo_bindings::OsmosisQuery;
+
+use crate::contract::{migrate, reply, INSTANTIATE_STAKING_CONTRACT_REPLY_ID};
+use crate::msg::{AuditResponse, DepositMismatch, MigrateMsg, StatusIndexMismatch};
+use crate::state::{
+    Ballot, Config, Deposit, ExecutePermission, Proposal, ProposalDepositSummary, Votes, BALLOTS,
+    CONFIG, DEPOSITS, DEPOSIT_SUMMARIES, GOV_TOKEN, IDX_PROPS_BY_DEPOSIT, IDX_PROPS_BY_STATUS,
+    NOTIFIED_EXPIRED, PROPOSALS, STAKING_CONTRACT, UNNOTIFIED_OPEN,
+};
+use crate::threshold::Threshold;
+use crate::ContractError;
+use crate::{execute, query};
+
+const PROP_ID: u64 = 1;
+const DENOM: &str = "denom";
+const STATUSES: [Status; 5] = [
+    Status::Pending,
+    Status::Open,
+    Status::Rejected,
+    Status::Passed,
+    Status::Executed,
+];
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier<OsmosisQuery>, OsmosisQuery>;
+
+fn mock_deps() -> MockDeps {
+    let mut querier = MockQuerier::<OsmosisQuery>::new(&[]);
+    // every voter holds the whole voting weight
+    querier.update_wasm(|query| match query {
+        WasmQuery::Smart { msg, .. } => match from_binary(msg) {
+            Ok(ion_stake::msg::QueryMsg::TotalStakedAtHeight { height }) => {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&ion_stake::msg::TotalStakedAtHeightResponse {
+                        total: Uint128::new(100),
+                        height: height.unwrap_or_default(),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Ok(ContractResult::Ok(
+                to_binary(&ion_stake::msg::StakedBalanceAtHeightResponse {
+                    balance: Uint128::new(100),
+                    height: 0,
+                })
+                .unwrap(),
+            )),
+        },
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "non-smart wasm query".to_string(),
+        }),
+    });
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier,
+        custom_query_type: PhantomData,
+    };
+
+    CONFIG
+        .save(
+            &mut deps.storage,
+            &Config {
+                name: "dao".to_string(),
+                description: "desc".to_string(),
+                threshold: Threshold {
+                    threshold: Decimal::percent(50),
+                    quorum: Decimal::percent(33),
+                    veto_threshold: Decimal::percent(33),
+                    min_voters: None,
+                    strict: false,
+                },
+                voting_period: Duration::Height(20),
+                deposit_period: Duration::Height(10),
+                proposal_deposit: Uint128::new(100),
+                proposal_min_deposit: Uint128::new(10),
+                high_participation_bonus: None,
+                auto_reap: false,
+                require_valid_link: false,
+                allow_deposit_top_up: false,
+                force_close_grace_period: Duration::Height(10),
+                max_query_limit: 30,
+                default_query_limit: 10,
+                execute_permission: ExecutePermission::Anyone,
+                execution_delay: None,
+                max_single_deposit: None,
+                min_distinct_depositors: None,
+                auto_scale_threshold: false,
+                total_token_supply: Uint128::zero(),
+                threshold_scaling_coefficient: Decimal::zero(),
+                bootstrap: None,
+                notifiers: vec![],
+                gov_token_decimals: None,
+                event_namespace: None,
+                field_thresholds: vec![],
+                auto_register_cw20: false,
+                execution_window: None,
+                recheck_quorum_on_execute: false,
+                voting_stake_age: 0,
+                reserves: vec![],
+                confiscation_policy: Default::default(),
+                record_tally_history: false,
+                max_treasury_tokens: crate::DEFAULT_MAX_TREASURY_TOKENS,
+            },
+        )
+        .unwrap();
+    GOV_TOKEN
+        .save(&mut deps.storage, &DENOM.to_string())
+        .unwrap();
+    STAKING_CONTRACT
+        .save(&mut deps.storage, &Addr::unchecked("stake"))
+        .unwrap();
+
+    deps
+}
+
+/// Injects proposal `PROP_ID` with `status` as of `mock_env()`: the deposit period ends
+/// 10 blocks later and the voting period 20 blocks later. Passed / Executed ones carry
+/// the votes that made them pass
+fn prop_with_status(deps: &mut MockDeps, status: Status) {
+    let env = mock_env();
+    let height = env.block.height;
+    let voted = matches!(status, Status::Passed | Status::Executed);
+    let prop = Proposal {
+        proposer: Addr::unchecked("proposer"),
+        status,
+        submitted_at: env.block.clone().into(),
+        deposit_ends_at: Expiration::AtHeight(height + 10),
+        vote_starts_at: env.block.into(),
+        vote_ends_at: Expiration::AtHeight(height + 20),
+        threshold: CONFIG.load(&deps.storage).unwrap().threshold,
+        total_weight: Uint128::new(100),
+        votes: if voted {
+            Votes::new(Uint128::new(100))
+        } else {
+            Votes::default()
+        },
+        voter_count: voted as u64,
+        total_deposit: if status == Status::Pending {
+            Uint128::new(10)
+        } else {
+            Uint128::new(100)
+        },
+        deposit_base_amount: Uint128::new(100),
+        ..Default::default()
+    };
+    PROPOSALS.save(&mut deps.storage, PROP_ID, &prop).unwrap();
+    IDX_PROPS_BY_STATUS
+        .save(
+            &mut deps.storage,
+            (status as u8, PROP_ID),
+            &Default::default(),
+        )
+        .unwrap();
+}
+
+/// `mock_env()` once every period of an injected proposal, force close grace included, is over
+fn ended_env() -> Env {
+    let mut env = mock_env();
+    env.block.height += 40;
+    env
+}
+
+/// Runs the message that moves a proposal to `to` and returns the status it ended up in
+fn transition(deps: &mut MockDeps, to: Status) -> Result<Status, ContractError> {
+    let env = mock_env();
+    let dao = env.contract.address.to_string();
+    match to {
+        Status::Open => {
+            let info = mock_info("depositor", &coins(90, DENOM));
+            execute::deposit(deps.as_mut(), env, info, PROP_ID)?;
+        }
+        Status::Passed => {
+            let info = mock_info("voter", &[]);
+            execute::vote(deps.as_mut(), env, info, PROP_ID, Vote::Yes, None)?;
+        }
+        Status::Executed => {
+            let info = mock_info("executor", &[]);
+            execute::execute(deps.as_mut(), ended_env(), info, PROP_ID)?;
+        }
+        Status::Rejected => {
+            // a passed proposal can only be thrown out by governance
+            let prop = PROPOSALS.load(&deps.storage, PROP_ID)?;
+            if prop.status == Status::Passed {
+                let info = mock_info(&dao, &[]);
+                execute::force_close(deps.as_mut(), ended_env(), info, PROP_ID)?;
+            } else {
+                let info = mock_info("closer", &[]);
+                execute::close(deps.as_mut(), ended_env(), info, PROP_ID)?;
+            }
+        }
+        Status::Pending => unreachable!("no message leads back to pending"),
+    }
+
+    let prop = PROPOSALS.load(&deps.storage, PROP_ID)?;
+    if to == Status::Passed {
+        // the vote only counts once the voting period is over
+        Ok(prop.current_status(&ended_env().block))
+    } else {
+        Ok(prop.status)
+    }
+}
+
+fn is_allowed(from: Status, to: Status) -> bool {
+    matches!(
+        (from, to),
+        (Status::Pending, Status::Open)
+            | (Status::Pending, Status::Rejected)
+            | (Status::Open, Status::Passed)
+            | (Status::Open, Status::Rejected)
+            | (Status::Passed, Status::Executed)
+            | (Status::Passed, Status::Rejected)
+    )
+}
+
+fn assert_status_index(deps: &MockDeps) {
+    let stored = PROPOSALS.load(&deps.storage, PROP_ID).unwrap().status;
+    for status in STATUSES {
+        assert_eq!(
+            IDX_PROPS_BY_STATUS.has(&deps.storage, (status as u8, PROP_ID)),
+            status == stored,
+            "status index out of sync, proposal is {:?}",
+            stored
+        );
+    }
+}
+
+#[test]
+fn should_follow_state_machine() {
+    for from in STATUSES {
+        for to in STATUSES {
+            if to == Status::Pending {
+                continue;
+            }
+
+            let mut deps = mock_deps();
+            prop_with_status(&mut deps, from);
+
+            let res = transition(&mut deps, to);
+            if is_allowed(from, to) {
+                assert_eq!(res, Ok(to), "{:?} -> {:?}", from, to);
+            } else {
+                assert!(
+                    matches!(res, Err(ContractError::InvalidProposalStatus { .. })),
+                    "{:?} -> {:?} gave {:?}",
+                    from,
+                    to,
+                    res
+                );
+                assert_eq!(PROPOSALS.load(&deps.storage, PROP_ID).unwrap().status, from);
+            }
+            assert_status_index(&deps);
+        }
+    }
+}
+
+#[test]
+fn should_never_fall_back_to_pending() {
+    for from in STATUSES {
+        let mut deps = mock_deps();
+        prop_with_status(&mut deps, from);
+        let prop = PROPOSALS.load(&deps.storage, PROP_ID).unwrap();
+
+        for env in [mock_env(), ended_env()] {
+            let status = prop.current_status(&env.block);
+            assert!(
+                from == Status::Pending || status != Status::Pending,
+                "{:?} fell back to pending",
+                from
+            );
+        }
+    }
+}
+
+#[test]
+fn should_recheck_quorum_on_execute() {
+    // 100 yes votes, the 33% quorum needs a total stake of at most 303
+    for (recheck, total_staked, passes) in
+        [(true, 300, true), (true, 400, false), (false, 400, true)]
+    {
+        let mut deps = mock_deps();
+        prop_with_status(&mut deps, Status::Passed);
+        CONFIG
+            .update(&mut deps.storage, |mut cfg| -> StdResult<_> {
+                cfg.recheck_quorum_on_execute = recheck;
+                Ok(cfg)
+            })
+            .unwrap();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+                ion_stake::msg::QueryMsg::TotalStakedAtHeight { height } => {
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&ion_stake::msg::TotalStakedAtHeightResponse {
+                            total: Uint128::new(total_staked),
+                            height: height.unwrap(),
+                        })
+                        .unwrap(),
+                    ))
+                }
+                _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: "staking query".to_string(),
+                }),
+            },
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "non-smart wasm query".to_string(),
+            }),
+        });
+
+        let simulation = query::simulate_execution(deps.as_ref(), ended_env(), PROP_ID).unwrap();
+        assert_eq!(simulation.will_succeed, passes);
+
+        let res = execute::execute(
+            deps.as_mut(),
+            ended_env(),
+            mock_info("executor", &[]),
+            PROP_ID,
+        );
+        let status = PROPOSALS.load(&deps.storage, PROP_ID).unwrap().status;
+        if passes {
+            assert!(res.is_ok(), "{} staked gave {:?}", total_staked, res);
+            assert_eq!(status, Status::Executed);
+        } else {
+            assert_eq!(res.unwrap_err(), ContractError::QuorumNotMet {});
+            assert_eq!(status, Status::Passed);
+        }
+    }
+}
+
+#[test]
+fn should_rebuild_deposit_summaries_on_migrate() {
+    let mut deps = mock_deps();
+    prop_with_status(&mut deps, Status::Open);
+    for (depositor, amount, claimed) in [("alice", 60, true), ("bob", 40, false)] {
+        let deposit = Deposit {
+            amount: Uint128::new(amount),
+            denom: DENOM.to_string(),
+            claimed,
+        };
+        DEPOSITS
+            .save(
+                &mut deps.storage,
+                (PROP_ID, Addr::unchecked(depositor)),
+                &deposit,
+            )
+            .unwrap();
+    }
+
+    IDX_PROPS_BY_DEPOSIT
+        .save(&mut deps.storage, (999, PROP_ID), &Empty {})
+        .unwrap();
+
+    // running it twice must not count anything twice
+    for _ in 0..2 {
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    }
+
+    assert_eq!(
+        DEPOSIT_SUMMARIES.load(&deps.storage, PROP_ID).unwrap(),
+        ProposalDepositSummary {
+            total_amount: Uint128::new(100),
+            depositor_count: 2,
+            claimed_count: 1,
+        }
+    );
+    assert_eq!(
+        IDX_PROPS_BY_DEPOSIT
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap(),
+        vec![(100, PROP_ID)]
+    );
+}
+
+#[test]
+fn should_reuse_ballot_weight_on_revote() {
+    let mut deps = mock_deps();
+    prop_with_status(&mut deps, Status::Open);
+
+    let queries = Rc::new(Cell::new(0));
+    let counter = queries.clone();
+    deps.querier.update_wasm(move |_| {
+        counter.set(counter.get() + 1);
+        SystemResult::Ok(ContractResult::Ok(
+            to_binary(&ion_stake::msg::StakedBalanceAtHeightResponse {
+                balance: Uint128::new(40),
+                height: 0,
+            })
+            .unwrap(),
+        ))
+    });
+
+    let voter = mock_info("voter", &[]);
+    execute::vote(
+        deps.as_mut(),
+        mock_env(),
+        voter.clone(),
+        PROP_ID,
+        Vote::No,
+        None,
+    )
+    .unwrap();
+    assert_eq!(queries.get(), 1);
+
+    execute::vote(
+        deps.as_mut(),
+        mock_env(),
+        voter.clone(),
+        PROP_ID,
+        Vote::Yes,
+        None,
+    )
+    .unwrap();
+    assert_eq!(queries.get(), 1);
+
+    let prop = PROPOSALS.load(&deps.storage, PROP_ID).unwrap();
+    assert_eq!(prop.votes, Votes::new(Uint128::new(40)));
+    assert_eq!(prop.voter_count, 1);
+    assert_eq!(
+        BALLOTS
+            .load(&deps.storage, (PROP_ID, &voter.sender))
+            .unwrap(),
+        Ballot {
+            weight: Uint128::new(40),
+            vote: Vote::Yes,
+            rationale: None,
+        }
+    );
+
+    // a new voter still asks the staking contract
+    execute::vote(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other", &[]),
+        PROP_ID,
+        Vote::Yes,
+        None,
+    )
+    .unwrap();
+    assert_eq!(queries.get(), 2);
+}
+
+#[test]
+fn should_queue_unnotified_open_proposals_on_migrate() {
+    for announced in [false, true] {
+        let mut deps = mock_deps();
+        prop_with_status(&mut deps, Status::Open);
+        if announced {
+            NOTIFIED_EXPIRED
+                .save(&mut deps.storage, PROP_ID, &Empty {})
+                .unwrap();
+        }
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        assert_eq!(UNNOTIFIED_OPEN.has(&deps.storage, PROP_ID), !announced);
+    }
+}
+
+#[test]
+fn should_report_malformed_staking_reply() {
+    let mut deps = mock_deps();
+
+    let cases = [
+        (
+            SubMsgResult::Err("out of gas".to_string()),
+            "Failure response from sub-message: out of gas",
+        ),
+        (
+            SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+            "Invalid reply from sub-message: Missing reply data",
+        ),
+    ];
+    for (result, reason) in cases {
+        let err = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: INSTANTIATE_STAKING_CONTRACT_REPLY_ID,
+                result,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InstantiateStakingError {
+                reason: reason.to_string()
+            }
+        );
+    }
+}
+
+#[test]
+fn should_audit_corrupted_storage() {
+    let mut deps = mock_deps();
+    prop_with_status(&mut deps, Status::Open);
+
+    // the proposal was never counted, holds no deposit and is indexed twice
+    IDX_PROPS_BY_STATUS
+        .save(
+            &mut deps.storage,
+            (Status::Rejected as u8, PROP_ID),
+            &Default::default(),
+        )
+        .unwrap();
+    IDX_PROPS_BY_STATUS
+        .save(
+            &mut deps.storage,
+            (Status::Pending as u8, PROP_ID + 1),
+            &Default::default(),
+        )
+        .unwrap();
+
+    let audit = query::audit(deps.as_ref(), None, None).unwrap();
+    assert_eq!(
+        audit,
+        AuditResponse {
+            healthy: false,
+            proposal_count: 0,
+            max_proposal_id: PROP_ID,
+            proposal_count_matches: false,
+            deposit_mismatches: vec![DepositMismatch {
+                id: PROP_ID,
+                total_deposit: Uint128::new(100),
+                deposited: Uint128::zero(),
+            }],
+            status_index_mismatches: vec![
+                StatusIndexMismatch {
+                    id: PROP_ID + 1,
+                    indexed: Some(Status::Pending),
+                    stored: None,
+                },
+                StatusIndexMismatch {
+                    id: PROP_ID,
+                    indexed: Some(Status::Rejected),
+                    stored: Some(Status::Open),
+                },
+            ],
+            last_id: Some(PROP_ID),
+        }
+    );
+
+    // a full page stops at its last proposal, the stray entry above it shows up on the next
+    let page = query::audit(deps.as_ref(), None, Some(1)).unwrap();
+    assert_eq!(page.status_index_mismatches.len(), 1);
+    assert_eq!(page.last_id, Some(PROP_ID));
+    let page = query::audit(deps.as_ref(), Some(PROP_ID), Some(1)).unwrap();
+    assert!(page.deposit_mismatches.is_empty());
+    assert_eq!(
+        page.status_index_mismatches,
+        vec![StatusIndexMismatch {
+            id: PROP_ID + 1,
+            indexed: Some(Status::Pending),
+            stored: None,
+        }]
+    );
+    assert_eq!(page.last_id, None);
+}
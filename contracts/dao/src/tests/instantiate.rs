@@ -1,12 +1,19 @@
-use cosmwasm_std::{Addr, Decimal, Uint128};
+use std::marker::PhantomData;
+
+use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{Addr, Decimal, OwnedDeps, Storage, Uint128};
 use cw2::query_contract_info;
 use cw20::Denom;
 use cw_multi_test::Executor;
 use cw_utils::Duration;
+use osmo_bindings::OsmosisQuery;
 use osmo_bindings_test::OsmosisApp;
 
-use crate::msg::{ConfigResponse, GovToken, InstantiateMsg, QueryMsg, TokenListResponse};
-use crate::state::Threshold;
+use crate::contract::migrate;
+use crate::msg::{
+    ConfigResponse, GovToken, InstantiateMsg, MigrateMsg, QueryMsg, TokenListResponse,
+};
+use crate::state::{Threshold, CONFIG, GOV_TOKEN};
 use crate::tests::suite::{contract_dao, contract_stake};
 use crate::ContractError;
 
@@ -43,11 +50,40 @@ fn happy_init_msg(stake: Stake) -> InstantiateMsg {
             threshold: Decimal::percent(50),
             quorum: Decimal::percent(40),
             veto_threshold: Decimal::percent(33),
+            min_voters: None,
+            strict: false,
         },
         voting_period: Duration::Height(20),
         deposit_period: Duration::Height(10),
         proposal_deposit_amount: Uint128::new(100),
         proposal_deposit_min_amount: Uint128::new(10),
+        high_participation_bonus: None,
+        auto_reap: false,
+        require_valid_link: false,
+        allow_deposit_top_up: false,
+        force_close_grace_period: None,
+        max_query_limit: None,
+        default_query_limit: None,
+        execute_permission: Default::default(),
+        execution_delay: None,
+        max_single_deposit: None,
+        min_distinct_depositors: None,
+        auto_scale_threshold: false,
+        total_token_supply: Uint128::zero(),
+        threshold_scaling_coefficient: Decimal::zero(),
+        bootstrap: None,
+        notifiers: vec![],
+        gov_token_decimals: None,
+        event_namespace: None,
+        field_thresholds: vec![],
+        auto_register_cw20: false,
+        execution_window: None,
+        recheck_quorum_on_execute: false,
+        voting_stake_age: 0,
+        reserves: vec![],
+        confiscation_policy: Default::default(),
+        record_tally_history: false,
+        max_treasury_tokens: crate::DEFAULT_MAX_TREASURY_TOKENS,
     }
 }
 
@@ -104,6 +140,8 @@ fn should_work_with_existing_stake_contract() {
                 admin: None,
                 denom: "utnt".to_string(),
                 unstaking_duration: Some(Duration::Height(20)),
+                basket_denoms: vec![],
+                bonus_denom: None,
             },
             &[],
             "new_stake",
@@ -137,6 +175,62 @@ fn should_work_with_existing_stake_contract() {
     );
 }
 
+#[test]
+fn should_fail_if_stake_contract_has_foreign_admin() {
+    let (mut app, dao_code_id, stake_code_id) = prepare();
+
+    let maker = Addr::unchecked("maker");
+    let instantiate_stake = |app: &mut OsmosisApp, admin: &str| {
+        app.instantiate_contract(
+            stake_code_id,
+            maker.clone(),
+            &ion_stake::msg::InstantiateMsg {
+                admin: Some(Addr::unchecked(admin)),
+                denom: "utnt".to_string(),
+                unstaking_duration: Some(Duration::Height(20)),
+                basket_denoms: vec![],
+                bonus_denom: None,
+            },
+            &[],
+            "new_stake",
+            None,
+        )
+        .unwrap()
+    };
+
+    let stake_addr = instantiate_stake(&mut app, "maker");
+    let err = app
+        .instantiate_contract(
+            dao_code_id,
+            maker.clone(),
+            &happy_init_msg(Stake::Addr(stake_addr)),
+            &[],
+            "new_dao",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ForeignStakingAdmin {
+            admin: "maker".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    // multi-test hands out sequential addresses, the dao lands right after the stake contract
+    let stake_addr = instantiate_stake(&mut app, "contract2");
+    let dao_addr = app
+        .instantiate_contract(
+            dao_code_id,
+            maker.clone(),
+            &happy_init_msg(Stake::Addr(stake_addr)),
+            &[],
+            "new_dao",
+            None,
+        )
+        .unwrap();
+    assert_eq!(dao_addr, Addr::unchecked("contract2"));
+}
+
 #[test]
 fn should_fail_if_threshold_is_invalid() {
     let (mut app, dao_code_id, stake_code_id) = prepare();
@@ -212,6 +306,91 @@ fn should_fail_if_period_is_invalid() {
         let err = app
             .instantiate_contract(dao_code_id, maker.clone(), &init_msg, &[], "new_dao", None)
             .unwrap_err();
-        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        assert_eq!(ContractError::InvalidPeriod {}, err.downcast().unwrap());
     }
 }
+
+#[test]
+fn should_report_gov_token_decimals() {
+    let (mut app, dao_code_id, stake_code_id) = prepare();
+
+    for decimals in [None, Some(6)] {
+        let init_msg = InstantiateMsg {
+            gov_token_decimals: decimals,
+            ..happy_init_msg(Stake::Code(stake_code_id))
+        };
+        let dao_addr = app
+            .instantiate_contract(
+                dao_code_id,
+                Addr::unchecked("maker"),
+                &init_msg,
+                &[],
+                "new_dao",
+                None,
+            )
+            .unwrap();
+
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&dao_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.config.gov_token_decimals, decimals);
+    }
+}
+
+#[test]
+fn should_fail_if_query_limits_are_invalid() {
+    let (mut app, dao_code_id, stake_code_id) = prepare();
+
+    for (max, default) in [(Some(10), Some(20)), (None, Some(0))] {
+        let init_msg = InstantiateMsg {
+            max_query_limit: max,
+            default_query_limit: default,
+            ..happy_init_msg(Stake::Code(stake_code_id))
+        };
+        let err = app
+            .instantiate_contract(
+                dao_code_id,
+                Addr::unchecked("maker"),
+                &init_msg,
+                &[],
+                "new_dao",
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::InvalidQueryLimit {}, err.downcast().unwrap());
+    }
+}
+
+#[test]
+fn should_load_config_stored_before_upgrade() {
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: MockQuerier::<OsmosisQuery>::new(&[]),
+        custom_query_type: PhantomData,
+    };
+    let stored = br#"{
+        "name": "dao",
+        "description": "desc",
+        "threshold": {"threshold": "0.5", "quorum": "0.33", "veto_threshold": "0.33"},
+        "voting_period": {"time": 100},
+        "deposit_period": {"time": 50},
+        "proposal_deposit": "100",
+        "proposal_min_deposit": "10"
+    }"#;
+    deps.storage.set(CONFIG.as_slice(), stored);
+    GOV_TOKEN
+        .save(&mut deps.storage, &"ugov".to_string())
+        .unwrap();
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let cfg = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(cfg.force_close_grace_period, Duration::Time(100));
+    assert_eq!(cfg.max_query_limit, crate::MAX_LIMIT);
+    assert_eq!(cfg.default_query_limit, crate::DEFAULT_LIMIT);
+    assert!(!cfg.auto_reap);
+    assert!(!cfg.auto_scale_threshold);
+    cfg.validate(&deps.api).unwrap();
+}
@@ -1,6 +1,8 @@
+mod execute;
 mod instantiate;
 mod internal;
 mod lifecycle;
 mod proposal;
 mod query;
 mod suite;
+mod templates;
@@ -1,22 +1,24 @@
-use cosmwasm_std::{Attribute, StdError, Uint128};
+use cosmwasm_std::{coin, Attribute, StdError, Uint128};
 use cw3::Status;
 use cw3::Vote;
-use cw_utils::Expiration;
+use cw_utils::{Duration, Expiration};
 
-use crate::state::BlockTime;
+use crate::state::{BlockTime, ConfiscationPolicy, ExecutePermission};
 use crate::tests::suite::{
-    SuiteBuilder, DEFAULT_DEPOSIT_PERIOD, DEFAULT_QUO_DEPOSIT, DEFAULT_VOTING_PERIOD,
+    Suite, SuiteBuilder, DEFAULT_DEPOSIT_PERIOD, DEFAULT_QUO_DEPOSIT, DEFAULT_VOTING_PERIOD,
 };
 use crate::ContractError;
 use crate::CosmosMsg;
 
 mod propose {
     use cosmwasm_std::{
-        coin, coins, to_binary, BankMsg, DistributionMsg, GovMsg, IbcMsg, IbcTimeout, StakingMsg,
-        VoteOption, WasmMsg,
+        coin, coins, to_binary, BankMsg, Decimal, DistributionMsg, GovMsg, IbcMsg, IbcTimeout,
+        StakingMsg, VoteOption, WasmMsg,
     };
     use osmo_bindings::{OsmosisMsg, SwapAmountWithLimit};
 
+    use crate::state::{Bootstrap, Threshold};
+
     use super::*;
 
     fn assert_event_attrs(
@@ -66,6 +68,43 @@ mod propose {
         assert_eq!(prop.total_deposit, Uint128::new(100));
     }
 
+    #[test]
+    fn should_namespace_actions() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+
+        for invalid in ["", "my/dao"] {
+            config.event_namespace = Some(invalid.to_string());
+            let err = suite
+                .update_config(dao.as_str(), config.clone())
+                .unwrap_err();
+            assert_eq!(
+                ContractError::InvalidEventNamespace {},
+                err.downcast().unwrap()
+            );
+        }
+
+        config.event_namespace = Some("mydao".to_string());
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        let resp = suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+        assert_eq!(
+            resp.custom_attrs(1)[0],
+            Attribute::new("action", "mydao/propose")
+        );
+        let resp = suite.vote("tester0", 1, Vote::Yes).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1)[0],
+            Attribute::new("action", "mydao/vote")
+        );
+    }
+
     #[test]
     fn should_work_with_min_deposit() {
         let mut suite = SuiteBuilder::new()
@@ -167,6 +206,209 @@ mod propose {
         assert_eq!(prop.msgs, msgs);
     }
 
+    #[test]
+    fn should_mirror_cosmos_gov_vote() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 300)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+
+        let gov_vote = GovMsg::Vote {
+            proposal_id: 42,
+            vote: VoteOption::NoWithVeto,
+        };
+        suite
+            .propose_gov_vote("tester0", vec![], gov_vote.clone(), Some(100))
+            .unwrap();
+        assert_eq!(
+            suite.query_proposal(1).unwrap().msgs,
+            vec![CosmosMsg::Gov(gov_vote.clone())]
+        );
+
+        let err = suite
+            .propose_gov_vote(
+                "tester0",
+                vec![],
+                GovMsg::Vote {
+                    proposal_id: 0,
+                    vote: VoteOption::Yes,
+                },
+                Some(100),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidProposalContent {
+                reason: "gov proposal id must be greater than 0".to_string()
+            },
+            err.downcast().unwrap()
+        );
+
+        let bank_msg = BankMsg::Send {
+            to_address: "tester0".to_string(),
+            amount: coins(1, "denom"),
+        };
+        let err = suite
+            .propose_gov_vote("tester0", vec![bank_msg.into()], gov_vote, Some(100))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidProposalContent {
+                reason: "msgs must be empty when cosmos_gov_vote is set".to_string()
+            },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_validate_link() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 300)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.require_valid_link = true;
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        suite
+            .propose(
+                "tester0",
+                "title",
+                "https://forum.example.com/t/1",
+                "desc",
+                vec![],
+                Some(100),
+            )
+            .unwrap();
+        suite
+            .propose(
+                "tester0",
+                "title",
+                "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+                "desc",
+                vec![],
+                Some(100),
+            )
+            .unwrap();
+
+        for link in ["link", "ftp://example.com", "https:///path", ""] {
+            let err = suite
+                .propose("tester0", "title", link, "desc", vec![], Some(100))
+                .unwrap_err();
+            assert_eq!(
+                ContractError::InvalidProposalContent {
+                    reason: format!("invalid link '{}'", link)
+                },
+                err.downcast().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn should_reap_expired_pending_proposals() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 200)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.auto_reap = true;
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        for _ in 0..3 {
+            suite
+                .propose("tester0", "title", "link", "desc", vec![], Some(10))
+                .unwrap();
+        }
+        suite.app().advance_blocks(DEFAULT_DEPOSIT_PERIOD);
+
+        // still within its deposit period, shouldn't be reaped
+        let resp = suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(10))
+            .unwrap();
+        assert!(resp
+            .custom_attrs(1)
+            .contains(&Attribute::new("reaped", "3")));
+
+        let ids_by_status = |suite: &crate::tests::suite::Suite, status: Status| {
+            suite
+                .query_proposals(
+                    crate::msg::ProposalsQueryOption::FindByStatus {
+                        status,
+                        cursor: None,
+                    },
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap()
+                .proposals
+                .iter()
+                .map(|p| p.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids_by_status(&suite, Status::Rejected), vec![1, 2, 3]);
+        assert_eq!(ids_by_status(&suite, Status::Pending), vec![4]);
+
+        let resp = suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+        assert!(!resp.custom_attrs(1).iter().any(|attr| attr.key == "reaped"));
+        assert!(!suite.query_proposal(1).unwrap().deposit_claimable);
+    }
+
+    #[test]
+    fn should_cap_single_deposit() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100), ("tester1", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.max_single_deposit = Some(Uint128::new(50));
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        let err = suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(60))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::OversizedRequest { size: 60, max: 50 },
+            err.downcast().unwrap()
+        );
+
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(50))
+            .unwrap();
+        let err = suite.deposit("tester1", 1, Some(51)).unwrap_err();
+        assert_eq!(
+            ContractError::OversizedRequest { size: 51, max: 50 },
+            err.downcast().unwrap()
+        );
+
+        // reaching the full deposit takes several contributions
+        suite.deposit("tester1", 1, Some(50)).unwrap();
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.status, Status::Open);
+        assert_eq!(prop.total_deposit, Uint128::new(100));
+    }
+
+    #[test]
+    fn should_accept_any_deposit_when_uncapped() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100), ("tester1", 1000)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(10))
+            .unwrap();
+        suite.deposit("tester1", 1, Some(1000)).unwrap();
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.status, Status::Open);
+        assert_eq!(prop.total_deposit, Uint128::new(1010));
+    }
+
     #[test]
     fn should_fail_if_paused() {
         let mut suite = SuiteBuilder::new()
@@ -175,12 +417,16 @@ mod propose {
             .build();
 
         let dao = suite.dao.clone();
-        suite.pause(dao.as_str(), Expiration::Never {}).unwrap();
+        let expiration = Expiration::AtHeight(suite.app().block_info().height + 10);
+        suite.pause(dao.as_str(), expiration).unwrap();
 
         let err = suite
             .propose("tester0", "title", "link", "desc", vec![], Some(100))
             .unwrap_err();
-        assert_eq!(ContractError::Paused {}, err.downcast().unwrap());
+        assert_eq!(
+            ContractError::PausedUntil { expiration },
+            err.downcast().unwrap()
+        );
     }
 
     #[test]
@@ -207,6 +453,142 @@ mod propose {
             .unwrap_err();
         assert_eq!(ContractError::LackOfStakes {}, err.downcast().unwrap());
     }
+
+    #[test]
+    fn should_scale_threshold_with_low_stake() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 200)])
+            .with_staked(vec![("tester0", 60), ("tester1", 40)])
+            .build();
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.auto_scale_threshold = true;
+        // can't scale against an unknown supply
+        let err = suite
+            .update_config(dao.as_str(), config.clone())
+            .unwrap_err();
+        assert_eq!(
+            ContractError::MissingTokenSupply {},
+            err.downcast().unwrap()
+        );
+
+        config.total_token_supply = Uint128::new(1000);
+        config.threshold_scaling_coefficient = Decimal::percent(50);
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        // 10% of the supply is staked: 50% + 90% * 0.5
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.threshold.threshold, Decimal::percent(95));
+
+        // 60% yes would have passed the configured threshold
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.vote("tester1", 1, Vote::No).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        assert_eq!(suite.query_proposal(1).unwrap().status, Status::Rejected);
+    }
+
+    #[test]
+    fn should_relax_quorum_during_bootstrap() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 200)])
+            .with_staked(vec![("tester0", 10), ("tester1", 90)])
+            .build();
+        let dao = suite.dao.clone();
+        let until = suite.app().block_info().height + 20;
+        let mut config = suite.query_config().unwrap().config;
+        let configured = config.threshold.clone();
+
+        // bootstrap can't ask for more than the configured quorum
+        config.bootstrap = Some(Bootstrap {
+            until: Expiration::AtHeight(until),
+            quorum: configured.quorum + Decimal::percent(1),
+        });
+        let err = suite
+            .update_config(dao.as_str(), config.clone())
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidBootstrapQuorum {},
+            err.downcast().unwrap()
+        );
+
+        config.bootstrap = Some(Bootstrap {
+            until: Expiration::AtHeight(until),
+            quorum: Decimal::percent(10),
+        });
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.threshold.quorum, Decimal::percent(10));
+        assert_eq!(prop.threshold.threshold, configured.threshold);
+
+        // 10% turnout is enough
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        assert_eq!(suite.query_proposal(1).unwrap().status, Status::Passed);
+
+        // once bootstrap ends the configured quorum is back
+        let height = suite.app().block_info().height;
+        suite.app().advance_blocks(until - height);
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+        let prop = suite.query_proposal(2).unwrap();
+        assert_eq!(prop.threshold, configured);
+        suite.vote("tester0", 2, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        assert_eq!(suite.query_proposal(2).unwrap().status, Status::Rejected);
+    }
+
+    #[test]
+    fn should_inherit_parent_threshold() {
+        let parent_threshold = Threshold {
+            threshold: Decimal::percent(67),
+            quorum: Decimal::percent(40),
+            veto_threshold: Decimal::percent(20),
+            strict: true,
+            min_voters: Some(2),
+        };
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 400)])
+            .with_staked(vec![("tester0", 100)])
+            .with_threshold(parent_threshold.clone())
+            .build();
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+
+        // the DAO moves back to a plain majority
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.threshold = Threshold::default();
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        suite.propose_amendment("tester0", 1, Some(100)).unwrap();
+        assert_eq!(suite.query_proposal(2).unwrap().threshold, parent_threshold);
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+        assert_eq!(
+            suite.query_proposal(3).unwrap().threshold,
+            Threshold::default()
+        );
+
+        let err = suite
+            .propose_amendment("tester0", 9, Some(100))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidProposalContent {
+                reason: "parent proposal 9 does not exist".to_string()
+            },
+            err.downcast().unwrap()
+        );
+    }
 }
 
 mod deposit {
@@ -257,6 +639,37 @@ mod deposit {
         assert!(suite.check_balance("tester1", 20));
     }
 
+    #[test]
+    fn should_wait_for_distinct_depositors() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 500), ("tester1", 100), ("tester2", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.min_distinct_depositors = Some(3);
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        // the whale alone covers the deposit several times over
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+        let resp = suite.deposit("tester0", 1, Some(300)).unwrap();
+        assert_event_attrs(resp.custom_attrs(1), 300, 1, "pending");
+        let resp = suite.deposit("tester1", 1, Some(10)).unwrap();
+        assert_event_attrs(resp.custom_attrs(1), 10, 1, "pending");
+        assert_eq!(suite.query_proposal(1).unwrap().status, Status::Pending);
+
+        let resp = suite.deposit("tester2", 1, Some(10)).unwrap();
+        assert_event_attrs(resp.custom_attrs(1), 10, 1, "open");
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.status, Status::Open);
+        assert_eq!(prop.total_deposit, Uint128::new(420));
+
+        // nothing refunded to the last depositor
+        assert!(suite.check_balance("tester2", 90));
+    }
+
     #[test]
     fn should_fail_if_paused() {
         let mut suite = SuiteBuilder::new()
@@ -269,45 +682,87 @@ mod deposit {
             .unwrap();
 
         let dao = suite.dao.clone();
-        suite.pause(dao.as_str(), Expiration::Never {}).unwrap();
+        let expiration = Expiration::AtTime(suite.app().block_info().time.plus_seconds(60));
+        suite.pause(dao.as_str(), expiration).unwrap();
 
         let err = suite.deposit("tester0", 1, Some(90)).unwrap_err();
-        assert_eq!(ContractError::Paused {}, err.downcast().unwrap());
+        assert_eq!(
+            ContractError::PausedUntil { expiration },
+            err.downcast().unwrap()
+        );
     }
 
     #[test]
-    fn should_fail_if_no_funds() {
+    fn should_withdraw_from_pending_proposal() {
         let mut suite = SuiteBuilder::new()
             .with_funds(vec![("tester0", 100), ("tester1", 100)])
             .with_staked(vec![("tester0", 100)])
             .build();
 
         suite
-            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .propose("tester0", "title", "link", "desc", vec![], Some(10))
             .unwrap();
+        suite.deposit("tester1", 1, Some(50)).unwrap();
 
-        let err = suite.deposit("tester1", 1, None).unwrap_err();
-        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        let err = suite.withdraw_deposit("tester2", 1).unwrap_err();
+        assert_eq!(ContractError::NoDeposit {}, err.downcast().unwrap());
+
+        let resp = suite.withdraw_deposit("tester1", 1).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1),
+            [
+                Attribute::new("action", "withdraw_deposit"),
+                Attribute::new("sender", "tester1"),
+                Attribute::new("amount", "50"),
+                Attribute::new("proposal_id", "1"),
+            ]
+        );
+        assert!(suite.check_balance("tester1", 100));
+
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.status, Status::Pending);
+        assert_eq!(prop.total_deposit, Uint128::new(10));
+        assert!(suite.query_deposit(1, "tester1").is_err());
+
+        // nothing left to withdraw
+        let err = suite.withdraw_deposit("tester1", 1).unwrap_err();
+        assert_eq!(ContractError::NoDeposit {}, err.downcast().unwrap());
+
+        // the proposer is a depositor like any other
+        suite.withdraw_deposit("tester0", 1).unwrap();
+        assert!(suite.check_balance("tester0", 100));
+        assert_eq!(
+            suite.query_proposal(1).unwrap().total_deposit,
+            Uint128::zero()
+        );
     }
 
     #[test]
-    fn should_fail_if_no_proposal() {
+    fn should_fail_to_withdraw_once_opened() {
         let mut suite = SuiteBuilder::new()
             .with_funds(vec![("tester0", 100), ("tester1", 100)])
             .with_staked(vec![("tester0", 100)])
             .build();
 
-        let err = suite.deposit("tester1", 1, Some(100)).unwrap_err();
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(10))
+            .unwrap();
+        suite.deposit("tester1", 1, Some(90)).unwrap();
+
+        let err = suite.withdraw_deposit("tester1", 1).unwrap_err();
         assert_eq!(
-            ContractError::Std(StdError::not_found("ion_dao::proposal::Proposal")),
+            ContractError::InvalidProposalStatus {
+                current: "Open".to_string(),
+                desired: "Pending".to_string()
+            },
             err.downcast().unwrap()
         );
     }
 
     #[test]
-    fn should_fail_if_status_is_invalid() {
+    fn should_top_up_open_proposal() {
         let mut suite = SuiteBuilder::new()
-            .with_funds(vec![("tester0", 100), ("tester1", 100)])
+            .with_funds(vec![("tester0", 200), ("tester1", 100)])
             .with_staked(vec![("tester0", 100)])
             .build();
 
@@ -315,7 +770,8 @@ mod deposit {
             .propose("tester0", "title", "link", "desc", vec![], Some(100))
             .unwrap();
 
-        let err = suite.deposit("tester1", 1, Some(100)).unwrap_err();
+        // disabled by default
+        let err = suite.deposit("tester0", 1, Some(10)).unwrap_err();
         assert_eq!(
             ContractError::InvalidProposalStatus {
                 current: "Open".to_string(),
@@ -323,15 +779,166 @@ mod deposit {
             },
             err.downcast().unwrap()
         );
-    }
-}
 
-mod vote {
-    use crate::state::Votes;
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.allow_deposit_top_up = true;
+        suite.update_config(dao.as_str(), config).unwrap();
 
-    use super::*;
+        let before = suite.query_proposal(1).unwrap();
+        let resp = suite.deposit("tester0", 1, Some(30)).unwrap();
+        assert_event_attrs(resp.custom_attrs(1), 30, 1, "top_up");
+        suite.deposit("tester1", 1, Some(20)).unwrap();
 
-    fn assert_event_attrs(src: &[Attribute], sender: &str, vote: Vote, proposal_id: u64) {
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.status, Status::Open);
+        assert_eq!(prop.total_deposit, Uint128::new(150));
+        assert_eq!(prop.vote_ends_at, before.vote_ends_at);
+        assert_eq!(
+            suite.query_deposit(1, "tester0").unwrap().amount,
+            Uint128::new(130)
+        );
+        assert_eq!(
+            suite.query_deposit(1, "tester1").unwrap().amount,
+            Uint128::new(20)
+        );
+        assert!(suite.check_balance("tester0", 70));
+
+        suite.app().advance_blocks(15);
+        let err = suite.deposit("tester1", 1, Some(10)).unwrap_err();
+        assert_eq!(ContractError::Expired {}, err.downcast().unwrap());
+    }
+
+    #[test]
+    fn should_fail_if_no_funds() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100), ("tester1", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+
+        let err = suite.deposit("tester1", 1, None).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    }
+
+    #[test]
+    fn should_fail_if_no_proposal() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100), ("tester1", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+
+        let err = suite.deposit("tester1", 1, Some(100)).unwrap_err();
+        assert_eq!(
+            ContractError::Std(StdError::not_found("ion_dao::proposal::Proposal")),
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_fail_if_status_is_invalid() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100), ("tester1", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+
+        let err = suite.deposit("tester1", 1, Some(100)).unwrap_err();
+        assert_eq!(
+            ContractError::InvalidProposalStatus {
+                current: "Open".to_string(),
+                desired: "Pending".to_string()
+            },
+            err.downcast().unwrap()
+        );
+    }
+}
+
+mod cosponsor {
+    use cosmwasm_std::Addr;
+
+    use super::*;
+
+    #[test]
+    fn should_work() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 10), ("tester1", 10), ("tester2", 10)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        let resp = suite.cosponsor("tester2", 1).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1),
+            &[
+                Attribute::new("action", "cosponsor"),
+                Attribute::new("sender", "tester2"),
+                Attribute::new("proposal_id", "1"),
+            ]
+        );
+        suite.cosponsor("tester1", 1).unwrap();
+        // co-sponsoring twice changes nothing
+        suite.cosponsor("tester1", 1).unwrap();
+
+        assert_eq!(suite.query_proposal(1).unwrap().cosponsor_count, 2);
+        assert_eq!(
+            suite.query_cosponsors(1, None, None).unwrap(),
+            vec![Addr::unchecked("tester1"), Addr::unchecked("tester2")]
+        );
+        assert_eq!(
+            suite
+                .query_cosponsors(1, Some("tester1".to_string()), Some(1))
+                .unwrap(),
+            vec![Addr::unchecked("tester2")]
+        );
+    }
+
+    #[test]
+    fn should_fail_if_not_staked() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 10)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        let err = suite.cosponsor("stranger", 1).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        assert_eq!(suite.query_proposal(1).unwrap().cosponsor_count, 0);
+    }
+
+    #[test]
+    fn should_fail_if_status_is_invalid() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 10)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        let err = suite.cosponsor("tester0", 1).unwrap_err();
+        assert_eq!(
+            ContractError::InvalidProposalStatus {
+                current: "Rejected".to_string(),
+                desired: "pending | open".to_string()
+            },
+            err.downcast().unwrap()
+        );
+    }
+}
+
+mod vote {
+    use cosmwasm_std::{Addr, Empty};
+    use cw_multi_test::Executor;
+
+    use crate::state::{Threshold, Votes};
+    use crate::tests::suite::contract_notifier;
+
+    use super::*;
+
+    fn assert_event_attrs(src: &[Attribute], sender: &str, vote: Vote, proposal_id: u64) {
         assert_eq!(
             src,
             &[
@@ -343,6 +950,125 @@ mod vote {
         )
     }
 
+    #[test]
+    fn should_require_min_voters() {
+        let mut suite = SuiteBuilder::new()
+            .with_threshold(Threshold {
+                min_voters: Some(2),
+                ..Default::default()
+            })
+            .with_staked(vec![("whale", 99), ("minnow", 1)])
+            .add_proposal("title", "link", "desc", vec![]) // 1
+            .add_proposal("title", "link", "desc", vec![]) // 2
+            .build();
+
+        // changing a vote doesn't count as another voter
+        suite.vote("whale", 1, Vote::No).unwrap();
+        suite.vote("whale", 1, Vote::Yes).unwrap();
+        assert_eq!(suite.query_proposal(1).unwrap().voter_count, 1);
+
+        suite.vote("whale", 2, Vote::Yes).unwrap();
+        suite.vote("minnow", 2, Vote::Yes).unwrap();
+        assert_eq!(suite.query_proposal(2).unwrap().voter_count, 2);
+
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        assert_eq!(suite.query_proposal(1).unwrap().status, Status::Rejected);
+        assert_eq!(suite.query_proposal(2).unwrap().status, Status::Passed);
+    }
+
+    #[test]
+    fn should_notify_expired_proposals() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .add_proposal("title", "link", "desc", vec![]) // 1
+            .add_proposal("title", "link", "desc", vec![]) // 2
+            .build();
+        let dao = suite.dao.clone();
+
+        let code_id = suite.app().store_code(contract_notifier());
+        let mut notifiers = vec![];
+        for fail in [false, true] {
+            let addr = suite
+                .app()
+                .instantiate_contract(code_id, dao.clone(), &fail, &[], "notifier", None)
+                .unwrap();
+            notifiers.push(addr);
+        }
+        let mut config = suite.query_config().unwrap().config;
+        config.notifiers = vec![Addr::unchecked("K")];
+        let err = suite
+            .update_config(dao.as_str(), config.clone())
+            .unwrap_err();
+        assert!(matches!(err.downcast().unwrap(), ContractError::Std(_)));
+
+        config.notifiers = notifiers.clone();
+        suite.update_config(dao.as_str(), config).unwrap();
+        let notified = |suite: &mut Suite| -> Vec<u64> {
+            suite
+                .app()
+                .wrap()
+                .query_wasm_smart(&notifiers[0], &Empty {})
+                .unwrap()
+        };
+
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        assert!(notified(&mut suite).is_empty());
+
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+        // the failing notifier doesn't get in the way
+        suite.vote("tester0", 3, Vote::Yes).unwrap();
+        assert_eq!(notified(&mut suite), vec![1, 2]);
+
+        // every expired proposal is announced once
+        suite.vote("tester0", 3, Vote::No).unwrap();
+        suite.execute_proposal("tester0", 1).unwrap();
+        assert_eq!(notified(&mut suite), vec![1, 2]);
+    }
+
+    #[test]
+    fn should_store_rationale() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 60), ("tester1", 40)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        suite
+            .vote_with_rationale("tester0", 1, Vote::Yes, Some("funds the audit"))
+            .unwrap();
+        suite.vote("tester1", 1, Vote::No).unwrap();
+
+        let votes = suite.query_votes(1, None, None, None).unwrap().votes;
+        assert_eq!(
+            votes
+                .iter()
+                .map(|info| (info.voter.as_str(), info.rationale.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![("tester0", Some("funds the audit")), ("tester1", None)]
+        );
+
+        // a re-vote replaces the rationale along with the option
+        suite.vote("tester0", 1, Vote::Abstain).unwrap();
+        let vote = suite.query_vote(1, "tester0").unwrap().vote.unwrap();
+        assert_eq!(vote.vote, Vote::Abstain);
+        assert_eq!(vote.rationale, None);
+
+        let err = suite
+            .vote_with_rationale("tester1", 1, Vote::Yes, Some(&"x".repeat(513)))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::RationaleTooLong {
+                length: 513,
+                max: 512
+            },
+            err.downcast().unwrap()
+        );
+    }
+
     #[test]
     fn should_work() {
         let mut suite = SuiteBuilder::new()
@@ -390,7 +1116,8 @@ mod vote {
                     .map(|(voter, weight, vote)| crate::msg::VoteInfo {
                         voter: voter.to_string(),
                         vote,
-                        weight: Uint128::new(weight)
+                        weight: Uint128::new(weight),
+                        rationale: None,
                     })
                     .to_vec()
             }
@@ -425,7 +1152,8 @@ mod vote {
                     .map(|(voter, weight, vote)| crate::msg::VoteInfo {
                         voter: voter.to_string(),
                         vote,
-                        weight: Uint128::new(weight)
+                        weight: Uint128::new(weight),
+                        rationale: None,
                     })
                     .to_vec()
             }
@@ -443,7 +1171,12 @@ mod vote {
         suite.pause(dao.as_str(), Expiration::Never {}).unwrap();
 
         let err = suite.vote("tester0", 1, Vote::Yes).unwrap_err();
-        assert_eq!(ContractError::Paused {}, err.downcast().unwrap());
+        assert_eq!(
+            ContractError::PausedUntil {
+                expiration: Expiration::Never {}
+            },
+            err.downcast().unwrap()
+        );
     }
 
     #[test]
@@ -494,8 +1227,13 @@ mod vote {
 }
 
 mod execute_proposal {
-    use cosmwasm_std::{coins, Addr, BankMsg};
-    use cw_multi_test::Executor;
+    use cosmwasm_std::{coins, to_binary, Addr, BankMsg, Decimal, WasmMsg};
+    use cw20::Denom;
+    use cw_multi_test::{BankSudo, Executor, SudoMsg};
+
+    use crate::msg::ExecuteMsg;
+    use crate::state::{Config, ConfigFieldKey, Threshold, CALLING_PROPOSAL_ID};
+    use crate::tests::suite::contract_stake;
 
     use super::*;
 
@@ -521,9 +1259,13 @@ mod execute_proposal {
         suite.vote("tester0", 1, Vote::Yes).unwrap();
         suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
 
+        assert_eq!(suite.query_proposal(1).unwrap().executed_at, None);
+
         let resp = suite.execute_proposal("owner", 1).unwrap();
         assert_event_attrs(resp.custom_attrs(1), "owner", 1);
-        assert!(suite.query_proposal(1).unwrap().deposit_claimable);
+        let prop = suite.query_proposal(1).unwrap();
+        assert!(prop.deposit_claimable);
+        assert_eq!(prop.executed_at, Some(suite.app().block_info().into()));
     }
 
     #[test]
@@ -538,22 +1280,447 @@ mod execute_proposal {
             .add_proposal("title", "link", "desc", vec![send_msg])
             .build();
 
-        let dao = suite.dao.clone();
-        suite
-            .app()
-            .send_tokens(
-                Addr::unchecked("tester0"),
-                dao,
-                coins(100, "denom").as_slice(),
-            )
-            .unwrap();
+        let dao = suite.dao.clone();
+        suite
+            .app()
+            .send_tokens(
+                Addr::unchecked("tester0"),
+                dao,
+                coins(100, "denom").as_slice(),
+            )
+            .unwrap();
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        let resp = suite.execute_proposal("owner", 1).unwrap();
+        assert_event_attrs(resp.custom_attrs(1), "owner", 1);
+
+        assert!(suite.check_balance("tester0", 100));
+    }
+
+    #[test]
+    fn should_upgrade_staking_contract() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+        let dao = suite.dao.clone();
+        let stake = suite.stake.clone();
+        let new_code_id = suite.app().store_code(contract_stake());
+        assert_ne!(
+            suite.app().contract_data(&stake).unwrap().code_id as u64,
+            new_code_id
+        );
+
+        let upgrade = ExecuteMsg::UpgradeContract {
+            contract: stake.clone(),
+            new_code_id,
+            migrate_msg: to_binary(&ion_stake::msg::MigrateMsg {}).unwrap(),
+        };
+        let err = suite
+            .app()
+            .execute_contract(Addr::unchecked("tester0"), dao.clone(), &upgrade, &[])
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let upgrade_msg = CosmosMsg::from(WasmMsg::Execute {
+            contract_addr: dao.to_string(),
+            msg: to_binary(&upgrade).unwrap(),
+            funds: vec![],
+        });
+        suite
+            .propose(
+                "tester0",
+                "title",
+                "link",
+                "desc",
+                vec![upgrade_msg],
+                Some(100),
+            )
+            .unwrap();
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        suite.execute_proposal("owner", 1).unwrap();
+
+        assert_eq!(
+            suite.app().contract_data(&stake).unwrap().code_id as u64,
+            new_code_id
+        );
+        // staking state survives the migration
+        let staked: ion_stake::msg::StakedBalanceAtHeightResponse = suite
+            .app()
+            .wrap()
+            .query_wasm_smart(
+                &stake,
+                &ion_stake::msg::QueryMsg::StakedBalanceAtHeight {
+                    address: "tester0".to_string(),
+                    height: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(staked.balance, Uint128::new(100));
+    }
+
+    #[test]
+    fn should_require_field_threshold() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 300)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        let required = |threshold, quorum| Threshold {
+            threshold: Decimal::percent(threshold),
+            quorum: Decimal::percent(quorum),
+            ..config.threshold.clone()
+        };
+        config.field_thresholds = vec![
+            (ConfigFieldKey::Description, required(50, 33)),
+            (ConfigFieldKey::Threshold, required(90, 33)),
+            (ConfigFieldKey::Name, required(50, 40)),
+        ];
+        suite.update_config(dao.as_str(), config.clone()).unwrap();
+
+        let update_msg = |config: Config| {
+            CosmosMsg::from(WasmMsg::Execute {
+                contract_addr: dao.to_string(),
+                msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(config))).unwrap(),
+                funds: vec![],
+            })
+        };
+        let mut described = config.clone();
+        described.description = "new desc".to_string();
+        let mut restricted = described.clone();
+        restricted.threshold.quorum = Decimal::percent(50);
+        let mut renamed = config.clone();
+        renamed.name = "new name".to_string();
+
+        for msg in [
+            update_msg(described),
+            update_msg(restricted),
+            update_msg(renamed),
+        ] {
+            suite
+                .propose("tester0", "title", "link", "desc", vec![msg], Some(100))
+                .unwrap();
+        }
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.vote("tester0", 2, Vote::Yes).unwrap();
+        suite.vote("tester0", 3, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        suite.execute_proposal("owner", 1).unwrap();
+        assert_eq!(suite.query_config().unwrap().config.description, "new desc");
+        // the calling proposal is forgotten once its messages ran
+        let calling = suite
+            .app()
+            .wrap()
+            .query_wasm_raw(&dao, CALLING_PROPOSAL_ID.as_slice())
+            .unwrap();
+        assert_eq!(calling, None);
+
+        // the most sensitive changed field is the one checked
+        let err = suite.execute_proposal("owner", 2).unwrap_err();
+        assert_eq!(
+            Some(&ContractError::InsufficientThresholdForField {
+                field: "Threshold".to_string(),
+                required_threshold: Decimal::percent(90),
+                required_quorum: Decimal::percent(33),
+                actual_threshold: Decimal::percent(50),
+                actual_quorum: Decimal::percent(33),
+            }),
+            err.root_cause().downcast_ref()
+        );
+
+        // a high enough threshold doesn't make up for a lower quorum
+        let err = suite.execute_proposal("owner", 3).unwrap_err();
+        assert_eq!(
+            Some(&ContractError::InsufficientThresholdForField {
+                field: "Name".to_string(),
+                required_threshold: Decimal::percent(50),
+                required_quorum: Decimal::percent(40),
+                actual_threshold: Decimal::percent(50),
+                actual_quorum: Decimal::percent(33),
+            }),
+            err.root_cause().downcast_ref()
+        );
+    }
+
+    #[test]
+    fn should_keep_treasury_reserve() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 200)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+        let dao = suite.dao.clone();
+        suite
+            .app()
+            .sudo(SudoMsg::Bank(BankSudo::Mint {
+                to_address: dao.to_string(),
+                amount: coins(500, "utreasury"),
+            }))
+            .unwrap();
+        let mut config = suite.query_config().unwrap().config;
+        config.reserves = vec![(Denom::Native("utreasury".to_string()), Uint128::new(300))];
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        let spend = |amount| {
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: "recipient".to_string(),
+                amount: coins(amount, "utreasury"),
+            })]
+        };
+        suite
+            .propose("tester0", "title", "link", "desc", spend(201), Some(100))
+            .unwrap();
+        suite
+            .propose("tester0", "title", "link", "desc", spend(200), Some(100))
+            .unwrap();
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.vote("tester0", 2, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        let violation = ContractError::ReserveViolation {
+            denom: "utreasury".to_string(),
+            reserve: Uint128::new(300),
+            remaining: Uint128::new(299),
+        };
+        let simulation = suite.query_simulate_dao_execution(1).unwrap();
+        assert_eq!(simulation.blocking_reasons, vec![violation.to_string()]);
+        assert!(suite.query_simulate_dao_execution(2).unwrap().will_succeed);
+
+        let err = suite.execute_proposal("tester0", 1).unwrap_err();
+        assert_eq!(violation, err.downcast().unwrap());
+
+        suite.execute_proposal("tester0", 2).unwrap();
+        let held = suite
+            .app()
+            .wrap()
+            .query_balance(&dao, "utreasury")
+            .unwrap()
+            .amount;
+        assert_eq!(held, Uint128::new(300));
+    }
+
+    #[test]
+    fn should_keep_reserve_net_of_escrowed_deposits() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 200)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.reserves = vec![(Denom::Native("denom".to_string()), Uint128::new(50))];
+        suite.update_config(dao.as_str(), config).unwrap();
+        suite
+            .app()
+            .send_tokens(
+                Addr::unchecked("tester0"),
+                dao.clone(),
+                coins(100, "denom").as_slice(),
+            )
+            .unwrap();
+
+        let spend = vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: "recipient".to_string(),
+            amount: coins(60, "denom"),
+        })];
+        suite
+            .propose("tester0", "title", "link", "desc", spend, Some(100))
+            .unwrap();
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        // 200 held, but 100 of it is the proposal's own deposit
+        let violation = ContractError::ReserveViolation {
+            denom: "denom".to_string(),
+            reserve: Uint128::new(50),
+            remaining: Uint128::new(40),
+        };
+        let err = suite.execute_proposal("tester0", 1).unwrap_err();
+        assert_eq!(violation, err.downcast().unwrap());
+    }
+
+    #[test]
+    fn should_pay_high_participation_bonus() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100)])
+            .with_staked(vec![("tester0", 60), ("tester1", 40)])
+            .add_proposal("title", "link", "desc", vec![]) // 1
+            .add_proposal("title", "link", "desc", vec![]) // 2
+            .build();
+
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.high_participation_bonus = Some((Decimal::percent(50), Uint128::new(7)));
+        suite.update_config(dao.as_str(), config).unwrap();
+        suite
+            .app()
+            .send_tokens(
+                Addr::unchecked("tester0"),
+                dao,
+                coins(100, "denom").as_slice(),
+            )
+            .unwrap();
+
+        // 60% participation
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        // 40% participation
+        suite.vote("tester1", 2, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        let resp = suite.execute_proposal("tester1", 1).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1).last().unwrap(),
+            Attribute::new("participation_bonus", "7")
+        );
+        assert!(suite.check_balance("owner", 7));
+
+        let resp = suite.execute_proposal("tester1", 2).unwrap();
+        assert_event_attrs(resp.custom_attrs(1), "tester1", 2);
+        assert!(suite.check_balance("owner", 7));
+    }
+
+    #[test]
+    fn should_keep_reserve_against_participation_bonus() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100)])
+            .with_staked(vec![("tester0", 60), ("tester1", 40)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.high_participation_bonus = Some((Decimal::percent(50), Uint128::new(7)));
+        config.reserves = vec![(Denom::Native("denom".to_string()), Uint128::new(195))];
+        suite.update_config(dao.as_str(), config).unwrap();
+        suite
+            .app()
+            .send_tokens(
+                Addr::unchecked("tester0"),
+                dao.clone(),
+                coins(100, "denom").as_slice(),
+            )
+            .unwrap();
+
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        // the deposit is escrowed, so paying the bonus would dip into the reserve
+        let simulation = suite.query_simulate_dao_execution(1).unwrap();
+        assert!(simulation.will_succeed);
+
+        let resp = suite.execute_proposal("tester1", 1).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1).last().unwrap(),
+            Attribute::new("participation_bonus_unfunded", "7")
+        );
+        let held = suite
+            .app()
+            .wrap()
+            .query_balance(&dao, "denom")
+            .unwrap()
+            .amount;
+        assert_eq!(held, Uint128::new(200));
+    }
+
+    #[test]
+    fn should_skip_participation_bonus_paid_from_deposits() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100)])
+            .with_staked(vec![("tester0", 60), ("tester1", 40)])
+            .add_proposal("title", "link", "desc", vec![]) // 1
+            .add_proposal("title", "link", "desc", vec![]) // 2
+            .build();
+
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.high_participation_bonus = Some((Decimal::percent(50), Uint128::new(7)));
+        suite.update_config(dao.as_str(), config).unwrap();
+        // short of the bonus once the escrowed deposits are set aside
+        suite
+            .app()
+            .send_tokens(
+                Addr::unchecked("tester0"),
+                dao.clone(),
+                coins(6, "denom").as_slice(),
+            )
+            .unwrap();
+
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.vote("tester0", 2, Vote::No).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        assert!(suite.query_simulate_dao_execution(1).unwrap().will_succeed);
+        let resp = suite.execute_proposal("tester1", 1).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1).last().unwrap(),
+            Attribute::new("participation_bonus_unfunded", "7")
+        );
+        assert!(suite.check_balance("owner", 0));
+
+        // every depositor still gets their deposit back
+        suite.close_proposal("tester1", 2).unwrap();
+        suite.claim_deposit("owner", 1).unwrap();
+        suite.claim_deposit("owner", 2).unwrap();
+        let held = suite
+            .app()
+            .wrap()
+            .query_balance(&dao, "denom")
+            .unwrap()
+            .amount;
+        assert_eq!(held, Uint128::new(6));
+    }
+
+    #[test]
+    fn should_restrict_executor_to_proposer() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 1)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.execute_permission = ExecutePermission::ProposerOnly;
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        let err = suite.execute_proposal("stranger", 1).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let resp = suite.execute_proposal("owner", 1).unwrap();
+        assert_event_attrs(resp.custom_attrs(1), "owner", 1);
+    }
+
+    #[test]
+    fn should_restrict_executor_to_allowlist() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 1)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.execute_permission = ExecutePermission::Allowlist(vec![Addr::unchecked("K")]);
+        let err = suite
+            .update_config(dao.as_str(), config.clone())
+            .unwrap_err();
+        assert!(matches!(err.downcast().unwrap(), ContractError::Std(_)));
+
+        config.execute_permission = ExecutePermission::Allowlist(vec![Addr::unchecked("keeper")]);
+        suite.update_config(dao.as_str(), config).unwrap();
+
         suite.vote("tester0", 1, Vote::Yes).unwrap();
         suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
 
-        let resp = suite.execute_proposal("owner", 1).unwrap();
-        assert_event_attrs(resp.custom_attrs(1), "owner", 1);
+        // not even the proposer is listed
+        let err = suite.execute_proposal("owner", 1).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
 
-        assert!(suite.check_balance("tester0", 100));
+        let resp = suite.execute_proposal("keeper", 1).unwrap();
+        assert_event_attrs(resp.custom_attrs(1), "keeper", 1);
     }
 
     #[test]
@@ -570,7 +1737,12 @@ mod execute_proposal {
         suite.pause(dao.as_str(), Expiration::Never {}).unwrap();
 
         let err = suite.execute_proposal("owner", 1).unwrap_err();
-        assert_eq!(ContractError::Paused {}, err.downcast().unwrap());
+        assert_eq!(
+            ContractError::PausedUntil {
+                expiration: Expiration::Never {}
+            },
+            err.downcast().unwrap()
+        );
     }
 
     #[test]
@@ -606,6 +1778,9 @@ mod execute_proposal {
 }
 
 mod close_proposal {
+    use cosmwasm_std::{Addr, Decimal};
+    use cw_multi_test::Executor;
+
     use super::*;
 
     fn assert_event_attrs(src: &[Attribute], sender: &str, proposal_id: u64, result: &str) {
@@ -658,13 +1833,158 @@ mod close_proposal {
 
         suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
 
+        assert!(suite.query_confiscated_totals().unwrap().is_empty());
+        let deposit_1 = suite.query_proposal(1).unwrap().total_deposit;
+
         let resp = suite.close_proposal("owner", 1).unwrap();
         assert_event_attrs(resp.custom_attrs(1), "owner", 1, "confiscate");
         assert!(suite.check_balance("owner", 0));
+        assert_eq!(
+            suite.query_confiscated_totals().unwrap(),
+            vec![coin(deposit_1.u128(), &suite.denom)]
+        );
 
         let resp = suite.close_proposal("owner", 2).unwrap();
         assert_event_attrs(resp.custom_attrs(1), "owner", 2, "confiscate");
         assert!(suite.check_balance("tester0", 0));
+        assert_eq!(
+            suite.query_confiscated_totals().unwrap(),
+            vec![coin(deposit_1.u128() + 10, &suite.denom)]
+        );
+    }
+
+    #[test]
+    fn should_reward_stakers_with_confiscation() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 20)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+        let dao = suite.dao.clone();
+        let stake = suite.stake.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.confiscation_policy = ConfiscationPolicy::RewardStakers;
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        // a boosted `Fund` would need a reward reserve the staking contract doesn't have
+        let update_stake_config = |suite: &mut Suite, admin, fund_allowlist| {
+            suite
+                .app()
+                .execute_contract(
+                    dao.clone(),
+                    stake.clone(),
+                    &ion_stake::msg::ExecuteMsg::UpdateConfig {
+                        admin,
+                        duration: None,
+                        reward_multiplier: Some(Decimal::percent(200)),
+                        fund_allowlist,
+                        clear_fund_allowlist: false,
+                        reward_vesting_blocks: None,
+                        min_claim_amount: None,
+                        warmup_period: None,
+                        bonus_denom: None,
+                    },
+                    &[],
+                )
+                .unwrap();
+        };
+        update_stake_config(&mut suite, Some(dao.clone()), None);
+
+        // min deposit not satisfied
+        for _ in 0..2 {
+            suite
+                .propose("tester0", "title", "link", "desc", vec![], Some(10))
+                .unwrap();
+        }
+        suite.app().advance_blocks(DEFAULT_DEPOSIT_PERIOD);
+
+        let value_before = suite.query_stake_total_value().unwrap();
+        let resp = suite.close_proposal("owner", 1).unwrap();
+        assert_event_attrs(resp.custom_attrs(1), "owner", 1, "confiscate");
+
+        assert_eq!(
+            suite.query_stake_total_value().unwrap(),
+            value_before + Uint128::new(10)
+        );
+        assert!(suite.check_balance(dao.as_str(), 10));
+        assert_eq!(
+            suite.query_confiscated_totals().unwrap(),
+            vec![coin(10, &suite.denom)]
+        );
+
+        // the staking contract refusing the deposit doesn't block the close, it stays with the DAO
+        update_stake_config(
+            &mut suite,
+            Some(Addr::unchecked("owner")),
+            Some(vec![Addr::unchecked("owner")]),
+        );
+        let resp = suite.close_proposal("owner", 2).unwrap();
+        assert!(resp.events.iter().any(|ev| ev
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "confiscation_reward_failed")));
+        assert_eq!(
+            suite.query_stake_total_value().unwrap(),
+            value_before + Uint128::new(10)
+        );
+        assert!(suite.check_balance(dao.as_str(), 10));
+        assert_eq!(
+            suite.query_confiscated_totals().unwrap(),
+            vec![coin(20, &suite.denom)]
+        );
+    }
+
+    #[test]
+    fn should_refresh_status() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 70), ("tester1", 30)])
+            .add_proposal("title", "link", "desc", vec![]) // 1
+            .add_proposal("title", "link", "desc", vec![]) // 2
+            .build();
+
+        suite.vote("tester0", 1, Vote::No).unwrap();
+        suite.vote("tester0", 2, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        let ids_by_status = |suite: &Suite, status: Status| {
+            suite
+                .query_proposals(
+                    crate::msg::ProposalsQueryOption::FindByStatus {
+                        status,
+                        cursor: None,
+                    },
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap()
+                .proposals
+                .iter()
+                .map(|p| p.id)
+                .collect::<Vec<_>>()
+        };
+        // both timed out, but still indexed as open
+        assert_eq!(ids_by_status(&suite, Status::Open), vec![1, 2]);
+        assert!(ids_by_status(&suite, Status::Rejected).is_empty());
+
+        let resp = suite.refresh_status("anyone", vec![1, 2]).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1),
+            &[
+                Attribute::new("action", "refresh_status"),
+                Attribute::new("refreshed", "2"),
+            ]
+        );
+        assert!(ids_by_status(&suite, Status::Open).is_empty());
+        assert_eq!(ids_by_status(&suite, Status::Rejected), vec![1]);
+        assert_eq!(ids_by_status(&suite, Status::Passed), vec![2]);
+        assert!(suite.query_proposal(1).unwrap().deposit_claimable);
+
+        // already settled, nothing left to close or refresh
+        suite.close_proposal("owner", 1).unwrap_err();
+        let resp = suite.refresh_status("anyone", vec![1, 2]).unwrap();
+        assert!(resp
+            .custom_attrs(1)
+            .contains(&Attribute::new("refreshed", "0")));
     }
 
     #[test]
@@ -681,7 +2001,12 @@ mod close_proposal {
         suite.pause(dao.as_str(), Expiration::Never {}).unwrap();
 
         let err = suite.close_proposal("owner", 1).unwrap_err();
-        assert_eq!(ContractError::Paused {}, err.downcast().unwrap());
+        assert_eq!(
+            ContractError::PausedUntil {
+                expiration: Expiration::Never {}
+            },
+            err.downcast().unwrap()
+        );
     }
 
     #[test]
@@ -841,3 +2166,304 @@ mod claim_deposit {
         );
     }
 }
+
+mod force_close {
+    use cosmwasm_std::{coins, BankMsg};
+
+    use super::*;
+
+    #[test]
+    fn should_close_wedged_proposal() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+
+        // the DAO can't cover this transfer, so execution always fails
+        let wedged = BankMsg::Send {
+            to_address: "tester0".to_string(),
+            amount: coins(1_000_000, "denom"),
+        };
+        suite
+            .propose(
+                "tester0",
+                "title",
+                "link",
+                "desc",
+                vec![wedged.into()],
+                Some(100),
+            )
+            .unwrap();
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        assert_eq!(suite.query_proposal(1).unwrap().status, Status::Passed);
+        suite.execute_proposal("tester0", 1).unwrap_err();
+
+        // still within the grace period
+        let dao = suite.dao.clone();
+        let err = suite.force_close(dao.as_str(), 1).unwrap_err();
+        assert_eq!(ContractError::NotExpired {}, err.downcast().unwrap());
+
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+        // only the DAO itself can force-close
+        let err = suite.force_close("tester0", 1).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let resp = suite.force_close(dao.as_str(), 1).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1),
+            &[
+                Attribute::new("action", "force_close"),
+                Attribute::new("proposal_id", "1"),
+                Attribute::new("result", "refund"),
+            ]
+        );
+
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+        assert!(prop.deposit_claimable);
+
+        suite.claim_deposit("tester0", 1).unwrap();
+        assert!(suite.check_balance("tester0", 100));
+
+        let err = suite.force_close(dao.as_str(), 1).unwrap_err();
+        assert_eq!(
+            ContractError::InvalidProposalStatus {
+                current: "Rejected".to_string(),
+                desired: "open | passed".to_string()
+            },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_confiscate_vetoed_proposal() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 100)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.confiscation_policy = ConfiscationPolicy::RewardStakers;
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        suite.vote("tester0", 1, Vote::Veto).unwrap();
+        suite.app().advance_blocks(2 * DEFAULT_VOTING_PERIOD);
+
+        let deposit = suite.query_proposal(1).unwrap().total_deposit;
+        let value_before = suite.query_stake_total_value().unwrap();
+        let resp = suite.force_close(dao.as_str(), 1).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1),
+            &[
+                Attribute::new("action", "force_close"),
+                Attribute::new("proposal_id", "1"),
+                Attribute::new("result", "confiscate"),
+            ]
+        );
+
+        assert!(!suite.query_proposal(1).unwrap().deposit_claimable);
+        assert_eq!(
+            suite.query_stake_total_value().unwrap(),
+            value_before + deposit
+        );
+        assert_eq!(
+            suite.query_confiscated_totals().unwrap(),
+            vec![coin(deposit.u128(), &suite.denom)]
+        );
+    }
+}
+
+mod veto_execution {
+    use super::*;
+
+    const EXECUTION_DELAY: u64 = 5;
+
+    fn passed_proposal_suite() -> Suite {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 1)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.execution_delay = Some(Duration::Height(EXECUTION_DELAY));
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        suite
+    }
+
+    #[test]
+    fn should_execute_after_delay() {
+        let mut suite = passed_proposal_suite();
+
+        let err = suite.execute_proposal("owner", 1).unwrap_err();
+        assert_eq!(ContractError::NotExpired {}, err.downcast().unwrap());
+
+        suite.app().advance_blocks(EXECUTION_DELAY);
+        suite.execute_proposal("owner", 1).unwrap();
+        assert_eq!(suite.query_proposal(1).unwrap().status, Status::Executed);
+
+        let dao = suite.dao.clone();
+        let err = suite.veto_execution(dao.as_str(), 1).unwrap_err();
+        assert_eq!(
+            ContractError::InvalidProposalStatus {
+                current: "Executed".to_string(),
+                desired: "Passed".to_string()
+            },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_veto_within_delay() {
+        let mut suite = passed_proposal_suite();
+        let dao = suite.dao.clone();
+
+        // only the DAO itself can veto
+        let err = suite.veto_execution("tester0", 1).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let resp = suite.veto_execution(dao.as_str(), 1).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1),
+            [
+                Attribute::new("action", "veto_execution"),
+                Attribute::new("proposal_id", "1"),
+            ]
+        );
+
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+        assert!(prop.deposit_claimable);
+
+        // the proposal passed, then got rejected at the veto
+        let vetoed_at: BlockTime = suite.app().block_info().into();
+        let timeline = suite.query_proposal_timeline(1).unwrap();
+        let events = timeline
+            .events
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec!["created", "opened", "passed", "rejected"]);
+        assert_eq!(timeline.events[3].1, vetoed_at);
+
+        suite.app().advance_blocks(EXECUTION_DELAY);
+        let err = suite.execute_proposal("owner", 1).unwrap_err();
+        assert_eq!(
+            ContractError::InvalidProposalStatus {
+                current: "Rejected".to_string(),
+                desired: "Passed".to_string()
+            },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_fail_to_veto_after_delay() {
+        let mut suite = passed_proposal_suite();
+        let dao = suite.dao.clone();
+
+        suite.app().advance_blocks(EXECUTION_DELAY);
+        let err = suite.veto_execution(dao.as_str(), 1).unwrap_err();
+        assert_eq!(
+            ContractError::ExecutionDelayElapsed {},
+            err.downcast().unwrap()
+        );
+    }
+}
+
+mod execution_window {
+    use super::*;
+
+    const EXECUTION_WINDOW: u64 = 5;
+
+    fn passed_proposal_suite() -> Suite {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 1)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.execution_window = Some(Duration::Height(EXECUTION_WINDOW));
+        suite.update_config(dao.as_str(), config).unwrap();
+
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        suite
+    }
+
+    #[test]
+    fn should_execute_within_window() {
+        let mut suite = passed_proposal_suite();
+
+        let err = suite.expire_execution("tester0", 1).unwrap_err();
+        assert_eq!(ContractError::NotExpired {}, err.downcast().unwrap());
+
+        // last block of the window
+        let vote_ends_at = match suite.query_proposal(1).unwrap().vote_ends_at {
+            Expiration::AtHeight(height) => height,
+            _ => unreachable!(),
+        };
+        let height = suite.app().block_info().height;
+        suite
+            .app()
+            .advance_blocks(vote_ends_at + EXECUTION_WINDOW - 1 - height);
+        suite.execute_proposal("owner", 1).unwrap();
+        assert_eq!(suite.query_proposal(1).unwrap().status, Status::Executed);
+    }
+
+    #[test]
+    fn should_expire_after_window() {
+        let mut suite = passed_proposal_suite();
+        suite.app().advance_blocks(EXECUTION_WINDOW);
+
+        let simulation = suite.query_simulate_dao_execution(1).unwrap();
+        assert!(!simulation.will_succeed);
+        assert_eq!(
+            simulation.blocking_reasons,
+            vec![ContractError::ExecutionWindowElapsed {}.to_string()]
+        );
+
+        let err = suite.execute_proposal("owner", 1).unwrap_err();
+        assert_eq!(
+            ContractError::ExecutionWindowElapsed {},
+            err.downcast().unwrap()
+        );
+
+        let resp = suite.expire_execution("tester0", 1).unwrap();
+        assert_eq!(
+            resp.custom_attrs(1),
+            [
+                Attribute::new("action", "expire_execution"),
+                Attribute::new("sender", "tester0"),
+                Attribute::new("proposal_id", "1"),
+            ]
+        );
+        let prop = suite.query_proposal(1).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+        assert!(prop.deposit_claimable);
+
+        suite.claim_deposit("owner", 1).unwrap();
+        assert!(suite.check_balance("owner", 100));
+    }
+
+    #[test]
+    fn should_fail_without_window() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![("tester0", 1)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD * 10);
+
+        let err = suite.expire_execution("tester0", 1).unwrap_err();
+        assert_eq!(ContractError::NoExecutionWindow {}, err.downcast().unwrap());
+        suite.execute_proposal("owner", 1).unwrap();
+    }
+}
@@ -1,11 +1,22 @@
-use crate::msg::{GovToken, RangeOrder};
-use crate::state::{Config, Threshold};
-use crate::tests::suite::{Suite, SuiteBuilder};
+use crate::msg::{
+    ConfigResponse, Cw3FixedMultisigInstantiateMsg, EligibilityHeightResponse, ExecuteMsg,
+    GovToken, ProposalDepositSummaryResponse, ProposalTimelineResponse, QueryMsg, RangeOrder,
+};
+use crate::state::{BlockTime, Config, ExecutePermission, Threshold, CONFIG_HISTORY_LIMIT};
+use crate::tests::suite::{
+    contract_cw20, contract_multisig, Suite, SuiteBuilder, DEFAULT_DEPOSIT_PERIOD,
+    DEFAULT_VOTING_PERIOD,
+};
+use crate::ContractError;
+use crate::CosmosMsg;
 
-use cosmwasm_std::{coins, Addr, Decimal, Uint128};
-use cw20::{Balance, Cw20CoinVerified, Denom};
+use cosmwasm_std::{coin, coins, to_binary, Addr, BankMsg, Decimal, Empty, Uint128, WasmMsg};
+use cw20::{Balance, Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg, Denom};
 use cw3::{Status, Vote};
-use cw_utils::{Duration, NativeBalance};
+use cw_multi_test::Executor;
+use cw_utils::{Duration, Expiration, NativeBalance};
+use osmo_bindings::{OsmosisMsg, SwapAmount, SwapAmountWithLimit};
+use osmo_bindings_test::Pool;
 
 #[test]
 fn test_get_config() {
@@ -20,6 +31,8 @@ fn test_get_config() {
             threshold: Decimal::percent(80),
             quorum: Decimal::percent(20),
             veto_threshold: Decimal::percent(99),
+            min_voters: None,
+            strict: false,
         })
         .with_periods(Some(Duration::Height(99)), Some(Duration::Height(10)))
         .with_deposits(Some(Uint128::new(10)), Some(Uint128::new(100)))
@@ -38,11 +51,40 @@ fn test_get_config() {
                 threshold: Decimal::percent(80),
                 quorum: Decimal::percent(20),
                 veto_threshold: Decimal::percent(99),
+                min_voters: None,
+                strict: false,
             },
             voting_period: Duration::Height(99),
             deposit_period: Duration::Height(10),
             proposal_deposit: Uint128::new(100),
-            proposal_min_deposit: Uint128::new(10)
+            proposal_min_deposit: Uint128::new(10),
+            high_participation_bonus: None,
+            auto_reap: false,
+            require_valid_link: false,
+            allow_deposit_top_up: false,
+            force_close_grace_period: Duration::Height(99),
+            max_query_limit: 30,
+            default_query_limit: 10,
+            execute_permission: ExecutePermission::Anyone,
+            execution_delay: None,
+            max_single_deposit: None,
+            min_distinct_depositors: None,
+            auto_scale_threshold: false,
+            total_token_supply: Uint128::zero(),
+            threshold_scaling_coefficient: Decimal::zero(),
+            bootstrap: None,
+            notifiers: vec![],
+            gov_token_decimals: None,
+            event_namespace: None,
+            field_thresholds: vec![],
+            auto_register_cw20: false,
+            execution_window: None,
+            recheck_quorum_on_execute: false,
+            voting_stake_age: 0,
+            reserves: vec![],
+            confiscation_policy: Default::default(),
+            record_tally_history: false,
+            max_treasury_tokens: crate::DEFAULT_MAX_TREASURY_TOKENS,
         }
     );
 }
@@ -78,6 +120,222 @@ fn test_token_list() {
     );
 }
 
+#[test]
+fn test_token_list_rejects_own_contracts() {
+    let mut suite = SuiteBuilder::new().build();
+    let dao = suite.dao.clone();
+    let stake = suite.stake.clone();
+
+    for addr in [&dao, &stake] {
+        let err = suite
+            .update_token_list(dao.as_str(), vec![Denom::Cw20(addr.clone())], vec![])
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidTreasuryToken {
+                address: addr.to_string()
+            },
+            err.downcast().unwrap()
+        );
+    }
+
+    let resp = suite.query_token_list().unwrap();
+    assert_eq!(resp.token_list, vec![Denom::Native("denom".to_string())]);
+}
+
+#[test]
+fn test_token_list_cap() {
+    let mut suite = SuiteBuilder::new().build();
+    let dao = suite.dao.clone();
+
+    let mut config = suite.query_config().unwrap().config;
+    assert_eq!(
+        config.max_treasury_tokens,
+        crate::DEFAULT_MAX_TREASURY_TOKENS
+    );
+    config.max_treasury_tokens = 3;
+    suite.update_config(dao.as_str(), config).unwrap();
+
+    // "denom" is listed from the start
+    suite
+        .update_token_list(
+            dao.as_str(),
+            vec![
+                Denom::Native("native-1".to_string()),
+                Denom::Native("native-2".to_string()),
+            ],
+            vec![],
+        )
+        .unwrap();
+
+    let err = suite
+        .update_token_list(
+            dao.as_str(),
+            vec![Denom::Native("native-3".to_string())],
+            vec![],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::OversizedRequest { size: 4, max: 3 },
+        err.downcast().unwrap()
+    );
+
+    // swapping a token keeps the list within the cap
+    suite
+        .update_token_list(
+            dao.as_str(),
+            vec![Denom::Native("native-3".to_string())],
+            vec![Denom::Native("native-1".to_string())],
+        )
+        .unwrap();
+
+    let resp = suite.query_token_list().unwrap();
+    assert_eq!(
+        resp.token_list,
+        vec![
+            Denom::Native("denom".to_string()),
+            Denom::Native("native-2".to_string()),
+            Denom::Native("native-3".to_string()),
+        ]
+    );
+
+    // received cw20 tokens count against the cap as well
+    let mut config = suite.query_config().unwrap().config;
+    config.max_treasury_tokens = 4;
+    config.auto_register_cw20 = true;
+    suite.update_config(dao.as_str(), config).unwrap();
+
+    let code_id = suite.app().store_code(contract_cw20());
+    let send = Cw20ExecuteMsg::Send {
+        contract: dao.to_string(),
+        amount: Uint128::new(10),
+        msg: Default::default(),
+    };
+    let mut tokens = vec![];
+    for symbol in ["TKNA", "TKNB"] {
+        let token = suite
+            .app()
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("owner"),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "token".to_string(),
+                    symbol: symbol.to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: "tester0".to_string(),
+                        amount: Uint128::new(100),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "cw20",
+                None,
+            )
+            .unwrap();
+        tokens.push(token);
+    }
+
+    suite
+        .app()
+        .execute_contract(Addr::unchecked("tester0"), tokens[0].clone(), &send, &[])
+        .unwrap();
+    let err = suite
+        .app()
+        .execute_contract(Addr::unchecked("tester0"), tokens[1].clone(), &send, &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::OversizedRequest { size: 5, max: 4 },
+        err.downcast().unwrap()
+    );
+    // the registered token can still receive more
+    suite
+        .app()
+        .execute_contract(Addr::unchecked("tester0"), tokens[0].clone(), &send, &[])
+        .unwrap();
+
+    let resp = suite.query_token_list().unwrap();
+    assert_eq!(resp.token_list.len(), 4);
+    assert!(resp.token_list.contains(&Denom::Cw20(tokens[0].clone())));
+}
+
+#[test]
+fn test_auto_register_cw20() {
+    let mut suite = SuiteBuilder::new().build();
+    let dao = suite.dao.clone();
+
+    let code_id = suite.app().store_code(contract_cw20());
+    let token = suite
+        .app()
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked("owner"),
+            &cw20_base::msg::InstantiateMsg {
+                name: "token".to_string(),
+                symbol: "TKN".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: "tester0".to_string(),
+                    amount: Uint128::new(100),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "cw20",
+            None,
+        )
+        .unwrap();
+    let send = Cw20ExecuteMsg::Send {
+        contract: dao.to_string(),
+        amount: Uint128::new(10),
+        msg: Default::default(),
+    };
+
+    // off by default, the tokens are received all the same
+    suite
+        .app()
+        .execute_contract(Addr::unchecked("tester0"), token.clone(), &send, &[])
+        .unwrap();
+    let resp = suite.query_token_list().unwrap();
+    assert_eq!(resp.token_list, vec![Denom::Native("denom".to_string())]);
+
+    let mut config = suite.query_config().unwrap().config;
+    config.auto_register_cw20 = true;
+    suite.update_config(dao.as_str(), config).unwrap();
+
+    suite
+        .app()
+        .execute_contract(Addr::unchecked("tester0"), token.clone(), &send, &[])
+        .unwrap();
+    let resp = suite.query_token_list().unwrap();
+    assert_eq!(
+        resp.token_list,
+        vec![Denom::Cw20(token), Denom::Native("denom".to_string())]
+    );
+
+    // only actual cw20 contracts get registered
+    let err = suite
+        .app()
+        .execute_contract(
+            Addr::unchecked("tester0"),
+            dao,
+            &ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+                sender: "tester0".to_string(),
+                amount: Uint128::new(10),
+                msg: Default::default(),
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidCw20 {
+            addr: "tester0".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
 #[test]
 fn test_token_balances() {
     let mut suite = SuiteBuilder::new()
@@ -211,6 +469,75 @@ mod proposal {
         }
     }
 
+    #[test]
+    fn test_single_query_maybe() {
+        let suite = SuiteBuilder::new()
+            .with_staked(vec![("owner", 100u128)])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        let resp = suite.query_proposal_maybe(1).unwrap().unwrap();
+        assert_eq!(resp, suite.query_proposal(1).unwrap());
+
+        assert_eq!(suite.query_proposal_maybe(2).unwrap(), None);
+        suite.query_proposal(2).unwrap_err();
+    }
+
+    #[test]
+    fn test_custom_query_limits() {
+        let mut builder = SuiteBuilder::new()
+            .with_staked(vec![("owner", 100u128)])
+            .with_query_limits(50, 20);
+        for i in 1..=40 {
+            builder = builder.add_proposal(i.to_string(), i.to_string(), i.to_string(), vec![]);
+        }
+        let suite = builder.build();
+
+        let resp = suite
+            .query_proposals(
+                ProposalsQueryOption::Everything { cursor: None },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(resp.proposals.len(), 20);
+
+        let resp = suite
+            .query_proposals(
+                ProposalsQueryOption::Everything { cursor: None },
+                None,
+                Some(40),
+                None,
+            )
+            .unwrap();
+        assert_eq!(resp.proposals.len(), 40);
+
+        suite
+            .query_proposals(
+                ProposalsQueryOption::Everything { cursor: None },
+                None,
+                Some(51),
+                None,
+            )
+            .unwrap_err();
+
+        // default limits still cap at 30
+        let mut builder = SuiteBuilder::new().with_staked(vec![("owner", 100u128)]);
+        for i in 1..=40 {
+            builder = builder.add_proposal(i.to_string(), i.to_string(), i.to_string(), vec![]);
+        }
+        let suite = builder.build();
+        suite
+            .query_proposals(
+                ProposalsQueryOption::Everything { cursor: None },
+                None,
+                Some(40),
+                None,
+            )
+            .unwrap_err();
+    }
+
     #[test]
     fn test_multi_query_everything() {
         let suite = pre_setup_proposal_state();
@@ -234,7 +561,7 @@ mod proposal {
 
             let resp = suite
                 .query_proposals(
-                    ProposalsQueryOption::Everything {},
+                    ProposalsQueryOption::Everything { cursor: None },
                     *start,
                     *limit,
                     order.clone(),
@@ -273,6 +600,7 @@ mod proposal {
                     .query_proposals(
                         ProposalsQueryOption::FindByProposer {
                             proposer: proposer.clone(),
+                            cursor: None,
                         },
                         *start,
                         *limit,
@@ -307,7 +635,7 @@ mod proposal {
             [1u64, 3u64, 5u64, 7u64],
             [2u64, 4u64, 6u64, 8u64],
         ];
-        for (i, status) in vec![
+        for (i, status) in [
             Status::Pending,
             Status::Open,
             Status::Rejected,
@@ -325,7 +653,10 @@ mod proposal {
 
             let resp = suite
                 .query_proposals(
-                    ProposalsQueryOption::FindByStatus { status: *status },
+                    ProposalsQueryOption::FindByStatus {
+                        status: *status,
+                        cursor: None,
+                    },
                     None,
                     None,
                     None,
@@ -340,6 +671,455 @@ mod proposal {
         }
     }
 
+    #[test]
+    fn test_multi_query_by_category() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 100000000u128)])
+            .with_staked(vec![("owner", 100u128)])
+            .build();
+        let dao = suite.dao.clone();
+
+        // only governance can maintain the category set
+        let err = suite
+            .update_categories("tester0", vec!["treasury"], vec![])
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        suite
+            .update_categories(dao.as_str(), vec!["treasury", "text"], vec![])
+            .unwrap();
+
+        let err = suite
+            .propose_in_category("tester0", "grants", Some(10000000))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidProposalContent {
+                reason: "unknown category 'grants'".to_string()
+            },
+            err.downcast().unwrap()
+        );
+
+        suite
+            .propose_in_category("tester0", "treasury", Some(10000000))
+            .unwrap();
+        suite
+            .propose_in_category("tester0", "text", Some(10000000))
+            .unwrap();
+        suite
+            .propose_in_category("tester0", "treasury", Some(10000000))
+            .unwrap();
+
+        let resp = suite
+            .query_proposals(
+                ProposalsQueryOption::FindByCategory {
+                    category: "treasury".to_string(),
+                    cursor: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            resp.proposals
+                .iter()
+                .map(|x| (x.id, x.category.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, Some("treasury".to_string())),
+                (3, Some("treasury".to_string()))
+            ]
+        );
+
+        // retiring a category blocks new proposals but keeps existing ones indexed
+        suite
+            .update_categories(dao.as_str(), vec![], vec!["treasury"])
+            .unwrap();
+        suite
+            .propose_in_category("tester0", "treasury", Some(10000000))
+            .unwrap_err();
+        let resp = suite
+            .query_proposals(
+                ProposalsQueryOption::FindByCategory {
+                    category: "treasury".to_string(),
+                    cursor: None,
+                },
+                None,
+                None,
+                Some(RangeOrder::Desc),
+            )
+            .unwrap();
+        assert_eq!(
+            resp.proposals.iter().map(|x| x.id).collect::<Vec<_>>(),
+            vec![3, 1]
+        );
+    }
+
+    #[test]
+    fn test_proposals_by_deposit() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 1000), ("tester1", 1000)])
+            .with_staked(vec![("owner", 100)])
+            .build();
+
+        for deposit in [30, 50, 10, 50] {
+            suite
+                .propose("tester0", "title", "link", "desc", vec![], Some(deposit))
+                .unwrap();
+        }
+        let ranked = |suite: &Suite, descending, start, limit| {
+            suite
+                .query_proposals(
+                    ProposalsQueryOption::ByDeposit {
+                        descending,
+                        cursor: None,
+                    },
+                    start,
+                    limit,
+                    None,
+                )
+                .unwrap()
+                .proposals
+                .iter()
+                .map(|p| (p.id, p.total_deposit.u128()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ranked(&suite, true, None, None),
+            vec![(4, 50), (2, 50), (1, 30), (3, 10)]
+        );
+        assert_eq!(
+            ranked(&suite, false, None, None),
+            vec![(3, 10), (1, 30), (2, 50), (4, 50)]
+        );
+
+        // every deposit change moves the proposal
+        suite.deposit("tester1", 3, Some(60)).unwrap();
+        suite.withdraw_deposit("tester1", 3).unwrap();
+        suite.deposit("tester1", 1, Some(40)).unwrap();
+        assert_eq!(
+            ranked(&suite, true, None, None),
+            vec![(1, 70), (4, 50), (2, 50), (3, 10)]
+        );
+
+        // pages continue after the proposal given as `start`
+        assert_eq!(
+            ranked(&suite, true, Some(4), Some(2)),
+            vec![(2, 50), (3, 10)]
+        );
+    }
+
+    #[test]
+    fn test_proposals_cursor() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 1000), ("tester1", 1000)])
+            .with_staked(vec![("owner", 100)])
+            .build();
+
+        for deposit in [30, 50, 10, 50, 20] {
+            suite
+                .propose("tester0", "title", "link", "desc", vec![], Some(deposit))
+                .unwrap();
+        }
+        let page = |suite: &Suite, query| {
+            let resp = suite.query_proposals(query, None, Some(2), None).unwrap();
+            let ids = resp.proposals.iter().map(|p| p.id).collect::<Vec<_>>();
+            (ids, resp.next_cursor)
+        };
+
+        let (ids, cursor) = page(&suite, ProposalsQueryOption::Everything { cursor: None });
+        assert_eq!(ids, vec![1, 2]);
+        let (ids, cursor) = page(&suite, ProposalsQueryOption::Everything { cursor });
+        assert_eq!(ids, vec![3, 4]);
+        let (ids, last) = page(
+            &suite,
+            ProposalsQueryOption::Everything {
+                cursor: cursor.clone(),
+            },
+        );
+        assert_eq!(ids, vec![5]);
+        assert_eq!(last, None);
+
+        // a cursor only resumes the query it came from
+        let err = suite
+            .query_proposals(
+                ProposalsQueryOption::FindByStatus {
+                    status: Status::Pending,
+                    cursor,
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("belongs to another query"));
+
+        let (ids, cursor) = page(
+            &suite,
+            ProposalsQueryOption::FindByStatus {
+                status: Status::Pending,
+                cursor: None,
+            },
+        );
+        assert_eq!(ids, vec![1, 2]);
+        let (ids, _) = page(
+            &suite,
+            ProposalsQueryOption::FindByStatus {
+                status: Status::Pending,
+                cursor,
+            },
+        );
+        assert_eq!(ids, vec![3, 4]);
+
+        let (ids, cursor) = page(
+            &suite,
+            ProposalsQueryOption::ByDeposit {
+                descending: true,
+                cursor: None,
+            },
+        );
+        assert_eq!(ids, vec![4, 2]);
+        // the last proposal seen moves up the ranking, the cursor keeps its place
+        suite.deposit("tester1", 2, Some(40)).unwrap();
+        let (ids, _) = page(
+            &suite,
+            ProposalsQueryOption::ByDeposit {
+                descending: true,
+                cursor,
+            },
+        );
+        assert_eq!(ids, vec![1, 5]);
+    }
+
+    #[test]
+    fn test_participation_by_height() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![
+                ("tester0", 40),
+                ("tester1", 30),
+                ("tester2", 20),
+                ("tester3", 10),
+            ])
+            .add_proposal("title", "link", "desc", vec![])
+            .build();
+
+        // nothing is recorded until the DAO opts in
+        suite.vote("tester0", 1, Vote::Yes).unwrap();
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.record_tally_history = true;
+        suite.update_config(dao.as_str(), config.clone()).unwrap();
+        assert!(suite
+            .query_participation(1, None, None)
+            .unwrap()
+            .points
+            .is_empty());
+
+        let mut heights = vec![];
+        for voters in [vec!["tester1"], vec!["tester2", "tester0"]] {
+            suite.app().advance_blocks(1);
+            heights.push(suite.app().block_info().height);
+            for voter in voters {
+                suite.vote(voter, 1, Vote::No).unwrap();
+            }
+        }
+
+        let resp = suite.query_participation(1, None, None).unwrap();
+        assert_eq!(resp.total_weight, Uint128::new(100));
+        assert_eq!(
+            resp.points
+                .iter()
+                .map(|p| (p.height, p.total_votes.u128(), p.turnout))
+                .collect::<Vec<_>>(),
+            vec![
+                (heights[0], 70, Decimal::percent(70)),
+                // a re-vote in the same block doesn't add turnout
+                (heights[1], 90, Decimal::percent(90)),
+            ]
+        );
+
+        let resp = suite
+            .query_participation(1, Some(heights[0]), None)
+            .unwrap();
+        assert_eq!(resp.points.len(), 1);
+        assert_eq!(resp.points[0].height, heights[1]);
+
+        // switched off again, the history is hidden
+        config.record_tally_history = false;
+        suite.update_config(dao.as_str(), config).unwrap();
+        assert!(suite
+            .query_participation(1, None, None)
+            .unwrap()
+            .points
+            .is_empty());
+    }
+
+    #[test]
+    fn test_audit_healthy_dao() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 1000), ("tester1", 1000)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+
+        let audit = suite.query_audit(None, None).unwrap();
+        assert!(audit.healthy);
+        assert_eq!(audit.proposal_count, 0);
+        assert_eq!(audit.last_id, None);
+
+        for deposit in [10, 100, 40] {
+            suite
+                .propose("tester0", "title", "link", "desc", vec![], Some(deposit))
+                .unwrap();
+        }
+        suite.deposit("tester1", 1, Some(20)).unwrap();
+        suite.deposit("tester1", 3, Some(20)).unwrap();
+        suite.withdraw_deposit("tester1", 3).unwrap();
+        suite.vote("tester0", 2, Vote::No).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        suite.close_proposal("tester0", 1).unwrap();
+        suite.close_proposal("tester0", 2).unwrap();
+        suite.claim_deposit("tester0", 2).unwrap();
+
+        let audit = suite.query_audit(None, None).unwrap();
+        assert_eq!(
+            audit,
+            crate::msg::AuditResponse {
+                healthy: true,
+                proposal_count: 3,
+                max_proposal_id: 3,
+                proposal_count_matches: true,
+                deposit_mismatches: vec![],
+                status_index_mismatches: vec![],
+                last_id: Some(3),
+            }
+        );
+
+        // paging through
+        assert_eq!(suite.query_audit(None, Some(2)).unwrap().last_id, Some(2));
+        let audit = suite.query_audit(Some(2), Some(2)).unwrap();
+        assert!(audit.healthy);
+        assert_eq!(audit.last_id, Some(3));
+        assert_eq!(suite.query_audit(Some(3), Some(2)).unwrap().last_id, None);
+    }
+
+    #[test]
+    fn test_eligibility_height() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("tester0", 1000), ("tester1", 100)])
+            .with_staked(vec![("tester0", 100)])
+            .build();
+        let dao = suite.dao.clone();
+        let mut config = suite.query_config().unwrap().config;
+        config.voting_stake_age = 5;
+        suite.update_config(dao.as_str(), config).unwrap();
+        // tester0 has held its stake long enough
+        suite.app().advance_blocks(5);
+
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(10))
+            .unwrap();
+        assert_eq!(
+            suite.query_eligibility_height(1).unwrap(),
+            EligibilityHeightResponse {
+                proposal_id: 1,
+                height: None,
+                voting_stake_age: 5,
+            }
+        );
+
+        // staked too late to count for a proposal opening now
+        suite.stake("tester1", 100u128).unwrap();
+        suite.app().advance_blocks(2);
+        suite
+            .propose("tester0", "title", "link", "desc", vec![], Some(100))
+            .unwrap();
+        let opened_at = suite.query_proposal(2).unwrap().vote_starts_at.height;
+        assert_eq!(
+            suite.query_eligibility_height(2).unwrap().height,
+            Some(opened_at - 5)
+        );
+
+        // the total weight is read at the same height
+        assert_eq!(
+            suite.query_proposal(2).unwrap().total_weight,
+            Uint128::new(100)
+        );
+
+        // lowering the stake age afterwards doesn't move the height of an open proposal
+        let mut config = suite.query_config().unwrap().config;
+        config.voting_stake_age = 0;
+        suite.update_config(dao.as_str(), config).unwrap();
+        assert_eq!(
+            suite.query_eligibility_height(2).unwrap(),
+            EligibilityHeightResponse {
+                proposal_id: 2,
+                height: Some(opened_at - 5),
+                voting_stake_age: 0,
+            }
+        );
+
+        let err = suite.vote("tester1", 2, Vote::Yes).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        suite.vote("tester0", 2, Vote::Yes).unwrap();
+    }
+
+    #[test]
+    fn test_all_proposals_status_map() {
+        let suite = pre_setup_proposal_state();
+
+        let map = suite
+            .query_all_proposals_status_map(None, Some(30), None)
+            .unwrap();
+        assert_eq!(map.len(), 16);
+        for (id, status) in &map {
+            assert_eq!(suite.query_proposal(*id).unwrap().status, *status);
+        }
+
+        let page = suite
+            .query_all_proposals_status_map(Some(10), None, None)
+            .unwrap();
+        assert_eq!(page, map[10..].to_vec());
+        let page = suite
+            .query_all_proposals_status_map(Some(5), Some(2), Some(RangeOrder::Desc))
+            .unwrap();
+        assert_eq!(page, vec![map[3], map[2]]);
+    }
+
+    #[test]
+    fn test_governance_stats() {
+        let suite = SuiteBuilder::new().build();
+        let stats = suite.query_governance_stats().unwrap();
+        assert_eq!(stats.total_proposals, 0);
+        assert_eq!(stats.execution_success_rate, Decimal::zero());
+
+        let suite = pre_setup_proposal_state();
+
+        // 4 rejected + 4 executed, all voted by the single staker
+        let stats = suite.query_governance_stats().unwrap();
+        assert_eq!(stats.total_proposals, 16);
+        assert_eq!(stats.avg_quorum, Decimal::percent(33));
+        assert_eq!(stats.avg_voter_turnout, Decimal::one());
+        assert_eq!(stats.execution_success_rate, Decimal::percent(50));
+        assert_eq!(stats.avg_deposit_fill_rate, Decimal::one());
+    }
+
+    #[test]
+    fn test_dao_stats() {
+        let mut suite = SuiteBuilder::new().build();
+        let stats = suite.query_dao_stats().unwrap();
+        assert_eq!(stats.total_proposals, 0);
+        assert_eq!(stats.total_executed, 0);
+        assert_eq!(stats.total_rejected, 0);
+        assert_eq!(stats.created_at, suite.query_config().unwrap().created_at);
+        assert!(stats.created_at.height <= suite.app().block_info().height);
+
+        let suite = pre_setup_proposal_state();
+        let stats = suite.query_dao_stats().unwrap();
+        assert_eq!(stats.total_proposals, 16);
+        assert_eq!(stats.total_executed, 4);
+        assert_eq!(stats.total_rejected, 4);
+    }
+
     #[test]
     fn test_query_count() {
         let suite = pre_setup_proposal_state();
@@ -366,7 +1146,7 @@ mod vote {
          * |------------|----------------|
          */
 
-        let votes = vec![Vote::Yes, Vote::No, Vote::Abstain, Vote::Veto];
+        let votes = [Vote::Yes, Vote::No, Vote::Abstain, Vote::Veto];
         for i in 0u64..4 {
             let voter = format!("tester{}", i);
             let id = |x: u64| x % 4 + 1;
@@ -411,6 +1191,65 @@ mod vote {
         assert!(suite.query_vote(5, "tester0").unwrap().vote.is_none());
     }
 
+    #[test]
+    fn test_votes_breakdown() {
+        let mut suite = SuiteBuilder::new()
+            .with_staked(vec![
+                ("tester0", 10),
+                ("tester1", 20),
+                ("tester2", 30),
+                ("tester3", 40),
+            ])
+            .add_proposal("t", "l", "d", vec![]) // 1
+            .add_proposal("t", "l", "d", vec![]) // 2
+            .add_proposal("t", "l", "d", vec![]) // 3
+            .add_proposal("t", "l", "d", vec![]) // 4
+            .build();
+        setup_voting_state("owner", &mut suite);
+        // pile more votes on proposal 1
+        suite.vote("tester1", 1, Vote::Yes).unwrap();
+        suite.vote("tester2", 1, Vote::Yes).unwrap();
+
+        let info = |voter: &str, vote: Vote, weight: u128| crate::msg::VoteInfo {
+            voter: voter.to_string(),
+            vote,
+            weight: Uint128::new(weight),
+            rationale: None,
+        };
+
+        let resp = suite.query_votes_breakdown(1, None).unwrap();
+        assert_eq!(
+            resp.yes,
+            vec![
+                info("tester2", Vote::Yes, 30),
+                info("tester1", Vote::Yes, 20),
+                info("tester0", Vote::Yes, 10),
+            ]
+        );
+        assert_eq!(resp.no, vec![info("tester3", Vote::No, 40)]);
+        assert_eq!(resp.abstain, vec![]);
+        assert_eq!(resp.veto, vec![]);
+        assert!(!resp.capped);
+
+        let resp = suite.query_votes_breakdown(1, Some(2)).unwrap();
+        assert_eq!(
+            resp.yes,
+            vec![
+                info("tester2", Vote::Yes, 30),
+                info("tester1", Vote::Yes, 20),
+            ]
+        );
+
+        // see the table above
+        let resp = suite.query_votes_breakdown(2, None).unwrap();
+        assert_eq!(resp.yes, vec![info("tester1", Vote::Yes, 20)]);
+        assert_eq!(resp.no, vec![info("tester0", Vote::No, 10)]);
+        assert_eq!(resp.abstain, vec![info("tester3", Vote::Abstain, 40)]);
+        assert_eq!(resp.veto, vec![info("tester2", Vote::Veto, 30)]);
+
+        suite.query_votes_breakdown(1, Some(6)).unwrap_err();
+    }
+
     #[test]
     fn test_multi_query() {
         let suite = pre_setup_vote_state();
@@ -504,8 +1343,216 @@ mod deposit {
         }
     }
 
-    // TODO
-    // #[test]
+    #[test]
+    fn test_top_depositors() {
+        let suite = pre_setup_deposit_state();
+        let ranking = |proposal_id, limit| {
+            suite
+                .query_top_depositors(proposal_id, limit)
+                .unwrap()
+                .depositors
+                .into_iter()
+                .map(|d| (d.depositor, d.amount.u128()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ranking(1, Some(3)),
+            vec![
+                ("owner".to_string(), 10),
+                ("tester3".to_string(), 4),
+                ("tester2".to_string(), 3)
+            ]
+        );
+        assert_eq!(
+            ranking(2, None),
+            vec![
+                ("owner".to_string(), 10),
+                ("tester2".to_string(), 4),
+                ("tester1".to_string(), 3),
+                ("tester0".to_string(), 2),
+                ("tester3".to_string(), 1)
+            ]
+        );
+        assert!(!suite.query_top_depositors(1, None).unwrap().capped);
+    }
+
+    #[test]
+    fn test_top_depositors_capped() {
+        let depositors = (0..31)
+            .map(|i| (format!("depositor{:02}", i), 10))
+            .collect::<Vec<(String, u128)>>();
+        let mut suite = SuiteBuilder::new()
+            .with_funds(depositors.clone())
+            .with_funds(vec![("owner", 20)])
+            .with_staked(vec![("owner", 10)])
+            .build();
+        suite
+            .propose("owner", "t", "l", "d", vec![], Some(10))
+            .unwrap();
+        for (depositor, _) in depositors.iter() {
+            suite.deposit(depositor, 1, Some(1)).unwrap();
+        }
+
+        // 32 deposits, only 30 are scanned
+        let resp = suite.query_top_depositors(1, Some(30)).unwrap();
+        assert!(resp.capped);
+        assert_eq!(resp.depositors.len(), 30);
+    }
+
+    #[test]
+    fn test_top_depositors_follow_query_limits() {
+        let depositors = (0..5)
+            .map(|i| (format!("depositor{:02}", i), 10))
+            .collect::<Vec<(String, u128)>>();
+        let mut suite = SuiteBuilder::new()
+            .with_funds(depositors.clone())
+            .with_funds(vec![("owner", 20)])
+            .with_staked(vec![("owner", 10)])
+            .with_query_limits(4, 2)
+            .build();
+        suite
+            .propose("owner", "t", "l", "d", vec![], Some(10))
+            .unwrap();
+        for (depositor, _) in depositors.iter() {
+            suite.deposit(depositor, 1, Some(1)).unwrap();
+        }
+
+        assert_eq!(
+            suite
+                .query_top_depositors(1, None)
+                .unwrap()
+                .depositors
+                .len(),
+            2
+        );
+        let resp = suite.query_top_depositors(1, Some(4)).unwrap();
+        assert!(resp.capped);
+        assert_eq!(resp.depositors.len(), 4);
+    }
+
+    #[test]
+    fn test_deposit_shortfall() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("owner", 110)])
+            .with_staked(vec![("owner", 10)])
+            .build();
+
+        // pending
+        suite
+            .propose("owner", "t", "l", "d", vec![], Some(10))
+            .unwrap();
+        // open
+        suite
+            .propose("owner", "t", "l", "d", vec![], Some(100))
+            .unwrap();
+
+        let resp = suite.query_deposit_shortfall(1).unwrap();
+        let block = suite.app().block_info();
+        assert_eq!(resp.total_deposit, Uint128::new(10));
+        assert_eq!(resp.required, Uint128::new(100));
+        assert_eq!(resp.shortfall, Uint128::new(90));
+        assert_eq!(
+            resp.deposit_ends_at,
+            Expiration::AtHeight(block.height + DEFAULT_DEPOSIT_PERIOD)
+        );
+        assert!(!resp.expired);
+
+        let resp = suite.query_deposit_shortfall(2).unwrap();
+        assert_eq!(resp.total_deposit, Uint128::new(100));
+        assert_eq!(resp.shortfall, Uint128::zero());
+
+        // expired pending proposal is rejected, nothing left to fill
+        suite.app().advance_blocks(DEFAULT_DEPOSIT_PERIOD);
+        let resp = suite.query_deposit_shortfall(1).unwrap();
+        assert_eq!(resp.shortfall, Uint128::zero());
+        assert!(resp.expired);
+    }
+
+    #[test]
+    fn test_proposal_deposit_summary() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("owner", 50), ("tester0", 50), ("tester1", 10)])
+            .with_staked(vec![("owner", 10)])
+            .build();
+        suite
+            .propose("owner", "t", "l", "d", vec![], Some(50))
+            .unwrap();
+        suite.deposit("tester0", 1, Some(20)).unwrap();
+        suite.deposit("tester1", 1, Some(10)).unwrap();
+
+        let summary = suite.query_proposal_deposit_summary(1).unwrap();
+        assert_eq!(summary.total_amount, Uint128::new(80));
+        assert_eq!(summary.depositor_count, 3);
+
+        suite.withdraw_deposit("tester1", 1).unwrap();
+        // topping up an existing deposit adds no depositor
+        suite.deposit("tester0", 1, Some(30)).unwrap();
+        let summary = suite.query_proposal_deposit_summary(1).unwrap();
+        assert_eq!(summary.total_amount, Uint128::new(100));
+        assert_eq!(summary.depositor_count, 2);
+        assert!(!summary.claimable);
+
+        suite.vote("owner", 1, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        suite.execute_proposal("owner", 1).unwrap();
+        suite.claim_deposit("tester0", 1).unwrap();
+
+        let summary = suite.query_proposal_deposit_summary(1).unwrap();
+        assert_eq!(
+            summary,
+            ProposalDepositSummaryResponse {
+                proposal_id: 1,
+                total_amount: Uint128::new(100),
+                depositor_count: 2,
+                claimable: true,
+                claimed_count: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_claimable_deposits() {
+        let mut suite = SuiteBuilder::new()
+            .with_funds(vec![("owner", 300), ("tester0", 30)])
+            .with_staked(vec![("owner", 10)])
+            .build();
+        for _ in 0..3 {
+            suite
+                .propose("owner", "t", "l", "d", vec![], Some(90))
+                .unwrap();
+        }
+        for prop in 1..=3 {
+            suite.deposit("tester0", prop, Some(10)).unwrap();
+        }
+        assert_eq!(suite.query_claimable_deposits("tester0").unwrap(), vec![]);
+
+        suite.vote("owner", 1, Vote::Yes).unwrap();
+        suite.vote("owner", 2, Vote::Yes).unwrap();
+        suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+        suite.execute_proposal("owner", 1).unwrap();
+        suite.execute_proposal("owner", 2).unwrap();
+
+        // proposal 3 failed but was never closed, its deposits are still locked
+        let claimable = vec![(1, Uint128::new(10)), (2, Uint128::new(10))];
+        assert_eq!(
+            suite.query_claimable_deposits("tester0").unwrap(),
+            claimable
+        );
+        assert_eq!(
+            suite.query_claimable_deposits("owner").unwrap(),
+            vec![(1, Uint128::new(90)), (2, Uint128::new(90))]
+        );
+
+        suite.claim_deposit("tester0", 1).unwrap();
+        assert_eq!(
+            suite.query_claimable_deposits("tester0").unwrap(),
+            vec![(2, Uint128::new(10))]
+        );
+    }
+
+    // TODO
+    // #[test]
     // fn test_multi_query_everything() {
     //     let suite = pre_setup_deposit_state();
     //
@@ -516,3 +1563,742 @@ mod deposit {
     //
     // }
 }
+
+#[test]
+fn test_self_stake() {
+    let mut suite = SuiteBuilder::new()
+        .with_funds(vec![("tester0", 200)])
+        .with_staked(vec![("tester0", 100)])
+        .build();
+
+    assert_eq!(suite.query_self_stake().unwrap().balance, Uint128::zero());
+
+    let dao = suite.dao.clone();
+    let stake = suite.stake.clone();
+    suite
+        .app()
+        .send_tokens(Addr::unchecked("tester0"), dao, &coins(50, "denom"))
+        .unwrap();
+
+    let stake_msg = WasmMsg::Execute {
+        contract_addr: stake.to_string(),
+        msg: to_binary(&ion_stake::msg::ExecuteMsg::Stake {}).unwrap(),
+        funds: coins(50, "denom"),
+    };
+    suite
+        .propose("tester0", "t", "l", "d", vec![stake_msg.into()], Some(100))
+        .unwrap();
+    suite.vote("tester0", 1, Vote::Yes).unwrap();
+    suite.app().advance_blocks(15);
+    suite.execute_proposal("tester0", 1).unwrap();
+    suite.app().advance_blocks(1);
+
+    let resp = suite.query_self_stake().unwrap();
+    assert_eq!(resp.balance, Uint128::new(50));
+    assert_eq!(resp.height, suite.app().block_info().height);
+}
+
+#[test]
+fn test_expiration_status() {
+    let mut suite = SuiteBuilder::new().build();
+    let block = suite.app().block_info();
+
+    let resp = suite
+        .query_expiration_status(Expiration::AtHeight(block.height + 10))
+        .unwrap();
+    assert!(!resp.is_expired);
+    assert_eq!(resp.blocks_remaining, Some(10));
+    assert_eq!(resp.seconds_remaining, None);
+
+    let resp = suite
+        .query_expiration_status(Expiration::AtTime(block.time.plus_seconds(60)))
+        .unwrap();
+    assert!(!resp.is_expired);
+    assert_eq!(resp.blocks_remaining, None);
+    assert_eq!(resp.seconds_remaining, Some(60));
+
+    let resp = suite.query_expiration_status(Expiration::Never {}).unwrap();
+    assert!(!resp.is_expired);
+    assert_eq!(resp.blocks_remaining, None);
+    assert_eq!(resp.seconds_remaining, None);
+
+    suite.app().advance_blocks(15);
+    let resp = suite
+        .query_expiration_status(Expiration::AtHeight(block.height + 10))
+        .unwrap();
+    assert!(resp.is_expired);
+    assert_eq!(resp.blocks_remaining, Some(0));
+}
+
+#[test]
+fn test_voting_powers() {
+    let mut suite = SuiteBuilder::new()
+        .with_funds(vec![("tester0", 300), ("tester1", 300), ("tester2", 300)])
+        .with_staked(vec![("tester0", 100), ("tester1", 50)])
+        .build();
+
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(100))
+        .unwrap();
+    let prop = suite.query_proposal(1).unwrap();
+    assert_eq!(prop.status, Status::Open);
+
+    // balances change after voting started
+    suite.app().advance_blocks(1);
+    suite.stake("tester0", 100u128).unwrap();
+    suite.stake("tester2", 70u128).unwrap();
+    suite.unstake("tester1", 20u128).unwrap();
+    suite.app().advance_blocks(1);
+
+    let resp = suite
+        .query_voting_powers(1, vec!["tester0", "tester1", "tester2"])
+        .unwrap();
+    assert_eq!(resp.height, prop.vote_starts_at.height);
+    assert_eq!(
+        resp.balances
+            .into_iter()
+            .map(|b| (b.address.to_string(), b.balance.u128()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("tester0".to_string(), 100),
+            ("tester1".to_string(), 50),
+            ("tester2".to_string(), 0)
+        ]
+    );
+
+    // a pending proposal has no voting height yet
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(10))
+        .unwrap();
+    assert_eq!(suite.query_proposal(2).unwrap().status, Status::Pending);
+    let err = suite.query_voting_powers(2, vec!["tester0"]).unwrap_err();
+    assert!(err.to_string().contains("Voting has not started"));
+}
+
+#[test]
+fn test_index_drift() {
+    let mut suite = SuiteBuilder::new()
+        .with_funds(vec![("tester0", 300)])
+        .with_staked(vec![("tester0", 100)])
+        .build();
+
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(100))
+        .unwrap();
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(100))
+        .unwrap();
+    suite.vote("tester0", 2, Vote::Yes).unwrap();
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(10))
+        .unwrap();
+    assert!(suite.query_index_drift(None).unwrap().is_empty());
+
+    // nobody voted on 1, 2 passed and 3 never got its deposit
+    suite.app().advance_blocks(DEFAULT_VOTING_PERIOD + 5);
+    let drift = suite.query_index_drift(None).unwrap();
+    assert_eq!(
+        drift
+            .iter()
+            .map(|entry| (entry.id, entry.stored, entry.current))
+            .collect::<Vec<_>>(),
+        vec![
+            (3, Status::Pending, Status::Rejected),
+            (1, Status::Open, Status::Rejected),
+            (2, Status::Open, Status::Passed),
+        ]
+    );
+    assert_eq!(suite.query_index_drift(Some(1)).unwrap(), drift[..1]);
+
+    // settling a proposal moves it out of the report
+    suite.close_proposal("tester0", 1).unwrap();
+    suite.execute_proposal("tester0", 2).unwrap();
+    let drift = suite.query_index_drift(None).unwrap();
+    assert_eq!(
+        drift.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+        vec![3]
+    );
+}
+
+#[test]
+fn test_proposal_status_at() {
+    let mut suite = SuiteBuilder::new()
+        .with_funds(vec![("tester0", 300)])
+        .with_staked(vec![("tester0", 100)])
+        .build();
+
+    let submitted = suite.app().block_info().height;
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(100))
+        .unwrap();
+    suite.vote("tester0", 1, Vote::Yes).unwrap();
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(100))
+        .unwrap();
+    suite.vote("tester0", 2, Vote::No).unwrap();
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(10))
+        .unwrap();
+
+    suite.app().advance_blocks(DEFAULT_VOTING_PERIOD + 5);
+    suite.execute_proposal("tester0", 1).unwrap();
+    let executed = suite.app().block_info().height;
+
+    let mid = submitted + DEFAULT_VOTING_PERIOD / 2;
+    let after = submitted + DEFAULT_VOTING_PERIOD + 1;
+
+    assert_eq!(
+        suite.query_proposal_status_at(1, mid).unwrap(),
+        Status::Open
+    );
+    assert_eq!(
+        suite.query_proposal_status_at(1, after).unwrap(),
+        Status::Passed
+    );
+    assert_eq!(
+        suite.query_proposal_status_at(1, executed).unwrap(),
+        Status::Executed
+    );
+    assert_eq!(
+        suite.query_proposal_status_at(2, mid).unwrap(),
+        Status::Open
+    );
+    assert_eq!(
+        suite.query_proposal_status_at(2, after).unwrap(),
+        Status::Rejected
+    );
+
+    // underfunded proposal stays pending until the deposit period ends
+    assert_eq!(
+        suite.query_proposal_status_at(3, submitted).unwrap(),
+        Status::Pending
+    );
+    assert_eq!(
+        suite
+            .query_proposal_status_at(3, submitted + DEFAULT_DEPOSIT_PERIOD)
+            .unwrap(),
+        Status::Rejected
+    );
+
+    suite
+        .query_proposal_status_at(1, submitted - 1)
+        .unwrap_err();
+}
+
+#[test]
+fn test_proposals_updated_after() {
+    let mut suite = SuiteBuilder::new()
+        .with_funds(vec![("tester0", 300)])
+        .with_staked(vec![("tester0", 100)])
+        .build();
+
+    let created = suite.app().block_info().height;
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(100))
+        .unwrap();
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(10))
+        .unwrap();
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(10))
+        .unwrap();
+    assert_eq!(
+        suite
+            .query_proposals_updated_after(created - 1, None, None)
+            .unwrap()
+            .0,
+        vec![1, 2, 3]
+    );
+    assert!(suite
+        .query_proposals_updated_after(created, None, None)
+        .unwrap()
+        .0
+        .is_empty());
+
+    // a page ending within a height resumes right after its last proposal
+    let (page, cursor) = suite
+        .query_proposals_updated_after(created - 1, None, Some(2))
+        .unwrap();
+    assert_eq!(page, vec![1, 2]);
+    let (page, cursor) = suite
+        .query_proposals_updated_after(created - 1, cursor, Some(2))
+        .unwrap();
+    assert_eq!(page, vec![3]);
+    assert!(cursor.is_none());
+
+    suite.app().advance_blocks(1);
+    suite.vote("tester0", 1, Vote::Yes).unwrap();
+    suite.app().advance_blocks(1);
+    suite.deposit("tester0", 2, Some(10)).unwrap();
+
+    // oldest change first, untouched proposals are left out
+    assert_eq!(
+        suite
+            .query_proposals_updated_after(created, None, None)
+            .unwrap()
+            .0,
+        vec![1, 2]
+    );
+    assert_eq!(
+        suite
+            .query_proposals_updated_after(created + 1, None, None)
+            .unwrap()
+            .0,
+        vec![2]
+    );
+    assert_eq!(
+        suite
+            .query_proposals_updated_after(created - 1, None, Some(2))
+            .unwrap()
+            .0,
+        vec![3, 1]
+    );
+    assert_eq!(
+        suite.query_proposal(1).unwrap().last_updated_height,
+        created + 1
+    );
+}
+
+#[test]
+fn test_proposal_timeline() {
+    let mut suite = SuiteBuilder::new()
+        .with_funds(vec![("tester0", 300)])
+        .with_staked(vec![("tester0", 100)])
+        .build();
+
+    let submitted = suite.app().block_info();
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(100))
+        .unwrap();
+    suite.vote("tester0", 1, Vote::Yes).unwrap();
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(10))
+        .unwrap();
+    suite
+        .propose("tester0", "t", "l", "d", vec![], Some(100))
+        .unwrap();
+    suite.vote("tester0", 3, Vote::Yes).unwrap();
+
+    let submitted_at: BlockTime = submitted.clone().into();
+    let names = |resp: &ProposalTimelineResponse| {
+        resp.events
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    // still voting
+    let resp = suite.query_proposal_timeline(1).unwrap();
+    assert_eq!(names(&resp), vec!["created", "opened"]);
+    assert_eq!(resp.events[0].1, submitted_at);
+    assert_eq!(resp.events[1].1, submitted_at);
+
+    suite.app().advance_blocks(DEFAULT_VOTING_PERIOD + 5);
+    suite.execute_proposal("tester0", 1).unwrap();
+    let executed_at: BlockTime = suite.app().block_info().into();
+
+    let resp = suite.query_proposal_timeline(1).unwrap();
+    assert_eq!(
+        names(&resp),
+        vec!["created", "opened", "passed", "executed"]
+    );
+    let (_, passed_at) = &resp.events[2];
+    assert_eq!(passed_at.height, submitted.height + DEFAULT_VOTING_PERIOD);
+    // blocks are evenly spaced in the test app, so the interpolated time is exact
+    let block_time = (executed_at.time.seconds() - submitted.time.seconds())
+        / (executed_at.height - submitted.height);
+    assert_eq!(
+        passed_at.time,
+        submitted
+            .time
+            .plus_seconds(block_time * DEFAULT_VOTING_PERIOD)
+    );
+    assert_eq!(resp.events[3].1, executed_at);
+    assert!(resp
+        .events
+        .windows(2)
+        .all(|pair| pair[0].1.height <= pair[1].1.height));
+
+    // underfunded proposal never opened
+    let resp = suite.query_proposal_timeline(2).unwrap();
+    assert_eq!(names(&resp), vec!["created", "rejected"]);
+    assert_eq!(
+        resp.events[1].1.height,
+        submitted.height + DEFAULT_DEPOSIT_PERIOD
+    );
+
+    // passed but never executed, then force-closed
+    suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+    let dao = suite.dao.clone();
+    suite.force_close(dao.as_str(), 3).unwrap();
+    let closed_at: BlockTime = suite.app().block_info().into();
+
+    let resp = suite.query_proposal_timeline(3).unwrap();
+    assert_eq!(
+        names(&resp),
+        vec!["created", "opened", "passed", "rejected"]
+    );
+    assert_eq!(resp.events[3].1, closed_at);
+    assert_eq!(
+        suite
+            .query_proposal_status_at(3, closed_at.height - 1)
+            .unwrap(),
+        Status::Passed
+    );
+    assert_eq!(
+        suite.query_proposal_status_at(3, closed_at.height).unwrap(),
+        Status::Rejected
+    );
+}
+
+#[test]
+fn test_list_multisigs() {
+    let mut suite = SuiteBuilder::new().build();
+    let code_id = suite.app().store_code(contract_multisig());
+    let dao = suite.dao.clone();
+
+    assert!(suite
+        .query_multisigs(None, None)
+        .unwrap()
+        .multisigs
+        .is_empty());
+
+    // only the DAO itself can deploy
+    let err = suite
+        .instantiate_multisig("tester0", code_id, "ops", vec![("tester0", 1)], 1)
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    let err = suite
+        .instantiate_multisig(dao.as_str(), code_id, "ops", vec![("tester0", 1)], 2)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnreachableThreshold {},
+        err.downcast().unwrap()
+    );
+
+    suite
+        .instantiate_multisig(
+            dao.as_str(),
+            code_id,
+            "ops",
+            vec![("tester0", 1), ("tester1", 1), ("tester2", 1)],
+            2,
+        )
+        .unwrap();
+    suite
+        .instantiate_multisig(dao.as_str(), code_id, "grants", vec![("tester0", 1)], 1)
+        .unwrap();
+
+    let err = suite
+        .instantiate_multisig(dao.as_str(), code_id, "ops", vec![("tester0", 1)], 1)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MultisigLabelTaken {
+            label: "ops".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    let multisigs = suite.query_multisigs(None, None).unwrap().multisigs;
+    assert_eq!(
+        multisigs
+            .iter()
+            .map(|m| m.label.as_str())
+            .collect::<Vec<_>>(),
+        vec!["grants", "ops"]
+    );
+    let page = suite.query_multisigs(Some("grants"), Some(1)).unwrap();
+    assert_eq!(page.multisigs, multisigs[1..].to_vec());
+
+    let ops = &multisigs[1].address;
+    assert_eq!(
+        suite.app().contract_data(ops).unwrap().admin,
+        Some(dao.clone())
+    );
+    let init_msg: Cw3FixedMultisigInstantiateMsg =
+        suite.app().wrap().query_wasm_smart(ops, &Empty {}).unwrap();
+    assert_eq!(init_msg.voters.len(), 3);
+    assert_eq!(
+        init_msg.threshold,
+        cw_utils::Threshold::AbsoluteCount { weight: 2 }
+    );
+}
+
+#[test]
+fn test_sub_daos() {
+    let mut suite = SuiteBuilder::new()
+        .with_funds(vec![("tester0", 200)])
+        .with_staked(vec![("tester0", 100)])
+        .build();
+    let dao = suite.dao.clone();
+    let code_id = suite.app().contract_data(&dao).unwrap().code_id as u64;
+
+    assert!(suite
+        .query_sub_daos(None, None)
+        .unwrap()
+        .sub_daos
+        .is_empty());
+
+    // only the DAO itself can deploy
+    let err = suite
+        .app()
+        .execute_contract(
+            Addr::unchecked("tester0"),
+            dao.clone(),
+            &ExecuteMsg::InstantiateSubDao {
+                code_id,
+                label: "grants".to_string(),
+                msg: to_binary(&Empty {}).unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    for label in ["grants", "ops"] {
+        suite
+            .propose_sub_dao("tester0", code_id, label, Some(100))
+            .unwrap();
+    }
+    for id in [1, 2] {
+        suite.vote("tester0", id, Vote::Yes).unwrap();
+    }
+    suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+    suite.execute_proposal("tester0", 1).unwrap();
+    suite.execute_proposal("tester0", 2).unwrap();
+
+    let sub_daos = suite.query_sub_daos(None, None).unwrap().sub_daos;
+    assert_eq!(sub_daos.len(), 2);
+    for sub_dao in &sub_daos {
+        assert_eq!(
+            suite.app().contract_data(sub_dao).unwrap().admin,
+            Some(dao.clone())
+        );
+        let config: ConfigResponse = suite
+            .app()
+            .wrap()
+            .query_wasm_smart(sub_dao, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.gov_token, suite.denom);
+    }
+
+    let page = suite
+        .query_sub_daos(Some(sub_daos[0].to_string()), Some(1))
+        .unwrap()
+        .sub_daos;
+    assert_eq!(page, vec![sub_daos[1].clone()]);
+}
+
+#[test]
+fn test_version() {
+    let suite = SuiteBuilder::new().build();
+
+    let res = suite.query_version().unwrap();
+    assert_eq!(res.dao.contract, "crates.io:ion-dao");
+    assert_eq!(res.dao.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(res.staking.contract, "crates.io:ion-stake");
+    assert_eq!(res.staking.version, "0.0.1");
+}
+
+#[test]
+fn test_simulate_osmosis_execution() {
+    let swap = |input| {
+        CosmosMsg::from(OsmosisMsg::simple_swap(
+            1,
+            "foo",
+            "bar",
+            SwapAmountWithLimit::ExactIn {
+                input: Uint128::new(input),
+                min_output: Uint128::zero(),
+            },
+        ))
+    };
+    let send = CosmosMsg::from(BankMsg::Send {
+        to_address: "tester0".to_string(),
+        amount: coins(1, "denom"),
+    });
+    let mut suite = SuiteBuilder::new()
+        .with_staked(vec![("tester0", 1)])
+        .add_proposal("title", "link", "desc", vec![swap(1000), send])
+        .add_proposal("title", "link", "desc", vec![swap(1000)])
+        .build();
+
+    // the pool doesn't exist yet
+    suite.query_simulate_execution(2).unwrap_err();
+
+    suite.app().init_modules(|router, _, storage| {
+        router
+            .custom
+            .set_pool(
+                storage,
+                1,
+                &Pool::new(coin(100_000, "foo"), coin(100_000, "bar")),
+            )
+            .unwrap()
+    });
+
+    let res = suite.query_simulate_execution(1).unwrap();
+    assert_eq!(res.estimated_gas, 400_000);
+    assert_eq!(res.messages.len(), 2);
+    assert!(matches!(
+        res.messages[0].swap_estimate,
+        Some(SwapAmount::Out(out)) if !out.is_zero() && out < Uint128::new(1000)
+    ));
+    assert_eq!(res.messages[1].swap_estimate, None);
+    assert_eq!(res.messages[1].estimated_gas, 200_000);
+}
+
+#[test]
+fn test_simulate_execution() {
+    let send = CosmosMsg::from(BankMsg::Send {
+        to_address: "tester0".to_string(),
+        amount: coins(1000, "denom"),
+    });
+    let mut suite = SuiteBuilder::new()
+        .with_funds(vec![("tester0", 1000)])
+        .with_staked(vec![("tester0", 100)])
+        .add_proposal("title", "link", "desc", vec![send])
+        .build();
+
+    let res = suite.query_simulate_dao_execution(1).unwrap();
+    assert!(!res.will_succeed);
+    assert_eq!(
+        res.blocking_reasons[..2],
+        [
+            ContractError::NotExpired {}.to_string(),
+            ContractError::InvalidProposalStatus {
+                current: "Open".to_string(),
+                desired: "Passed".to_string(),
+            }
+            .to_string(),
+        ]
+    );
+
+    suite.vote("tester0", 1, Vote::Yes).unwrap();
+    suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+
+    // passed, but the treasury can't cover the spend
+    let res = suite.query_simulate_dao_execution(1).unwrap();
+    assert!(!res.will_succeed);
+    assert_eq!(res.blocking_reasons.len(), 1);
+    assert!(res.blocking_reasons[0].starts_with("Insufficient treasury balance: 1000denom"));
+
+    let dao = suite.dao.clone();
+    suite
+        .app()
+        .send_tokens(Addr::unchecked("tester0"), dao, &coins(1000, "denom"))
+        .unwrap();
+    let res = suite.query_simulate_dao_execution(1).unwrap();
+    assert!(res.will_succeed);
+    assert!(res.blocking_reasons.is_empty());
+    suite.execute_proposal("owner", 1).unwrap();
+}
+
+#[test]
+fn test_config_history() {
+    let mut suite = SuiteBuilder::new().build();
+    let dao = suite.dao.clone();
+
+    assert!(suite
+        .query_config_history(None, None)
+        .unwrap()
+        .entries
+        .is_empty());
+
+    let initial = suite.query_config().unwrap().config;
+    let mut updated = initial.clone();
+    updated.name = "renamed".to_string();
+    suite.update_config(dao.as_str(), updated.clone()).unwrap();
+    let changed_at: crate::state::BlockTime = suite.app().block_info().into();
+
+    suite.app().advance_blocks(1);
+    let mut latest = updated.clone();
+    latest.auto_reap = true;
+    suite.update_config(dao.as_str(), latest).unwrap();
+
+    let entries = suite.query_config_history(None, None).unwrap().entries;
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].version, 1);
+    assert_eq!(entries[0].config, initial);
+    assert_eq!(entries[0].changed_at, changed_at);
+    assert_eq!(entries[1].version, 2);
+    assert_eq!(entries[1].config, updated);
+
+    let entries = suite.query_config_history(Some(1), None).unwrap().entries;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].version, 2);
+}
+
+#[test]
+fn test_config_history_is_capped() {
+    let mut suite = SuiteBuilder::new().build();
+    let dao = suite.dao.clone();
+    let config = suite.query_config().unwrap().config;
+
+    for _ in 0..CONFIG_HISTORY_LIMIT + 2 {
+        suite.update_config(dao.as_str(), config.clone()).unwrap();
+    }
+
+    let mut versions = vec![];
+    let mut start_after = None;
+    loop {
+        let entries = suite
+            .query_config_history(start_after, Some(30))
+            .unwrap()
+            .entries;
+        match entries.last() {
+            Some(last) => start_after = Some(last.version),
+            None => break,
+        }
+        versions.extend(entries.into_iter().map(|entry| entry.version));
+    }
+
+    // the two oldest entries were dropped
+    assert_eq!(versions, (3..=CONFIG_HISTORY_LIMIT + 2).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_update_deposit_params() {
+    let mut suite = SuiteBuilder::new().build();
+    let dao = suite.dao.clone();
+    let initial = suite.query_config().unwrap().config;
+
+    let err = suite
+        .update_deposit_params("owner", Some(50), None)
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    // only the deposit changes
+    suite
+        .update_deposit_params(dao.as_str(), Some(50), None)
+        .unwrap();
+    let config = suite.query_config().unwrap().config;
+    assert_eq!(
+        config,
+        Config {
+            proposal_deposit: Uint128::new(50),
+            ..initial.clone()
+        }
+    );
+    assert_eq!(
+        suite.query_config_history(None, None).unwrap().entries[0].config,
+        initial
+    );
+
+    // the minimum can't go above the deposit
+    let err = suite
+        .update_deposit_params(dao.as_str(), None, Some(51))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidDepositParams {},
+        err.downcast().unwrap()
+    );
+    suite
+        .update_deposit_params(dao.as_str(), Some(80), Some(80))
+        .unwrap();
+    let config = suite.query_config().unwrap().config;
+    assert_eq!(config.proposal_deposit, Uint128::new(80));
+    assert_eq!(config.proposal_min_deposit, Uint128::new(80));
+    assert_eq!(config.name, initial.name);
+}
@@ -1,9 +1,9 @@
 use std::borrow::{Borrow, BorrowMut};
 
 use anyhow::Result as AnyResult;
-use cosmwasm_std::{coins, Addr, CosmosMsg, Decimal, StdResult, Uint128};
+use cosmwasm_std::{coins, Addr, Coin, CosmosMsg, Decimal, GovMsg, StdResult, Uint128};
 use cw20::Denom;
-use cw3::Vote;
+use cw3::{Status, Vote};
 use cw_multi_test::{AppResponse, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
 use cw_utils::{Duration, Expiration};
 use osmo_bindings::{OsmosisMsg, OsmosisQuery};
@@ -32,6 +32,78 @@ pub fn contract_stake() -> Box<dyn Contract<OsmosisMsg, OsmosisQuery>> {
         ion_stake::contract::execute,
         ion_stake::contract::instantiate,
         ion_stake::contract::query,
+    )
+    .with_migrate(ion_stake::contract::migrate);
+    Box::new(contract)
+}
+
+pub fn contract_cw20() -> Box<dyn Contract<OsmosisMsg, OsmosisQuery>> {
+    let contract = ContractWrapper::new_with_empty(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+/// Stand-in for cw3-fixed-multisig that records its instantiate msg
+pub fn contract_multisig() -> Box<dyn Contract<OsmosisMsg, OsmosisQuery>> {
+    use cosmwasm_std::{to_binary, Binary, Empty, Env, MessageInfo};
+    use cw_storage_plus::Item;
+
+    use crate::msg::Cw3FixedMultisigInstantiateMsg;
+    use crate::{Deps, DepsMut, Response};
+
+    const INIT_MSG: Item<Cw3FixedMultisigInstantiateMsg> = Item::new("init_msg");
+
+    let contract = ContractWrapper::new(
+        |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |deps: DepsMut,
+         _: Env,
+         _: MessageInfo,
+         msg: Cw3FixedMultisigInstantiateMsg|
+         -> StdResult<Response> {
+            INIT_MSG.save(deps.storage, &msg)?;
+            Ok(Response::new())
+        },
+        |deps: Deps, _: Env, _: Empty| -> StdResult<Binary> {
+            to_binary(&INIT_MSG.load(deps.storage)?)
+        },
+    );
+    Box::new(contract)
+}
+
+/// Records every `NotifierMsg` it gets, or rejects them all when instantiated with `true`
+pub fn contract_notifier() -> Box<dyn Contract<OsmosisMsg, OsmosisQuery>> {
+    use cosmwasm_std::{to_binary, Binary, Empty, Env, MessageInfo, StdError};
+    use cw_storage_plus::Item;
+
+    use crate::msg::NotifierMsg;
+    use crate::{Deps, DepsMut, Response};
+
+    const FAIL: Item<bool> = Item::new("fail");
+    const EXPIRED: Item<Vec<u64>> = Item::new("expired");
+
+    let contract = ContractWrapper::new(
+        |deps: DepsMut, _: Env, _: MessageInfo, msg: NotifierMsg| -> StdResult<Response> {
+            if FAIL.load(deps.storage)? {
+                return Err(StdError::generic_err("notifier failed"));
+            }
+            let NotifierMsg::ProposalExpired { id } = msg;
+            let mut expired = EXPIRED.may_load(deps.storage)?.unwrap_or_default();
+            expired.push(id);
+            EXPIRED.save(deps.storage, &expired)?;
+            Ok(Response::new())
+        },
+        |deps: DepsMut, _: Env, _: MessageInfo, fail: bool| -> StdResult<Response> {
+            FAIL.save(deps.storage, &fail)?;
+            Ok(Response::new())
+        },
+        |deps: Deps, _: Env, _: Empty| -> StdResult<Binary> {
+            to_binary(&EXPIRED.may_load(deps.storage)?.unwrap_or_default())
+        },
     );
     Box::new(contract)
 }
@@ -46,8 +118,9 @@ pub struct SuiteBuilder {
 
     gov_token: crate::msg::GovToken,
     threshold: crate::threshold::Threshold,
-    periods: (Duration, Duration), // voting, deposit
-    deposits: (Uint128, Uint128),  // min, quo
+    periods: (Duration, Duration),    // voting, deposit
+    deposits: (Uint128, Uint128),     // min, quo
+    query_limits: Option<(u32, u32)>, // max, default
 }
 
 impl SuiteBuilder {
@@ -69,6 +142,8 @@ impl SuiteBuilder {
                 threshold: Decimal::percent(50),      // 50%
                 quorum: Decimal::percent(33),         // 33%
                 veto_threshold: Decimal::percent(33), // 33%
+                min_voters: None,
+                strict: false,
             },
             periods: (
                 Duration::Height(DEFAULT_VOTING_PERIOD),
@@ -78,6 +153,7 @@ impl SuiteBuilder {
                 Uint128::new(DEFAULT_MIN_DEPOSIT),
                 Uint128::new(DEFAULT_QUO_DEPOSIT),
             ),
+            query_limits: None,
         }
     }
 
@@ -93,12 +169,15 @@ impl SuiteBuilder {
             link: link.to_string(),
             description: desc.to_string(),
             msgs,
+            cosmos_gov_vote: None,
+            parent_proposal: None,
+            category: None,
         });
         self
     }
 
     pub fn with_funds(mut self, funds: Vec<(impl ToString, u128)>) -> Self {
-        self.funds = vec![
+        self.funds = [
             self.funds,
             funds
                 .iter()
@@ -110,7 +189,7 @@ impl SuiteBuilder {
     }
 
     pub fn with_staked(mut self, staked: Vec<(impl ToString, u128)>) -> Self {
-        self.staked = vec![
+        self.staked = [
             self.staked,
             staked
                 .iter()
@@ -126,6 +205,11 @@ impl SuiteBuilder {
         self
     }
 
+    pub fn with_query_limits(mut self, max: u32, default: u32) -> Self {
+        self.query_limits = Some((max, default));
+        self
+    }
+
     pub fn with_threshold(mut self, threshold: crate::threshold::Threshold) -> Self {
         self.threshold = threshold;
         self
@@ -203,6 +287,33 @@ impl SuiteBuilder {
                     deposit_period: self.periods.1,
                     proposal_deposit_amount: self.deposits.1,
                     proposal_deposit_min_amount: self.deposits.0,
+                    high_participation_bonus: None,
+                    auto_reap: false,
+                    require_valid_link: false,
+                    allow_deposit_top_up: false,
+                    force_close_grace_period: None,
+                    max_query_limit: self.query_limits.map(|(max, _)| max),
+                    default_query_limit: self.query_limits.map(|(_, default)| default),
+                    execute_permission: Default::default(),
+                    execution_delay: None,
+                    max_single_deposit: None,
+                    min_distinct_depositors: None,
+                    auto_scale_threshold: false,
+                    total_token_supply: Uint128::zero(),
+                    threshold_scaling_coefficient: Decimal::zero(),
+                    bootstrap: None,
+                    notifiers: vec![],
+                    gov_token_decimals: None,
+                    event_namespace: None,
+                    field_thresholds: vec![],
+                    auto_register_cw20: false,
+                    execution_window: None,
+                    recheck_quorum_on_execute: false,
+                    voting_stake_age: 0,
+                    reserves: vec![],
+                    confiscation_policy: Default::default(),
+                    record_tally_history: false,
+                    max_treasury_tokens: crate::DEFAULT_MAX_TREASURY_TOKENS,
                 },
                 &[],
                 "dao",
@@ -380,6 +491,178 @@ impl Suite {
                 link: link.to_string(),
                 description: desc.to_string(),
                 msgs,
+                cosmos_gov_vote: None,
+                parent_proposal: None,
+                category: None,
+            }),
+            funds.as_slice(),
+        )
+    }
+
+    /// Proposes a sub-DAO staking the same token in a staking contract of its own
+    pub fn propose_sub_dao(
+        &mut self,
+        proposer: impl ToString,
+        code_id: u64,
+        label: impl ToString,
+        deposit: Option<u128>,
+    ) -> AnyResult<AppResponse> {
+        let funds = deposit
+            .map(|amount| coins(amount, &self.denom))
+            .unwrap_or_default();
+
+        let stake_code_id = self.app.contract_data(&self.stake)?.code_id as u64;
+        let instantiate_msg = crate::msg::InstantiateMsg {
+            name: label.to_string(),
+            description: "sub-DAO".to_string(),
+            gov_token: crate::msg::GovToken::Create {
+                denom: self.denom.clone(),
+                label: format!("{} stake", label.to_string()),
+                stake_contract_code_id: stake_code_id,
+                unstaking_duration: None,
+            },
+            threshold: crate::threshold::Threshold::default(),
+            voting_period: Duration::Height(DEFAULT_VOTING_PERIOD),
+            deposit_period: Duration::Height(DEFAULT_DEPOSIT_PERIOD),
+            proposal_deposit_amount: Uint128::new(100),
+            proposal_deposit_min_amount: Uint128::zero(),
+            high_participation_bonus: None,
+            auto_reap: false,
+            require_valid_link: false,
+            allow_deposit_top_up: false,
+            force_close_grace_period: None,
+            max_query_limit: None,
+            default_query_limit: None,
+            execute_permission: Default::default(),
+            execution_delay: None,
+            max_single_deposit: None,
+            min_distinct_depositors: None,
+            auto_scale_threshold: false,
+            total_token_supply: Uint128::zero(),
+            threshold_scaling_coefficient: Decimal::zero(),
+            bootstrap: None,
+            notifiers: vec![],
+            gov_token_decimals: None,
+            event_namespace: None,
+            field_thresholds: vec![],
+            auto_register_cw20: false,
+            execution_window: None,
+            recheck_quorum_on_execute: false,
+            voting_stake_age: 0,
+            reserves: vec![],
+            confiscation_policy: Default::default(),
+            record_tally_history: false,
+            max_treasury_tokens: crate::DEFAULT_MAX_TREASURY_TOKENS,
+        };
+
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(proposer.to_string()),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::ProposeSubDao(Box::new(crate::msg::ProposeSubDaoMsg {
+                title: "sub-DAO".to_string(),
+                link: "link".to_string(),
+                description: "desc".to_string(),
+                category: None,
+                code_id,
+                label: label.to_string(),
+                instantiate_msg,
+            })),
+            funds.as_slice(),
+        )
+    }
+
+    pub fn propose_msg(
+        &mut self,
+        proposer: impl ToString,
+        propose_msg: crate::msg::ProposeMsg,
+        deposit: Option<u128>,
+    ) -> AnyResult<AppResponse> {
+        let funds = deposit
+            .map(|amount| coins(amount, &self.denom))
+            .unwrap_or_default();
+
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(proposer.to_string()),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::Propose(propose_msg),
+            funds.as_slice(),
+        )
+    }
+
+    pub fn propose_amendment(
+        &mut self,
+        proposer: impl ToString,
+        parent_proposal: u64,
+        deposit: Option<u128>,
+    ) -> AnyResult<AppResponse> {
+        let funds = deposit
+            .map(|amount| coins(amount, &self.denom))
+            .unwrap_or_default();
+
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(proposer.to_string()),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::Propose(crate::msg::ProposeMsg {
+                title: "title".to_string(),
+                link: "link".to_string(),
+                description: "desc".to_string(),
+                msgs: vec![],
+                cosmos_gov_vote: None,
+                parent_proposal: Some(parent_proposal),
+                category: None,
+            }),
+            funds.as_slice(),
+        )
+    }
+
+    pub fn propose_in_category(
+        &mut self,
+        proposer: impl ToString,
+        category: &str,
+        deposit: Option<u128>,
+    ) -> AnyResult<AppResponse> {
+        let funds = deposit
+            .map(|amount| coins(amount, &self.denom))
+            .unwrap_or_default();
+
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(proposer.to_string()),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::Propose(crate::msg::ProposeMsg {
+                title: "title".to_string(),
+                link: "link".to_string(),
+                description: "desc".to_string(),
+                msgs: vec![],
+                cosmos_gov_vote: None,
+                parent_proposal: None,
+                category: Some(category.to_string()),
+            }),
+            funds.as_slice(),
+        )
+    }
+
+    pub fn propose_gov_vote(
+        &mut self,
+        proposer: impl ToString,
+        msgs: Vec<CosmosMsg<OsmosisMsg>>,
+        gov_vote: GovMsg,
+        deposit: Option<u128>,
+    ) -> AnyResult<AppResponse> {
+        let funds = deposit
+            .map(|amount| coins(amount, &self.denom))
+            .unwrap_or_default();
+
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(proposer.to_string()),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::Propose(crate::msg::ProposeMsg {
+                title: "title".to_string(),
+                link: "link".to_string(),
+                description: "desc".to_string(),
+                msgs,
+                cosmos_gov_vote: Some(gov_vote),
+                parent_proposal: None,
+                category: None,
             }),
             funds.as_slice(),
         )
@@ -403,6 +686,28 @@ impl Suite {
         )
     }
 
+    pub fn cosponsor(&mut self, sender: &str, proposal_id: u64) -> AnyResult<AppResponse> {
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(sender),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::Cosponsor { proposal_id },
+            &[],
+        )
+    }
+
+    pub fn withdraw_deposit(
+        &mut self,
+        depositor: &str,
+        proposal_id: u64,
+    ) -> AnyResult<AppResponse> {
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(depositor),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::WithdrawDeposit { proposal_id },
+            &[],
+        )
+    }
+
     pub fn claim_deposit(&mut self, claimer: &str, proposal_id: u64) -> AnyResult<AppResponse> {
         self.app.borrow_mut().execute_contract(
             Addr::unchecked(claimer),
@@ -413,12 +718,23 @@ impl Suite {
     }
 
     pub fn vote(&mut self, voter: &str, proposal_id: u64, option: Vote) -> AnyResult<AppResponse> {
+        self.vote_with_rationale(voter, proposal_id, option, None)
+    }
+
+    pub fn vote_with_rationale(
+        &mut self,
+        voter: &str,
+        proposal_id: u64,
+        option: Vote,
+        rationale: Option<&str>,
+    ) -> AnyResult<AppResponse> {
         self.app.borrow_mut().execute_contract(
             Addr::unchecked(voter),
             self.dao.clone(),
             &crate::msg::ExecuteMsg::Vote(crate::msg::VoteMsg {
                 proposal_id,
                 vote: option,
+                rationale: rationale.map(String::from),
             }),
             &[],
         )
@@ -442,6 +758,71 @@ impl Suite {
         )
     }
 
+    pub fn refresh_status(
+        &mut self,
+        sender: &str,
+        proposal_ids: Vec<u64>,
+    ) -> AnyResult<AppResponse> {
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(sender),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::RefreshStatus { proposal_ids },
+            &[],
+        )
+    }
+
+    pub fn force_close(&mut self, closer: &str, proposal_id: u64) -> AnyResult<AppResponse> {
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(closer),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::ForceClose { proposal_id },
+            &[],
+        )
+    }
+
+    pub fn veto_execution(&mut self, sender: &str, proposal_id: u64) -> AnyResult<AppResponse> {
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(sender),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::VetoExecution { proposal_id },
+            &[],
+        )
+    }
+
+    pub fn expire_execution(&mut self, sender: &str, proposal_id: u64) -> AnyResult<AppResponse> {
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(sender),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::ExpireExecution { proposal_id },
+            &[],
+        )
+    }
+
+    pub fn instantiate_multisig(
+        &mut self,
+        sender: &str,
+        code_id: u64,
+        label: &str,
+        members: Vec<(&str, u64)>,
+        required_weight: u64,
+    ) -> AnyResult<AppResponse> {
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(sender),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::InstantiateMultisig {
+                code_id,
+                label: label.to_string(),
+                members: members
+                    .into_iter()
+                    .map(|(addr, weight)| (Addr::unchecked(addr), weight))
+                    .collect(),
+                required_weight,
+                max_voting_period: Duration::Height(100),
+            },
+            &[],
+        )
+    }
+
     pub fn pause(&mut self, pauser: &str, expiration: Expiration) -> AnyResult<AppResponse> {
         self.app.borrow_mut().execute_contract(
             Addr::unchecked(pauser),
@@ -455,7 +836,24 @@ impl Suite {
         self.app.borrow_mut().execute_contract(
             Addr::unchecked(updater),
             self.dao.clone(),
-            &crate::msg::ExecuteMsg::UpdateConfig(config),
+            &crate::msg::ExecuteMsg::UpdateConfig(Box::new(config)),
+            &[],
+        )
+    }
+
+    pub fn update_deposit_params(
+        &mut self,
+        updater: &str,
+        proposal_deposit: Option<u128>,
+        proposal_min_deposit: Option<u128>,
+    ) -> AnyResult<AppResponse> {
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(updater),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::UpdateDepositParams {
+                proposal_deposit: proposal_deposit.map(Uint128::new),
+                proposal_min_deposit: proposal_min_deposit.map(Uint128::new),
+            },
             &[],
         )
     }
@@ -489,6 +887,23 @@ impl Suite {
         )
     }
 
+    pub fn update_categories(
+        &mut self,
+        updater: &str,
+        to_add: Vec<&str>,
+        to_remove: Vec<&str>,
+    ) -> AnyResult<AppResponse> {
+        self.app.borrow_mut().execute_contract(
+            Addr::unchecked(updater),
+            self.dao.clone(),
+            &crate::msg::ExecuteMsg::UpdateCategories {
+                to_add: to_add.into_iter().map(String::from).collect(),
+                to_remove: to_remove.into_iter().map(String::from).collect(),
+            },
+            &[],
+        )
+    }
+
     /***
      * DAO CONTRACT QUERIES
      */
@@ -533,6 +948,16 @@ impl Suite {
             .query_wasm_smart(&self.dao, &crate::msg::QueryMsg::Proposal { proposal_id })
     }
 
+    pub fn query_proposal_maybe(
+        &self,
+        proposal_id: u64,
+    ) -> StdResult<Option<crate::msg::ProposalResponse<OsmosisMsg>>> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::ProposalMaybe { proposal_id },
+        )
+    }
+
     pub fn query_proposals(
         &self,
         query: crate::msg::ProposalsQueryOption,
@@ -551,6 +976,250 @@ impl Suite {
         )
     }
 
+    pub fn query_deposit_shortfall(
+        &self,
+        proposal_id: u64,
+    ) -> StdResult<crate::msg::DepositShortfallResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::DepositShortfall { proposal_id },
+        )
+    }
+
+    pub fn query_self_stake(&self) -> StdResult<ion_stake::msg::StakedBalanceAtHeightResponse> {
+        self.app
+            .borrow()
+            .wrap()
+            .query_wasm_smart(&self.dao, &crate::msg::QueryMsg::SelfStake {})
+    }
+
+    pub fn query_participation(
+        &self,
+        proposal_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<crate::msg::ProposalParticipationResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::ProposalParticipationByHeight {
+                proposal_id,
+                start_after,
+                limit,
+            },
+        )
+    }
+
+    pub fn query_audit(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<crate::msg::AuditResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::Audit { start_after, limit },
+        )
+    }
+
+    pub fn query_stake_total_value(&self) -> StdResult<Uint128> {
+        let resp: ion_stake::msg::TotalValueResponse = self
+            .app
+            .borrow()
+            .wrap()
+            .query_wasm_smart(&self.stake, &ion_stake::msg::QueryMsg::TotalValue {})?;
+        Ok(resp.total)
+    }
+
+    pub fn query_proposal_status_at(&self, proposal_id: u64, height: u64) -> StdResult<Status> {
+        let resp: crate::msg::ProposalStatusAtResponse =
+            self.app.borrow().wrap().query_wasm_smart(
+                &self.dao,
+                &crate::msg::QueryMsg::ProposalStatusAt {
+                    proposal_id,
+                    height,
+                },
+            )?;
+        Ok(resp.status)
+    }
+
+    pub fn query_all_proposals_status_map(
+        &self,
+        start: Option<u64>,
+        limit: Option<u32>,
+        order: Option<RangeOrder>,
+    ) -> StdResult<Vec<(u64, Status)>> {
+        let resp: crate::msg::ProposalStatusMapResponse =
+            self.app.borrow().wrap().query_wasm_smart(
+                &self.dao,
+                &crate::msg::QueryMsg::AllProposalsStatusMap {
+                    start,
+                    limit,
+                    order,
+                },
+            )?;
+        Ok(resp.statuses)
+    }
+
+    pub fn query_index_drift(
+        &self,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<crate::msg::IndexDriftEntry>> {
+        let resp: crate::msg::IndexDriftResponse = self
+            .app
+            .borrow()
+            .wrap()
+            .query_wasm_smart(&self.dao, &crate::msg::QueryMsg::IndexDrift { limit })?;
+        Ok(resp.proposals)
+    }
+
+    pub fn query_proposals_updated_after(
+        &self,
+        height: u64,
+        cursor: Option<crate::msg::ProposalCursor>,
+        limit: Option<u32>,
+    ) -> StdResult<(Vec<u64>, Option<crate::msg::ProposalCursor>)> {
+        let resp: crate::msg::ProposalsResponse<OsmosisMsg> =
+            self.app.borrow().wrap().query_wasm_smart(
+                &self.dao,
+                &crate::msg::QueryMsg::ProposalsUpdatedAfter {
+                    height,
+                    cursor,
+                    limit,
+                },
+            )?;
+        Ok((
+            resp.proposals.into_iter().map(|p| p.id).collect(),
+            resp.next_cursor,
+        ))
+    }
+
+    pub fn query_proposal_timeline(
+        &self,
+        proposal_id: u64,
+    ) -> StdResult<crate::msg::ProposalTimelineResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::ProposalTimeline { proposal_id },
+        )
+    }
+
+    pub fn query_voting_powers(
+        &self,
+        proposal_id: u64,
+        addresses: Vec<&str>,
+    ) -> StdResult<ion_stake::msg::StakedBalancesAtHeightResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::VotingPowers {
+                proposal_id,
+                addresses: addresses.into_iter().map(|a| a.to_string()).collect(),
+            },
+        )
+    }
+
+    pub fn query_multisigs(
+        &self,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<crate::msg::ListMultisigsResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::ListMultisigs {
+                start_after: start_after.map(str::to_string),
+                limit,
+            },
+        )
+    }
+
+    pub fn query_cosponsors(
+        &self,
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Addr>> {
+        let resp: crate::msg::CosponsorsResponse = self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::Cosponsors {
+                proposal_id,
+                start_after,
+                limit,
+            },
+        )?;
+        Ok(resp.cosponsors)
+    }
+
+    pub fn query_sub_daos(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<crate::msg::SubDaosResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::SubDaos { start_after, limit },
+        )
+    }
+
+    pub fn query_config_history(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<crate::msg::ConfigHistoryResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::ConfigHistory { start_after, limit },
+        )
+    }
+
+    pub fn query_version(&self) -> StdResult<crate::msg::VersionResponse> {
+        self.app
+            .borrow()
+            .wrap()
+            .query_wasm_smart(&self.dao, &crate::msg::QueryMsg::Version {})
+    }
+
+    pub fn query_simulate_execution(
+        &self,
+        proposal_id: u64,
+    ) -> StdResult<crate::msg::SimulateResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::SimulateOsmosisExecution { proposal_id },
+        )
+    }
+
+    pub fn query_simulate_dao_execution(
+        &self,
+        proposal_id: u64,
+    ) -> StdResult<crate::msg::SimulateExecutionResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::SimulateExecution { proposal_id },
+        )
+    }
+
+    pub fn query_dao_stats(&self) -> StdResult<crate::msg::DaoStatsResponse> {
+        self.app
+            .borrow()
+            .wrap()
+            .query_wasm_smart(&self.dao, &crate::msg::QueryMsg::DaoStats {})
+    }
+
+    pub fn query_governance_stats(&self) -> StdResult<crate::msg::GovernanceStatsResponse> {
+        self.app
+            .borrow()
+            .wrap()
+            .query_wasm_smart(&self.dao, &crate::msg::QueryMsg::GovernanceStats {})
+    }
+
+    pub fn query_expiration_status(
+        &self,
+        expiration: Expiration,
+    ) -> StdResult<crate::msg::ExpirationStatusResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::ExpirationStatus { expiration },
+        )
+    }
+
     pub fn query_proposal_count(&self) -> StdResult<u64> {
         self.app
             .borrow()
@@ -586,6 +1255,17 @@ impl Suite {
         )
     }
 
+    pub fn query_votes_breakdown(
+        &self,
+        proposal_id: u64,
+        top: Option<u32>,
+    ) -> StdResult<crate::msg::VotesBreakdownResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::ProposalVotesBreakdown { proposal_id, top },
+        )
+    }
+
     pub fn query_deposit(
         &self,
         proposal_id: u64,
@@ -600,6 +1280,58 @@ impl Suite {
         )
     }
 
+    pub fn query_confiscated_totals(&self) -> StdResult<Vec<Coin>> {
+        let resp: crate::msg::ConfiscatedTotalsResponse = self
+            .app
+            .borrow()
+            .wrap()
+            .query_wasm_smart(&self.dao, &crate::msg::QueryMsg::ConfiscatedTotals {})?;
+        Ok(resp.totals)
+    }
+
+    pub fn query_top_depositors(
+        &self,
+        proposal_id: u64,
+        limit: Option<u32>,
+    ) -> StdResult<crate::msg::TopDepositorsResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::TopDepositors { proposal_id, limit },
+        )
+    }
+
+    pub fn query_proposal_deposit_summary(
+        &self,
+        proposal_id: u64,
+    ) -> StdResult<crate::msg::ProposalDepositSummaryResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::ProposalDepositSummary { proposal_id },
+        )
+    }
+
+    pub fn query_eligibility_height(
+        &self,
+        proposal_id: u64,
+    ) -> StdResult<crate::msg::EligibilityHeightResponse> {
+        self.app.borrow().wrap().query_wasm_smart(
+            &self.dao,
+            &crate::msg::QueryMsg::EligibilityHeight { proposal_id },
+        )
+    }
+
+    pub fn query_claimable_deposits(&self, depositor: &str) -> StdResult<Vec<(u64, Uint128)>> {
+        let resp: crate::msg::ClaimableDepositsResponse =
+            self.app.borrow().wrap().query_wasm_smart(
+                &self.dao,
+                &crate::msg::QueryMsg::ClaimableDeposits {
+                    depositor: depositor.to_string(),
+                },
+            )?;
+
+        Ok(resp.deposits)
+    }
+
     pub fn query_deposits(
         &self,
         query: crate::msg::DepositsQueryOption,
@@ -0,0 +1,101 @@
+use cosmwasm_std::{coins, from_binary, Addr, BankMsg, CosmosMsg, Uint128, WasmMsg};
+use cw3::Vote;
+use cw_multi_test::Executor;
+use cw_utils::{Duration, Expiration};
+
+use crate::msg::{ExecuteMsg, ProposeMsg};
+use crate::templates::{pause_dao, treasury_transfer, update_voting_period, LINK_PLACEHOLDER};
+use crate::tests::suite::{Suite, SuiteBuilder, DEFAULT_VOTING_PERIOD};
+use crate::ContractError;
+
+fn setup() -> Suite {
+    SuiteBuilder::new()
+        .with_funds(vec![("tester0", 1000)])
+        .with_staked(vec![("tester0", 100)])
+        .build()
+}
+
+fn wasm_execute(prop: &ProposeMsg) -> (String, ExecuteMsg) {
+    match prop.msgs.as_slice() {
+        [CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        })] => (contract_addr.clone(), from_binary(msg).unwrap()),
+        msgs => panic!("unexpected msgs: {:?}", msgs),
+    }
+}
+
+fn pass(suite: &mut Suite, prop: ProposeMsg) {
+    suite.propose_msg("tester0", prop, Some(100)).unwrap();
+    let id = suite.query_proposal_count().unwrap();
+    suite.vote("tester0", id, Vote::Yes).unwrap();
+    suite.app().advance_blocks(DEFAULT_VOTING_PERIOD);
+    suite.execute_proposal("tester0", id).unwrap();
+}
+
+#[test]
+fn test_treasury_transfer() {
+    let mut suite = setup();
+    let dao = suite.dao.clone();
+    let denom = suite.denom.clone();
+    suite
+        .app()
+        .send_tokens(Addr::unchecked("tester0"), dao, &coins(500, &denom))
+        .unwrap();
+
+    let prop = treasury_transfer(&Addr::unchecked("recipient"), Uint128::new(500), &denom);
+    assert_eq!(
+        prop.title,
+        format!("Treasury transfer: 500{} to recipient", denom)
+    );
+    assert_eq!(prop.link, LINK_PLACEHOLDER);
+    assert_eq!(
+        prop.msgs,
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: "recipient".to_string(),
+            amount: coins(500, &denom),
+        })]
+    );
+
+    pass(&mut suite, prop);
+    assert!(suite.check_balance("recipient", 500));
+}
+
+#[test]
+fn test_update_voting_period() {
+    let mut suite = setup();
+    let dao = suite.dao.clone();
+    let config = suite.query_config().unwrap().config;
+
+    let prop = update_voting_period(&dao, config.clone(), Duration::Height(30)).unwrap();
+    assert_eq!(prop.title, "Update voting period to height: 30");
+    let (contract, msg) = wasm_execute(&prop);
+    assert_eq!(contract, dao.to_string());
+    let mut expected = config;
+    expected.voting_period = Duration::Height(30);
+    assert_eq!(msg, ExecuteMsg::UpdateConfig(Box::new(expected.clone())));
+
+    pass(&mut suite, prop);
+    assert_eq!(suite.query_config().unwrap().config, expected);
+}
+
+#[test]
+fn test_pause_dao() {
+    let mut suite = setup();
+    let dao = suite.dao.clone();
+    let expiration = Expiration::AtHeight(suite.app().block_info().height + 100);
+
+    let prop = pause_dao(&dao, expiration).unwrap();
+    assert_eq!(prop.title, format!("Pause DAO until {}", expiration));
+    let (contract, msg) = wasm_execute(&prop);
+    assert_eq!(contract, dao.to_string());
+    assert_eq!(msg, ExecuteMsg::PauseDAO { expiration });
+
+    pass(&mut suite, prop);
+    let err = suite
+        .propose("tester0", "title", "link", "desc", vec![], Some(100))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::PausedUntil { expiration },
+        err.downcast().unwrap()
+    );
+}
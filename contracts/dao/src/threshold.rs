@@ -1,4 +1,4 @@
-use cosmwasm_std::Decimal;
+use cosmwasm_std::{Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +12,12 @@ pub struct Threshold {
     pub threshold: Decimal,
     pub quorum: Decimal,
     pub veto_threshold: Decimal,
+    /// Minimum number of distinct voters for the proposal to pass
+    #[serde(default)]
+    pub min_voters: Option<u64>,
+    /// Require strictly more than `threshold` yes votes, exactly `threshold` isn't enough
+    #[serde(default)]
+    pub strict: bool,
 }
 
 impl Default for Threshold {
@@ -20,6 +26,8 @@ impl Default for Threshold {
             threshold: Decimal::from_ratio(1u128, 2u128),      // 50%
             quorum: Decimal::from_ratio(1u128, 3u128),         // 33%
             veto_threshold: Decimal::from_ratio(1u128, 3u128), // 33%
+            min_voters: None,
+            strict: false,
         }
     }
 }
@@ -32,10 +40,20 @@ impl Threshold {
         valid_percentage(&self.quorum)?;
         valid_percentage(&self.veto_threshold)
     }
+
+    /// Raises `threshold` by `coefficient * (1 - staked / total_supply)`, capped at 100%
+    pub fn scaled(&self, staked: Uint128, total_supply: Uint128, coefficient: Decimal) -> Self {
+        let staked_fraction = Decimal::from_ratio(staked, total_supply).min(Decimal::one());
+        let raise = (Decimal::one() - staked_fraction) * coefficient;
+        Self {
+            threshold: (self.threshold + raise).min(Decimal::one()),
+            ..self.clone()
+        }
+    }
 }
 
 /// Asserts that the 0.0 < percent <= 1.0
-fn valid_percentage(percent: &Decimal) -> Result<(), ContractError> {
+pub(crate) fn valid_percentage(percent: &Decimal) -> Result<(), ContractError> {
     if percent.is_zero() {
         Err(ContractError::ZeroThreshold {})
     } else if *percent > Decimal::one() {
@@ -84,6 +102,8 @@ mod tests {
             threshold: Decimal::percent(51),
             quorum: Decimal::percent(40),
             veto_threshold: Decimal::percent(33),
+            min_voters: None,
+            strict: false,
         }
         .validate()
         .unwrap();
@@ -91,6 +111,8 @@ mod tests {
             threshold: Decimal::percent(101),
             quorum: Decimal::percent(40),
             veto_threshold: Decimal::percent(33),
+            min_voters: None,
+            strict: false,
         }
         .validate()
         .unwrap_err();
@@ -102,9 +124,32 @@ mod tests {
             threshold: Decimal::percent(51),
             quorum: Decimal::percent(0),
             veto_threshold: Decimal::percent(10),
+            min_voters: None,
+            strict: false,
         }
         .validate()
         .unwrap_err();
         assert_eq!(err.to_string(), ContractError::ZeroThreshold {}.to_string());
     }
+
+    #[test]
+    fn scaled_threshold() {
+        let threshold = Threshold::default();
+        let supply = Uint128::new(1000);
+
+        // 10% staked: 50% + 90% * 0.5
+        let scaled = threshold.scaled(Uint128::new(100), supply, Decimal::percent(50));
+        assert_eq!(scaled.threshold, Decimal::percent(95));
+        assert_eq!(scaled.quorum, threshold.quorum);
+
+        // fully staked keeps the configured threshold
+        let scaled = threshold.scaled(supply, supply, Decimal::percent(50));
+        assert_eq!(scaled, threshold);
+        let scaled = threshold.scaled(Uint128::new(2000), supply, Decimal::percent(50));
+        assert_eq!(scaled, threshold);
+
+        // never above 100%
+        let scaled = threshold.scaled(Uint128::zero(), supply, Decimal::one());
+        assert_eq!(scaled.threshold, Decimal::one());
+    }
 }
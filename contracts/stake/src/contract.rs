@@ -1,18 +1,41 @@
-use cosmwasm_std::{
-    Addr, BankMsg, Binary, coins, Env, MessageInfo, StdError, StdResult, to_binary, Uint128,
-};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::convert::TryFrom;
+
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    coin, coins, to_binary, Addr, BankMsg, Binary, BlockInfo, Coin, Decimal, DistributionMsg, Env,
+    Fraction, MessageInfo, Order, OverflowError, OverflowOperation, Reply, StdError, StdResult,
+    Storage, Timestamp, Uint128,
+};
 use cw2::set_contract_version;
-use osmo_bindings::{OsmosisMsg, OsmosisQuery};
+use cw_storage_plus::Bound;
+use cw_utils::{Expiration, NativeBalance};
+use osmo_bindings::{OsmosisMsg, OsmosisQuery, SpotPriceResponse};
 
-use crate::ContractError;
 use crate::msg::{
-    ClaimsResponse, Duration, ExecuteMsg, GetConfigResponse, InstantiateMsg, QueryMsg,
-    StakedBalanceAtHeightResponse, StakedValueResponse, TotalStakedAtHeightResponse,
-    TotalValueResponse,
+    AddressStakedBalance, BalanceDriftResponse, BasketClaimsResponse, BasketCompositionResponse,
+    ClaimStatusEntry, ClaimStatusResponse, ClaimsResponse, ConvertToAssetsResponse,
+    ConvertToSharesResponse, Duration, ExecuteMsg, ForfeitableClaimsValueResponse,
+    GetConfigResponse, InstantiateMsg, MigrateMsg, PendingBonusResponse, QueryMsg,
+    SlashRecordEntry, SlashRecordsResponse, StakeResponse, StakedBalanceAtHeightResponse,
+    StakedBalancesAtHeightResponse, StakedValueResponse, StakerInfo, StakerLifetimeResponse,
+    TopStakersResponse, TotalStakedAtHeightResponse, TotalStakedHistoryResponse,
+    TotalSuperfluidRewardsCollectedResponse, TotalValueResponse, UnbondingQueueEntry,
+    UnbondingQueueResponse,
 };
-use crate::state::{BALANCE, CLAIMS, Config, CONFIG, MAX_CLAIMS, STAKED_BALANCES, STAKED_TOTAL};
+use crate::state::{
+    BasketClaim, BasketDenom, BonusRewards, Config, LifetimeTotals, PendingRewards, SlashRecord,
+    WarmupDeposit, BALANCE, BASKET_BALANCES, BASKET_CLAIMS, BONUS_ACCRUALS, BONUS_REWARDS, CLAIMS,
+    CLAIMS_BY_ADDRESS, CLAIMS_TOTAL, COMPOUNDED_BALANCES, COMPOUNDED_TOTAL, CONFIG,
+    EMERGENCY_CLAIM_HEIGHT, LIFETIME_TOTALS, MAX_CLAIMS, MAX_REWARD_MULTIPLIER_PERCENT,
+    MAX_UNSTAKING_DURATION_HEIGHT, MAX_UNSTAKING_DURATION_TIME, MAX_WARMUP_PERIOD, PENDING_REWARDS,
+    PRE_COLLECT_BALANCE, REWARD_RESERVE, REWARD_VESTING_ENDS, SLASH_COUNTS, SLASH_RECORDS,
+    STAKED_BALANCES, STAKED_TOTAL, SUPERFLUID_REWARDS_COLLECTED, WARMING_BALANCES, WARMING_TOTAL,
+    WARMUP_DEPOSITS, WARMUP_ENDS,
+};
+use crate::ContractError;
 
 /// type aliases
 pub type Response = cosmwasm_std::Response<OsmosisMsg>;
@@ -25,6 +48,13 @@ pub type QuerierWrapper<'a> = cosmwasm_std::QuerierWrapper<'a, OsmosisQuery>;
 const CONTRACT_NAME: &str = "crates.io:ion-stake";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_TOP_STAKERS: u32 = 50;
+const MAX_HISTORY_SAMPLES: usize = 100;
+
+pub const COLLECT_SUPERFLUID_REWARDS_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -37,10 +67,39 @@ pub fn instantiate(
         None => None,
     };
 
+    validate_unstaking_duration(msg.unstaking_duration)?;
+
+    for (i, basket) in msg.basket_denoms.iter().enumerate() {
+        if basket.denom == msg.denom {
+            return Err(ContractError::InvalidBasket {
+                reason: format!("{} is already the staking denom", basket.denom),
+            });
+        }
+        if msg.basket_denoms[..i]
+            .iter()
+            .any(|b| b.denom == basket.denom)
+        {
+            return Err(ContractError::InvalidBasket {
+                reason: format!("{} is listed twice", basket.denom),
+            });
+        }
+    }
+
+    if msg.bonus_denom.as_ref() == Some(&msg.denom) {
+        return Err(ContractError::InvalidBonusDenom { denom: msg.denom });
+    }
+
     let config = Config {
         admin,
         denom: msg.denom,
         unstaking_duration: msg.unstaking_duration,
+        reward_multiplier: Decimal::one(),
+        fund_allowlist: None,
+        reward_vesting_blocks: 0,
+        basket_denoms: msg.basket_denoms,
+        min_claim_amount: Uint128::zero(),
+        warmup_period: 0,
+        bonus_denom: msg.bonus_denom,
     };
     CONFIG.save(deps.storage, &config)?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -55,30 +114,91 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    release_pending_rewards(deps.storage, env.block.height)?;
+
     match msg {
         ExecuteMsg::Stake {} => {
-            let denom = CONFIG.load(deps.storage)?.denom;
-            let received = cw_utils::may_pay(&info, denom.as_str()).unwrap();
-            execute_stake(deps, env, &info.sender, received)
+            let config = CONFIG.load(deps.storage)?;
+            if config.basket_denoms.is_empty() {
+                let received = cw_utils::may_pay(&info, config.denom.as_str()).unwrap();
+                execute_stake(deps, env, &info.sender, received)
+            } else {
+                execute_stake_basket(deps, env, &info.sender, info.funds)
+            }
         }
         ExecuteMsg::Fund {} => {
             let denom = CONFIG.load(deps.storage)?.denom;
             let received = cw_utils::may_pay(&info, denom.as_str()).unwrap();
             execute_fund(deps, env, &info.sender, received)
         }
+        ExecuteMsg::FundUnboosted {} => {
+            let denom = CONFIG.load(deps.storage)?.denom;
+            let received = cw_utils::may_pay(&info, denom.as_str())?;
+            execute_fund_unboosted(deps, env, &info.sender, received)
+        }
+        ExecuteMsg::FundRewardReserve {} => {
+            let denom = CONFIG.load(deps.storage)?.denom;
+            let received = cw_utils::may_pay(&info, denom.as_str())?;
+            execute_fund_reward_reserve(deps, env, &info.sender, received)
+        }
         ExecuteMsg::Unstake { amount } => execute_unstake(deps, env, info, amount),
         ExecuteMsg::Claim {} => execute_claim(deps, env, info),
-        ExecuteMsg::UpdateConfig { admin, duration } => {
-            execute_update_config(info, deps, admin, duration)
+        ExecuteMsg::FundBonus {} => execute_fund_bonus(deps, env, info),
+        ExecuteMsg::ClaimBonus {} => execute_claim_bonus(deps, env, info),
+        ExecuteMsg::CollectSuperfluidRewards {} => execute_collect_superfluid_rewards(deps, env),
+        ExecuteMsg::ReconcileBalance {} => execute_reconcile_balance(deps, env, info),
+        ExecuteMsg::ForfeitClaims {} => execute_forfeit_claims(deps, env, info),
+        ExecuteMsg::CompoundVotingPower {} => execute_compound_voting_power(deps, env, info),
+        ExecuteMsg::SetEmergencyClaimHeight { height } => {
+            execute_set_emergency_claim_height(deps, info, Some(height))
+        }
+        ExecuteMsg::ClearEmergencyClaimHeight {} => {
+            execute_set_emergency_claim_height(deps, info, None)
         }
+        ExecuteMsg::Slash {
+            address,
+            fraction,
+            reason,
+        } => execute_slash(deps, env, info, address, fraction, reason),
+        ExecuteMsg::UpdateConfig {
+            admin,
+            duration,
+            reward_multiplier,
+            fund_allowlist,
+            clear_fund_allowlist,
+            reward_vesting_blocks,
+            min_claim_amount,
+            warmup_period,
+            bonus_denom,
+        } => execute_update_config(
+            info,
+            deps,
+            admin,
+            duration,
+            reward_multiplier,
+            fund_allowlist,
+            clear_fund_allowlist,
+            reward_vesting_blocks,
+            min_claim_amount,
+            warmup_period,
+            bonus_denom,
+        ),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     info: MessageInfo,
     deps: DepsMut,
     new_admin: Option<Addr>,
     duration: Option<Duration>,
+    reward_multiplier: Option<Decimal>,
+    fund_allowlist: Option<Vec<Addr>>,
+    clear_fund_allowlist: bool,
+    reward_vesting_blocks: Option<u64>,
+    min_claim_amount: Option<Uint128>,
+    warmup_period: Option<u64>,
+    bonus_denom: Option<String>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = CONFIG.load(deps.storage)?;
     match config.admin {
@@ -91,8 +211,53 @@ pub fn execute_update_config(
                 });
             }
 
+            // `config.denom` is deliberately not updatable, see `Config::denom`
             config.admin = new_admin;
+            validate_unstaking_duration(duration)?;
             config.unstaking_duration = duration;
+            if let Some(reward_multiplier) = reward_multiplier {
+                let max = Decimal::percent(MAX_REWARD_MULTIPLIER_PERCENT);
+                if reward_multiplier < Decimal::one() || reward_multiplier > max {
+                    return Err(ContractError::InvalidRewardMultiplier { max });
+                }
+                config.reward_multiplier = reward_multiplier;
+            }
+            if clear_fund_allowlist {
+                config.fund_allowlist = None;
+            } else if let Some(fund_allowlist) = fund_allowlist {
+                config.fund_allowlist = Some(
+                    fund_allowlist
+                        .iter()
+                        .map(|addr| deps.api.addr_validate(addr.as_str()))
+                        .collect::<StdResult<Vec<_>>>()?,
+                );
+            }
+            if let Some(reward_vesting_blocks) = reward_vesting_blocks {
+                config.reward_vesting_blocks = reward_vesting_blocks;
+            }
+            if let Some(min_claim_amount) = min_claim_amount {
+                config.min_claim_amount = min_claim_amount;
+            }
+            if let Some(warmup_period) = warmup_period {
+                if warmup_period > MAX_WARMUP_PERIOD {
+                    return Err(ContractError::WarmupPeriodTooLong {
+                        max: MAX_WARMUP_PERIOD,
+                    });
+                }
+                config.warmup_period = warmup_period;
+            }
+            if let Some(bonus_denom) = bonus_denom {
+                if bonus_denom == config.denom {
+                    return Err(ContractError::InvalidBonusDenom { denom: bonus_denom });
+                }
+                // accrued bonus is only tracked as an amount, it can't move to another denom
+                if let Some(bonus) = BONUS_REWARDS.may_load(deps.storage)? {
+                    if bonus.denom != bonus_denom {
+                        return Err(ContractError::BonusDenomLocked { denom: bonus.denom });
+                    }
+                }
+                config.bonus_denom = Some(bonus_denom);
+            }
 
             CONFIG.save(deps.storage, &config)?;
             Ok(Response::new().add_attribute(
@@ -106,14 +271,46 @@ pub fn execute_update_config(
     }
 }
 
+fn validate_unstaking_duration(duration: Option<Duration>) -> Result<(), ContractError> {
+    let max = match duration {
+        Some(Duration::Height(height)) if height > MAX_UNSTAKING_DURATION_HEIGHT => {
+            Duration::Height(MAX_UNSTAKING_DURATION_HEIGHT)
+        }
+        Some(Duration::Time(time)) if time > MAX_UNSTAKING_DURATION_TIME => {
+            Duration::Time(MAX_UNSTAKING_DURATION_TIME)
+        }
+        _ => return Ok(()),
+    };
+    Err(ContractError::UnstakingDurationTooLong { max })
+}
+
 pub fn execute_stake(
     deps: DepsMut,
     env: Env,
     sender: &Addr,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
-    let balance = BALANCE.load(deps.storage).unwrap_or_default();
-    let staked_total = STAKED_TOTAL.load(deps.storage).unwrap_or_default();
+    let staked = stake_shares(deps.storage, sender, amount, env.block.height)?;
+    update_lifetime_totals(deps, sender, |totals| {
+        totals.staked = totals.staked.checked_add(amount)?;
+        Ok(())
+    })?;
+    Ok(Response::new()
+        .set_data(to_binary(&staked)?)
+        .add_attribute("action", "stake")
+        .add_attribute("from", sender)
+        .add_attribute("amount", amount))
+}
+
+/// Mints the shares `amount` of the staking denom is worth and adds it to `BALANCE`
+fn stake_shares(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    amount: Uint128,
+    height: u64,
+) -> StdResult<StakeResponse> {
+    let balance = BALANCE.load(storage).unwrap_or_default();
+    let staked_total = STAKED_TOTAL.load(storage).unwrap_or_default();
     let amount_to_stake = if staked_total == Uint128::zero() || balance == Uint128::zero() {
         amount
     } else {
@@ -123,27 +320,293 @@ pub fn execute_stake(
             .checked_div(balance)
             .map_err(StdError::divide_by_zero)?
     };
-    STAKED_BALANCES.update(
-        deps.storage,
-        sender,
-        env.block.height,
-        |bal| -> StdResult<Uint128> { Ok(bal.unwrap_or_default().checked_add(amount_to_stake)?) },
-    )?;
-    STAKED_TOTAL.update(
-        deps.storage,
-        env.block.height,
-        |total| -> StdResult<Uint128> {
-            Ok(total.unwrap_or_default().checked_add(amount_to_stake)?)
-        },
-    )?;
+    let new_balance = mint_shares(storage, sender, amount_to_stake, height)?;
     BALANCE.save(
-        deps.storage,
+        storage,
         &balance.checked_add(amount).map_err(StdError::overflow)?,
     )?;
+    Ok(StakeResponse {
+        shares_minted: amount_to_stake,
+        new_balance,
+    })
+}
+
+/// `Stake` when `Config::basket_denoms` is set: any mix of the staking denom and basket
+/// denoms is accepted, and shares follow the deposit's value in the staking denom
+pub fn execute_stake_basket(
+    mut deps: DepsMut,
+    env: Env,
+    sender: &Addr,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let (staked, value) = stake_basket_shares(deps.branch(), sender, funds, env.block.height)?;
+    update_lifetime_totals(deps, sender, |totals| {
+        totals.staked = totals.staked.checked_add(value)?;
+        Ok(())
+    })?;
     Ok(Response::new()
+        .set_data(to_binary(&staked)?)
         .add_attribute("action", "stake")
         .add_attribute("from", sender)
-        .add_attribute("amount", amount))
+        .add_attribute("amount", value))
+}
+
+/// Mints the shares a basket deposit is worth, returned along with its value in the
+/// staking denom
+fn stake_basket_shares(
+    deps: DepsMut,
+    sender: &Addr,
+    funds: Vec<Coin>,
+    height: u64,
+) -> Result<(StakeResponse, Uint128), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let prices = basket_prices(deps.as_ref(), &config)?;
+    let balance = BALANCE.load(deps.storage).unwrap_or_default();
+    let holdings = basket_value(deps.storage, balance, &prices)?;
+
+    let mut received = Uint128::zero();
+    let mut value = Uint128::zero();
+    for fund in funds {
+        if fund.denom == config.denom {
+            received = received
+                .checked_add(fund.amount)
+                .map_err(StdError::overflow)?;
+            value = value.checked_add(fund.amount).map_err(StdError::overflow)?;
+            continue;
+        }
+
+        let price = prices
+            .iter()
+            .find(|(denom, _)| denom == &fund.denom)
+            .map(|(_, price)| *price)
+            .ok_or_else(|| ContractError::NotInBasket {
+                denom: fund.denom.clone(),
+            })?;
+        value = value
+            .checked_add(value_in_denom(fund.amount, price)?)
+            .map_err(StdError::overflow)?;
+        BASKET_BALANCES.update(deps.storage, &fund.denom, |bal| -> StdResult<Uint128> {
+            Ok(bal.unwrap_or_default().checked_add(fund.amount)?)
+        })?;
+    }
+
+    let staked_total = STAKED_TOTAL.load(deps.storage).unwrap_or_default();
+    let amount_to_stake = if staked_total.is_zero() || holdings.is_zero() {
+        value
+    } else {
+        staked_total
+            .checked_multiply_ratio(value, holdings)
+            .map_err(|err| StdError::generic_err(err.to_string()))?
+    };
+    let new_balance = mint_shares(deps.storage, sender, amount_to_stake, height)?;
+    BALANCE.save(
+        deps.storage,
+        &balance.checked_add(received).map_err(StdError::overflow)?,
+    )?;
+    Ok((
+        StakeResponse {
+            shares_minted: amount_to_stake,
+            new_balance,
+        },
+        value,
+    ))
+}
+
+/// Returns the new share balance of `sender`
+fn mint_shares(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    amount: Uint128,
+    height: u64,
+) -> StdResult<Uint128> {
+    let before = STAKED_BALANCES
+        .may_load(storage, sender)?
+        .unwrap_or_default();
+    settle_bonus(storage, sender, before)?;
+    settle_warmups(storage, sender, height)?;
+    let warmup_period = CONFIG.load(storage)?.warmup_period;
+    if warmup_period > 0 {
+        // stakes within one block share the end of the first one
+        let deposit =
+            WARMUP_DEPOSITS.update(storage, (sender, height), |deposit| -> StdResult<_> {
+                Ok(match deposit {
+                    Some(deposit) => WarmupDeposit {
+                        amount: deposit.amount.checked_add(amount)?,
+                        ..deposit
+                    },
+                    None => WarmupDeposit {
+                        amount,
+                        ends_at: height + warmup_period,
+                    },
+                })
+            })?;
+        WARMUP_ENDS.update(storage, deposit.ends_at, |ending| -> StdResult<_> {
+            Ok(ending.unwrap_or_default().checked_add(amount)?)
+        })?;
+        WARMING_BALANCES.update(storage, sender, height, |warming| -> StdResult<_> {
+            Ok(warming.unwrap_or_default().checked_add(amount)?)
+        })?;
+        WARMING_TOTAL.update(storage, height, |warming| -> StdResult<_> {
+            Ok(warming.unwrap_or_default().checked_add(amount)?)
+        })?;
+    }
+    let balance = before.checked_add(amount)?;
+    STAKED_BALANCES.save(storage, sender, &balance, height)?;
+    STAKED_TOTAL.update(storage, height, |total| -> StdResult<Uint128> {
+        Ok(total.unwrap_or_default().checked_add(amount)?)
+    })?;
+    Ok(balance)
+}
+
+/// Settles the warmups that ended by `height`: the sender's into `WARMING_BALANCES` and
+/// everyone's into `WARMING_TOTAL`. Nothing touched either snapshot since they were last
+/// settled, so each end can still be written at the block before it
+fn settle_warmups(storage: &mut dyn Storage, staker: &Addr, height: u64) -> StdResult<()> {
+    let mut ended = WARMUP_DEPOSITS
+        .prefix(staker)
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, deposit)) if deposit.ends_at > height))
+        .collect::<StdResult<Vec<_>>>()?;
+    ended.sort_by_key(|(_, deposit)| deposit.ends_at);
+    for (staked_at, deposit) in ended {
+        WARMUP_DEPOSITS.remove(storage, (staker, staked_at));
+        WARMING_BALANCES.update(
+            storage,
+            staker,
+            deposit.ends_at - 1,
+            |warming| -> StdResult<_> {
+                Ok(warming.unwrap_or_default().checked_sub(deposit.amount)?)
+            },
+        )?;
+    }
+
+    let ended = WARMUP_ENDS
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive(height)),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<_>>>()?;
+    for (ends_at, amount) in ended {
+        WARMUP_ENDS.remove(storage, ends_at);
+        WARMING_TOTAL.update(storage, ends_at - 1, |warming| -> StdResult<_> {
+            Ok(warming.unwrap_or_default().checked_sub(amount)?)
+        })?;
+    }
+    Ok(())
+}
+
+/// Takes burned shares out of the staker's warmups, newest first, so burning fresh stake
+/// doesn't cost voting power the older shares already earned
+fn burn_warmups(
+    storage: &mut dyn Storage,
+    staker: &Addr,
+    shares: Uint128,
+    height: u64,
+) -> StdResult<()> {
+    settle_warmups(storage, staker, height)?;
+    let deposits = WARMUP_DEPOSITS
+        .prefix(staker)
+        .range(storage, None, None, Order::Descending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut burned = Uint128::zero();
+    for (staked_at, mut deposit) in deposits {
+        let amount = deposit.amount.min(shares - burned);
+        if amount.is_zero() {
+            break;
+        }
+        deposit.amount -= amount;
+        if deposit.amount.is_zero() {
+            WARMUP_DEPOSITS.remove(storage, (staker, staked_at));
+        } else {
+            WARMUP_DEPOSITS.save(storage, (staker, staked_at), &deposit)?;
+        }
+        let ending = WARMUP_ENDS.load(storage, deposit.ends_at)? - amount;
+        if ending.is_zero() {
+            WARMUP_ENDS.remove(storage, deposit.ends_at);
+        } else {
+            WARMUP_ENDS.save(storage, deposit.ends_at, &ending)?;
+        }
+        burned += amount;
+    }
+
+    if !burned.is_zero() {
+        WARMING_BALANCES.update(storage, staker, height, |warming| -> StdResult<_> {
+            Ok(warming.unwrap_or_default().checked_sub(burned)?)
+        })?;
+        WARMING_TOTAL.update(storage, height, |warming| -> StdResult<_> {
+            Ok(warming.unwrap_or_default().checked_sub(burned)?)
+        })?;
+    }
+    Ok(())
+}
+
+/// Spot price of every basket denom, in the staking denom
+fn basket_prices(deps: Deps, config: &Config) -> StdResult<Vec<(String, Decimal)>> {
+    config
+        .basket_denoms
+        .iter()
+        .map(|basket| {
+            let query = OsmosisQuery::spot_price(basket.pool_id, &basket.denom, &config.denom);
+            let res: SpotPriceResponse = deps.querier.query(&query.into())?;
+            Ok((basket.denom.clone(), res.price))
+        })
+        .collect()
+}
+
+fn basket_value(
+    storage: &dyn Storage,
+    balance: Uint128,
+    prices: &[(String, Decimal)],
+) -> StdResult<Uint128> {
+    prices.iter().try_fold(balance, |value, (denom, price)| {
+        let held = BASKET_BALANCES
+            .may_load(storage, denom)?
+            .unwrap_or_default();
+        Ok(value.checked_add(value_in_denom(held, *price)?)?)
+    })
+}
+
+/// `amount` of a basket denom priced in the staking denom
+fn value_in_denom(amount: Uint128, price: Decimal) -> StdResult<Uint128> {
+    amount
+        .checked_multiply_ratio(price.numerator(), price.denominator())
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+/// Staking denom held for the stakers plus the basket tokens, valued at their spot price
+fn total_holdings(deps: Deps, height: u64) -> StdResult<Uint128> {
+    let config = CONFIG.load(deps.storage)?;
+    let balance = vested_balance(deps.storage, height)?;
+    let prices = basket_prices(deps, &config)?;
+    basket_value(deps.storage, balance, &prices)
+}
+
+/// Takes the basket tokens backing `shares` out of `BASKET_BALANCES`
+fn withdraw_basket(
+    storage: &mut dyn Storage,
+    basket_denoms: &[BasketDenom],
+    shares: Uint128,
+    staked_total: Uint128,
+) -> StdResult<Vec<Coin>> {
+    let mut withdrawn = vec![];
+    for basket in basket_denoms {
+        let held = BASKET_BALANCES
+            .may_load(storage, &basket.denom)?
+            .unwrap_or_default();
+        let amount = held
+            .checked_multiply_ratio(shares, staked_total)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        if amount.is_zero() {
+            continue;
+        }
+
+        BASKET_BALANCES.save(storage, &basket.denom, &(held - amount))?;
+        withdrawn.push(coin(amount.u128(), &basket.denom));
+    }
+    Ok(withdrawn)
 }
 
 pub fn execute_unstake(
@@ -160,12 +623,24 @@ pub fn execute_unstake(
         .map_err(StdError::overflow)?
         .checked_div(staked_total)
         .map_err(StdError::divide_by_zero)?;
-    STAKED_BALANCES.update(
+    let staked_before = STAKED_BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    settle_bonus(deps.storage, &info.sender, staked_before)?;
+    burn_warmups(deps.storage, &info.sender, amount, env.block.height)?;
+    let staked_after = STAKED_BALANCES.update(
         deps.storage,
         &info.sender,
         env.block.height,
         |bal| -> StdResult<Uint128> { Ok(bal.unwrap_or_default().checked_sub(amount)?) },
     )?;
+    scale_compounded_balance(
+        deps.storage,
+        &info.sender,
+        staked_before,
+        staked_after,
+        env.block.height,
+    )?;
     STAKED_TOTAL.update(
         deps.storage,
         env.block.height,
@@ -177,17 +652,33 @@ pub fn execute_unstake(
             .checked_sub(amount_to_claim)
             .map_err(StdError::overflow)?,
     )?;
+    let basket_to_claim =
+        withdraw_basket(deps.storage, &config.basket_denoms, amount, staked_total)?;
     match config.unstaking_duration {
-        None => Ok(Response::new()
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: coins(amount_to_claim.u128(), config.denom),
-            })
-            .add_attribute("action", "unstake")
-            .add_attribute("from", info.sender)
-            .add_attribute("amount", amount)
-            .add_attribute("claim_duration", "None")),
+        None => {
+            update_lifetime_totals(deps, &info.sender, |totals| {
+                totals.claimed = totals.claimed.checked_add(amount_to_claim)?;
+                Ok(())
+            })?;
+            let mut payout = coins(amount_to_claim.u128(), config.denom);
+            payout.extend(basket_to_claim);
+            Ok(Response::new()
+                .add_message(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: payout,
+                })
+                .add_attribute("action", "unstake")
+                .add_attribute("from", info.sender)
+                .add_attribute("amount", amount)
+                .add_attribute("claim_duration", "None"))
+        }
         Some(duration) => {
+            if amount_to_claim < config.min_claim_amount {
+                return Err(ContractError::ClaimAmountTooSmall {
+                    min: config.min_claim_amount,
+                    received: amount_to_claim,
+                });
+            }
             let outstanding_claims = CLAIMS.query_claims(deps.as_ref(), &info.sender)?.claims;
             if outstanding_claims.len() >= MAX_CLAIMS as usize {
                 return Err(ContractError::TooManyClaims {});
@@ -199,6 +690,26 @@ pub fn execute_unstake(
                 amount_to_claim,
                 duration.after(&env.block),
             )?;
+            let claims_total = CLAIMS_TOTAL.may_load(deps.storage)?.unwrap_or_default();
+            CLAIMS_TOTAL.save(
+                deps.storage,
+                &claims_total
+                    .checked_add(amount_to_claim)
+                    .map_err(StdError::overflow)?,
+            )?;
+            if !basket_to_claim.is_empty() {
+                let mut basket_claims = BASKET_CLAIMS
+                    .may_load(deps.storage, &info.sender)?
+                    .unwrap_or_default();
+                if basket_claims.len() >= MAX_CLAIMS as usize {
+                    return Err(ContractError::TooManyClaims {});
+                }
+                basket_claims.push(BasketClaim {
+                    amounts: basket_to_claim,
+                    release_at: duration.after(&env.block),
+                });
+                BASKET_CLAIMS.save(deps.storage, &info.sender, &basket_claims)?;
+            }
             Ok(Response::new()
                 .add_attribute("action", "unstake")
                 .add_attribute("from", info.sender)
@@ -208,125 +719,1380 @@ pub fn execute_unstake(
     }
 }
 
+/// Block to check claim maturity against, past the emergency height every claim counts
+/// as matured, whatever its release date
+fn maturity_block(storage: &dyn Storage, block: BlockInfo) -> StdResult<BlockInfo> {
+    let emergency = EMERGENCY_CLAIM_HEIGHT
+        .may_load(storage)?
+        .is_some_and(|height| block.height >= height);
+    if emergency {
+        return Ok(BlockInfo {
+            height: u64::MAX,
+            time: Timestamp::from_nanos(u64::MAX),
+            ..block
+        });
+    }
+
+    Ok(block)
+}
+
 pub fn execute_claim(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
-    let release = CLAIMS.claim_tokens(deps.storage, &info.sender, &_env.block, None)?;
-    if release.is_zero() {
+    let block = maturity_block(deps.storage, env.block)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let dust = drop_dust_claims(deps.storage, &info.sender, &block, config.min_claim_amount)?;
+    let release = CLAIMS.claim_tokens(deps.storage, &info.sender, &block, None)?;
+    let basket_release = claim_basket(deps.storage, &info.sender, &block)?;
+    if release.is_zero() && basket_release.is_empty() && dust.is_zero() {
         return Err(ContractError::NothingToClaim {});
     }
-    let config = CONFIG.load(deps.storage)?;
+    settle_claims_total(deps.storage, release + dust)?;
+    update_lifetime_totals(deps, &info.sender, |totals| {
+        totals.claimed = totals.claimed.checked_add(release)?;
+        Ok(())
+    })?;
 
-    Ok(Response::new()
-        .add_message(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: coins(release.u128(), config.denom),
-        })
+    let mut payout = basket_release;
+    if !release.is_zero() {
+        payout.insert(0, coin(release.u128(), config.denom));
+    }
+    let mut resp = Response::new()
         .add_attribute("action", "claim")
-        .add_attribute("from", info.sender)
-        .add_attribute("amount", release))
+        .add_attribute("from", info.sender.clone())
+        .add_attribute("amount", release)
+        .add_attribute("skipped_dust", dust);
+    if !payout.is_empty() {
+        resp = resp.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: payout,
+        });
+    }
+    Ok(resp)
 }
 
-pub fn execute_fund(
-    deps: DepsMut,
-    _env: Env,
-    sender: &Addr,
-    amount: Uint128,
-) -> Result<Response, ContractError> {
-    let balance = BALANCE.load(deps.storage).unwrap_or_default();
-    BALANCE.save(
-        deps.storage,
-        &balance.checked_add(amount).map_err(StdError::overflow)?,
-    )?;
-    Ok(Response::new()
-        .add_attribute("action", "fund")
-        .add_attribute("from", sender)
-        .add_attribute("amount", amount))
+/// Takes `amount` of paid out, forfeited or dropped claims off `CLAIMS_TOTAL`
+fn settle_claims_total(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let claims_total = CLAIMS_TOTAL.may_load(storage)?.unwrap_or_default();
+    CLAIMS_TOTAL.save(
+        storage,
+        &claims_total
+            .checked_sub(amount)
+            .map_err(StdError::overflow)?,
+    )
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
-        QueryMsg::StakedBalanceAtHeight { address, height } => {
-            to_binary(&query_staked_balance_at_height(deps, env, address, height)?)
-        }
-        QueryMsg::TotalStakedAtHeight { height } => {
-            to_binary(&query_total_staked_at_height(deps, env, height)?)
-        }
-        QueryMsg::StakedValue { address } => to_binary(&query_staked_value(deps, env, address)?),
-        QueryMsg::TotalValue {} => to_binary(&query_total_value(deps, env)?),
-        QueryMsg::Claims { address } => to_binary(&query_claims(deps, address)?),
+/// Removes the matured claims of `address` below `min` without paying them out, returning
+/// their total. They can only exist when `min_claim_amount` was raised after they were created
+fn drop_dust_claims(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    block: &BlockInfo,
+    min: Uint128,
+) -> StdResult<Uint128> {
+    if min.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    let claims = CLAIMS_BY_ADDRESS
+        .may_load(storage, address)?
+        .unwrap_or_default();
+    let (dust, kept): (Vec<_>, Vec<_>) = claims
+        .into_iter()
+        .partition(|claim| claim.release_at.is_expired(block) && claim.amount < min);
+    if dust.is_empty() {
+        return Ok(Uint128::zero());
     }
+    CLAIMS_BY_ADDRESS.save(storage, address, &kept)?;
+    Ok(dust.into_iter().map(|claim| claim.amount).sum())
 }
 
-pub fn query_staked_balance_at_height(
-    deps: Deps,
-    _env: Env,
-    address: String,
-    height: Option<u64>,
-) -> StdResult<StakedBalanceAtHeightResponse> {
-    let address = deps.api.addr_validate(&address)?;
-    let height = height.unwrap_or(_env.block.height);
-    let balance = STAKED_BALANCES
-        .may_load_at_height(deps.storage, &address, height)?
+/// Removes the matured basket claims of `address` and returns their combined amounts
+fn claim_basket(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    block: &BlockInfo,
+) -> StdResult<Vec<Coin>> {
+    let claims = BASKET_CLAIMS
+        .may_load(storage, address)?
         .unwrap_or_default();
-    Ok(StakedBalanceAtHeightResponse { balance, height })
+    if claims.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let (matured, pending): (Vec<_>, Vec<_>) = claims
+        .into_iter()
+        .partition(|claim| claim.release_at.is_expired(block));
+    if pending.is_empty() {
+        BASKET_CLAIMS.remove(storage, address);
+    } else {
+        BASKET_CLAIMS.save(storage, address, &pending)?;
+    }
+
+    let mut released = NativeBalance::default();
+    for amount in matured.into_iter().flat_map(|claim| claim.amounts) {
+        released += amount;
+    }
+    Ok(released.into_vec())
 }
 
-pub fn query_total_staked_at_height(
-    deps: Deps,
-    _env: Env,
+pub fn execute_set_emergency_claim_height(
+    deps: DepsMut,
+    info: MessageInfo,
     height: Option<u64>,
-) -> StdResult<TotalStakedAtHeightResponse> {
-    let height = height.unwrap_or(_env.block.height);
-    let total = STAKED_TOTAL
-        .may_load_at_height(deps.storage, height)?
-        .unwrap_or_default();
-    Ok(TotalStakedAtHeightResponse { total, height })
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    match config.admin {
+        None => Err(ContractError::NoAdminConfigured {}),
+        Some(current_admin) => {
+            if info.sender != current_admin {
+                return Err(ContractError::Unauthorized {
+                    expected: current_admin,
+                    received: info.sender,
+                });
+            }
+
+            match height {
+                Some(height) => EMERGENCY_CLAIM_HEIGHT.save(deps.storage, &height)?,
+                None => EMERGENCY_CLAIM_HEIGHT.remove(deps.storage),
+            }
+
+            Ok(Response::new()
+                .add_attribute("action", "set_emergency_claim_height")
+                .add_attribute(
+                    "height",
+                    height
+                        .map(|h| h.to_string())
+                        .unwrap_or_else(|| "None".to_string()),
+                ))
+        }
+    }
 }
 
-pub fn query_staked_value(
-    deps: Deps,
-    _env: Env,
-    address: String,
-) -> StdResult<StakedValueResponse> {
-    let address = deps.api.addr_validate(&address)?;
-    let balance = BALANCE.load(deps.storage).unwrap_or_default();
-    let staked = STAKED_BALANCES
-        .load(deps.storage, &address)
+/// Re-stakes the whole claimed amount at the current share price, matured claims included
+pub fn execute_forfeit_claims(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let forfeited = pending_claims_value(deps.storage, &info.sender)?;
+    let basket_claims = BASKET_CLAIMS
+        .may_load(deps.storage, &info.sender)?
         .unwrap_or_default();
-    let total = STAKED_TOTAL.load(deps.storage).unwrap_or_default();
-    if balance == Uint128::zero() || staked == Uint128::zero() || total == Uint128::zero() {
-        Ok(StakedValueResponse {
-            value: Uint128::zero(),
-        })
-    } else {
-        let value = staked
-            .checked_mul(balance)
-            .map_err(StdError::overflow)?
-            .checked_div(total)
-            .map_err(StdError::divide_by_zero)?;
-        Ok(StakedValueResponse { value })
+    if forfeited.is_zero() && basket_claims.is_empty() {
+        return Err(ContractError::NothingToForfeit {});
     }
+    CLAIMS_BY_ADDRESS.remove(deps.storage, &info.sender);
+    BASKET_CLAIMS.remove(deps.storage, &info.sender);
+    settle_claims_total(deps.storage, forfeited)?;
+    // the claimed tokens never left the contract, staking them again only moves them back into
+    // `BALANCE` and `BASKET_BALANCES`. They were staked before, so the lifetime totals stay put,
+    // but the new shares warm up like any other: unstaking burns warming shares first, and
+    // forfeiting right after would otherwise skip the warmup
+    let staked = if config.basket_denoms.is_empty() {
+        stake_shares(deps.storage, &info.sender, forfeited, env.block.height)?
+    } else {
+        let mut funds = NativeBalance(coins(forfeited.u128(), config.denom));
+        for amount in basket_claims.into_iter().flat_map(|claim| claim.amounts) {
+            funds += amount;
+        }
+        funds.normalize();
+        stake_basket_shares(deps, &info.sender, funds.into_vec(), env.block.height)?.0
+    };
+
+    Ok(Response::new()
+        .set_data(to_binary(&staked)?)
+        .add_attribute("action", "forfeit_claims")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", forfeited))
 }
 
-pub fn query_total_value(deps: Deps, _env: Env) -> StdResult<TotalValueResponse> {
-    let balance = BALANCE.load(deps.storage).unwrap_or_default();
-    Ok(TotalValueResponse { total: balance })
+fn pending_claims_value(storage: &dyn Storage, address: &Addr) -> StdResult<Uint128> {
+    CLAIMS_BY_ADDRESS
+        .may_load(storage, address)?
+        .unwrap_or_default()
+        .iter()
+        .try_fold(Uint128::zero(), |total, claim| {
+            total.checked_add(claim.amount).map_err(StdError::overflow)
+        })
 }
 
-pub fn query_config(deps: Deps) -> StdResult<GetConfigResponse> {
-    let config = CONFIG.load(deps.storage)?;
-    Ok(GetConfigResponse {
-        admin: config.admin,
-        denom: config.denom,
+pub fn execute_compound_voting_power(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let staked = STAKED_BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if staked.is_zero() {
+        return Err(ContractError::NothingToCompound {});
+    }
+
+    let balance = total_holdings(deps.as_ref(), env.block.height)?;
+    let staked_total = STAKED_TOTAL.load(deps.storage)?;
+    let value = staked
+        .checked_multiply_ratio(balance, staked_total)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    // shares stay untouched so the value of other stakers is preserved,
+    // only the voting weight on top of them follows the accrued rewards
+    let compounded = value.saturating_sub(staked);
+    set_compounded_balance(deps.storage, &info.sender, compounded, env.block.height)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "compound_voting_power")
+        .add_attribute("from", info.sender)
+        .add_attribute("voting_power", staked + compounded))
+}
+
+fn set_compounded_balance(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    amount: Uint128,
+    height: u64,
+) -> StdResult<()> {
+    let before = COMPOUNDED_BALANCES
+        .may_load(storage, address)?
+        .unwrap_or_default();
+    if before == amount {
+        return Ok(());
+    }
+
+    COMPOUNDED_BALANCES.save(storage, address, &amount, height)?;
+    COMPOUNDED_TOTAL.update(storage, height, |total| -> StdResult<Uint128> {
+        Ok(total
+            .unwrap_or_default()
+            .checked_add(amount)?
+            .checked_sub(before)?)
+    })?;
+    Ok(())
+}
+
+/// Shrinks the compounded voting power along with the staked shares it was credited for
+fn scale_compounded_balance(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    staked_before: Uint128,
+    staked_after: Uint128,
+    height: u64,
+) -> StdResult<()> {
+    let compounded = COMPOUNDED_BALANCES
+        .may_load(storage, address)?
+        .unwrap_or_default();
+    if compounded.is_zero() || staked_before.is_zero() {
+        return Ok(());
+    }
+
+    let scaled = compounded.multiply_ratio(staked_after, staked_before);
+    set_compounded_balance(storage, address, scaled, height)
+}
+
+pub fn execute_slash(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    fraction: Decimal,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    match config.admin {
+        None => Err(ContractError::NoAdminConfigured {}),
+        Some(current_admin) => {
+            if info.sender != current_admin {
+                return Err(ContractError::Unauthorized {
+                    expected: current_admin,
+                    received: info.sender,
+                });
+            }
+            if fraction.is_zero() || fraction > Decimal::one() {
+                return Err(ContractError::InvalidSlashFraction {});
+            }
+
+            let address = deps.api.addr_validate(&address)?;
+            let amount_before = STAKED_BALANCES
+                .may_load(deps.storage, &address)?
+                .unwrap_or_default();
+            settle_bonus(deps.storage, &address, amount_before)?;
+            let slashed = amount_before * fraction;
+            let amount_after = amount_before - slashed;
+            burn_warmups(deps.storage, &address, slashed, env.block.height)?;
+
+            // slashed shares are burned and their underlying tokens go to the slasher
+            let balance = BALANCE.load(deps.storage).unwrap_or_default();
+            let staked_total = STAKED_TOTAL.load(deps.storage).unwrap_or_default();
+            let amount_to_send = if staked_total.is_zero() {
+                Uint128::zero()
+            } else {
+                slashed
+                    .checked_mul(balance)
+                    .map_err(StdError::overflow)?
+                    .checked_div(staked_total)
+                    .map_err(StdError::divide_by_zero)?
+            };
+
+            let basket_to_send = if staked_total.is_zero() {
+                vec![]
+            } else {
+                withdraw_basket(deps.storage, &config.basket_denoms, slashed, staked_total)?
+            };
+
+            STAKED_BALANCES.save(deps.storage, &address, &amount_after, env.block.height)?;
+            scale_compounded_balance(
+                deps.storage,
+                &address,
+                amount_before,
+                amount_after,
+                env.block.height,
+            )?;
+            STAKED_TOTAL.save(
+                deps.storage,
+                &staked_total
+                    .checked_sub(slashed)
+                    .map_err(StdError::overflow)?,
+                env.block.height,
+            )?;
+            BALANCE.save(
+                deps.storage,
+                &balance
+                    .checked_sub(amount_to_send)
+                    .map_err(StdError::overflow)?,
+            )?;
+            save_slash_record(
+                deps.storage,
+                &address,
+                SlashRecord {
+                    height: env.block.height,
+                    slasher: info.sender.clone(),
+                    amount_before,
+                    amount_after,
+                    fraction,
+                    reason,
+                },
+            )?;
+
+            let mut resp = Response::new()
+                .add_attribute("action", "slash")
+                .add_attribute("address", address)
+                .add_attribute("fraction", fraction.to_string())
+                .add_attribute("amount", slashed);
+            let mut payout = basket_to_send;
+            if !amount_to_send.is_zero() {
+                payout.insert(0, coin(amount_to_send.u128(), config.denom));
+            }
+            if !payout.is_empty() {
+                resp = resp.add_message(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: payout,
+                });
+            }
+            Ok(resp)
+        }
+    }
+}
+
+fn update_lifetime_totals(
+    deps: DepsMut,
+    address: &Addr,
+    update: impl FnOnce(&mut LifetimeTotals) -> StdResult<()>,
+) -> StdResult<()> {
+    let mut totals = LIFETIME_TOTALS
+        .may_load(deps.storage, address)?
+        .unwrap_or_default();
+    update(&mut totals)?;
+    LIFETIME_TOTALS.save(deps.storage, address, &totals)
+}
+
+/// With `fund_allowlist` set, only the admin and the listed addresses may fund
+fn check_fund_allowlist(config: &Config, sender: &Addr) -> Result<(), ContractError> {
+    if let Some(allowlist) = &config.fund_allowlist {
+        let is_admin = config.admin.as_ref() == Some(sender);
+        if !is_admin && !allowlist.contains(sender) {
+            return Err(ContractError::Unauthorized {
+                expected: config.admin.clone().unwrap_or_else(|| Addr::unchecked("")),
+                received: sender.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn execute_fund(
+    deps: DepsMut,
+    env: Env,
+    sender: &Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_fund_allowlist(&config, sender)?;
+
+    let boosted = boost_rewards(deps.storage, &config, amount)?;
+    credit_rewards(deps.storage, &config, env.block.height, boosted)?;
+    Ok(Response::new()
+        .add_attribute("action", "fund")
+        .add_attribute("from", sender)
+        .add_attribute("amount", amount)
+        .add_attribute("boosted_amount", boosted))
+}
+
+/// Like `execute_fund`, but the tokens are credited as they are, without drawing a
+/// `reward_multiplier` bonus from the reserve
+pub fn execute_fund_unboosted(
+    deps: DepsMut,
+    env: Env,
+    sender: &Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_fund_allowlist(&config, sender)?;
+
+    credit_rewards(deps.storage, &config, env.block.height, amount)?;
+    Ok(Response::new()
+        .add_attribute("action", "fund_unboosted")
+        .add_attribute("from", sender)
+        .add_attribute("amount", amount))
+}
+
+/// Boosts `amount` of rewards by `reward_multiplier`, the bonus is taken out of
+/// `REWARD_RESERVE`. Returns the boosted amount
+fn boost_rewards(
+    storage: &mut dyn Storage,
+    config: &Config,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let boosted = amount
+        .checked_multiply_ratio(
+            config.reward_multiplier.numerator(),
+            config.reward_multiplier.denominator(),
+        )
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    // the boosted portion is paid from the pre-funded reserve
+    let bonus = boosted.checked_sub(amount).map_err(StdError::overflow)?;
+    if !bonus.is_zero() {
+        let reserve = REWARD_RESERVE.may_load(storage)?.unwrap_or_default();
+        if reserve < bonus {
+            return Err(ContractError::InsufficientRewardReserve {
+                required: bonus,
+                reserve,
+            });
+        }
+        REWARD_RESERVE.save(storage, &(reserve - bonus))?;
+    }
+    Ok(boosted)
+}
+
+/// Adds `amount` of rewards to `BALANCE`, or to `PENDING_REWARDS` when rewards vest
+fn credit_rewards(
+    storage: &mut dyn Storage,
+    config: &Config,
+    height: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    if config.reward_vesting_blocks == 0 {
+        let balance = BALANCE.load(storage).unwrap_or_default();
+        BALANCE.save(
+            storage,
+            &balance.checked_add(amount).map_err(StdError::overflow)?,
+        )?;
+    } else {
+        // vests apart from the earlier rewards, so funding again never holds those back
+        release_pending_rewards(storage, height)?;
+        let rate = Decimal::checked_from_ratio(amount, config.reward_vesting_blocks)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        REWARD_VESTING_ENDS.update(
+            storage,
+            height + config.reward_vesting_blocks,
+            |ending| -> StdResult<_> { Ok(ending.unwrap_or_default() + rate) },
+        )?;
+        let pending = PENDING_REWARDS.may_load(storage)?.unwrap_or_default();
+        PENDING_REWARDS.save(
+            storage,
+            &PendingRewards {
+                amount: pending
+                    .amount
+                    .checked_add(amount)
+                    .map_err(StdError::overflow)?,
+                rate: pending.rate + rate,
+                last_height: height,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Withdraws the rewards of every delegation the contract holds. The last withdrawal replies
+/// so the received staking denom can be credited once all of them went through
+pub fn execute_collect_superfluid_rewards(
+    deps: DepsMut,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let delegations = deps
+        .querier
+        .query_all_delegations(env.contract.address.as_str())?;
+    if delegations.is_empty() {
+        return Err(ContractError::NoDelegations {});
+    }
+
+    let held = deps
+        .querier
+        .query_balance(&env.contract.address, &config.denom)?
+        .amount;
+    PRE_COLLECT_BALANCE.save(deps.storage, &held)?;
+
+    let last = delegations.len() - 1;
+    let msgs = delegations.into_iter().enumerate().map(|(i, delegation)| {
+        let msg = DistributionMsg::WithdrawDelegatorReward {
+            validator: delegation.validator,
+        };
+        if i == last {
+            SubMsg::reply_on_success(msg, COLLECT_SUPERFLUID_REWARDS_REPLY_ID)
+        } else {
+            SubMsg::new(msg)
+        }
+    });
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attribute("action", "collect_superfluid_rewards")
+        .add_attribute("validators", (last + 1).to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        COLLECT_SUPERFLUID_REWARDS_REPLY_ID => reply_collect_superfluid_rewards(deps, env),
+        id => Err(StdError::generic_err(format!("Unknown reply id: {}", id)).into()),
+    }
+}
+
+fn reply_collect_superfluid_rewards(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let before = PRE_COLLECT_BALANCE.load(deps.storage)?;
+    PRE_COLLECT_BALANCE.remove(deps.storage);
+
+    let held = deps
+        .querier
+        .query_balance(&env.contract.address, &config.denom)?
+        .amount;
+    let collected = held.saturating_sub(before);
+    // staking yield, not a deposit the reward multiplier is meant to encourage
+    credit_rewards(deps.storage, &config, env.block.height, collected)?;
+
+    let total = SUPERFLUID_REWARDS_COLLECTED
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    SUPERFLUID_REWARDS_COLLECTED.save(
+        deps.storage,
+        &total.checked_add(collected).map_err(StdError::overflow)?,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "collect_superfluid_rewards_reply")
+        .add_attribute("amount", collected))
+}
+
+/// Moves the vested part of `PENDING_REWARDS` into `BALANCE`
+pub fn release_pending_rewards(storage: &mut dyn Storage, height: u64) -> StdResult<()> {
+    let mut pending = match PENDING_REWARDS.may_load(storage)? {
+        Some(pending) => pending,
+        None => return Ok(()),
+    };
+
+    let ended = vesting_ends(storage, height)?;
+    let (release, rate) = vested_rewards(&pending, &ended, height);
+    // an unchanged rate keeps its start, so rounding doesn't add up over short steps
+    if release.is_zero() && ended.is_empty() {
+        return Ok(());
+    }
+    for (ends_at, _) in &ended {
+        REWARD_VESTING_ENDS.remove(storage, *ends_at);
+    }
+
+    let balance = BALANCE.load(storage).unwrap_or_default();
+    BALANCE.save(storage, &balance.checked_add(release)?)?;
+
+    if rate.is_zero() {
+        PENDING_REWARDS.remove(storage);
+    } else {
+        pending.amount -= release;
+        pending.rate = rate;
+        pending.last_height = height;
+        PENDING_REWARDS.save(storage, &pending)?;
+    }
+
+    Ok(())
+}
+
+/// `REWARD_VESTING_ENDS` reached by `height`
+fn vesting_ends(storage: &dyn Storage, height: u64) -> StdResult<Vec<(u64, Decimal)>> {
+    REWARD_VESTING_ENDS
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive(height)),
+            Order::Ascending,
+        )
+        .collect()
+}
+
+/// Part of `pending` vested by `height` given the vesting `ended` by then, along with the
+/// rate still vesting after it
+fn vested_rewards(
+    pending: &PendingRewards,
+    ended: &[(u64, Decimal)],
+    height: u64,
+) -> (Uint128, Decimal) {
+    let mut rate = pending.rate;
+    let mut last_height = pending.last_height;
+    let mut release = Uint128::zero();
+    for (ends_at, ending) in ended {
+        release += Uint128::from(ends_at - last_height) * rate;
+        rate -= *ending;
+        last_height = *ends_at;
+    }
+    release += Uint128::from(height - last_height) * rate;
+    // once nothing vests anymore, whatever rounding held back goes out too
+    let release = if rate.is_zero() {
+        pending.amount
+    } else {
+        release.min(pending.amount)
+    };
+    (release, rate)
+}
+
+/// `BALANCE` including the rewards vested by `height`, queries can't release them
+fn vested_balance(storage: &dyn Storage, height: u64) -> StdResult<Uint128> {
+    let balance = BALANCE.load(storage).unwrap_or_default();
+    let vested = match PENDING_REWARDS.may_load(storage)? {
+        Some(pending) => vested_rewards(&pending, &vesting_ends(storage, height)?, height).0,
+        None => Uint128::zero(),
+    };
+    Ok(balance.checked_add(vested)?)
+}
+
+pub fn execute_fund_reward_reserve(
+    deps: DepsMut,
+    _env: Env,
+    sender: &Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let reserve = REWARD_RESERVE.may_load(deps.storage)?.unwrap_or_default();
+    REWARD_RESERVE.save(
+        deps.storage,
+        &reserve.checked_add(amount).map_err(StdError::overflow)?,
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "fund_reward_reserve")
+        .add_attribute("from", sender)
+        .add_attribute("amount", amount))
+}
+
+/// Appends `record` to the slash history of `address`
+fn save_slash_record(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    record: SlashRecord,
+) -> StdResult<()> {
+    let seq = SLASH_COUNTS.may_load(storage, address)?.unwrap_or_default();
+    SLASH_RECORDS.save(storage, (address, seq), &record)?;
+    SLASH_COUNTS.save(storage, address, &(seq + 1))
+}
+
+/// Staking denom the contract holds for its stakers: the bank balance minus what is owed to
+/// outstanding claims, kept in `REWARD_RESERVE` or still vesting in `PENDING_REWARDS`
+fn held_staked_balance(deps: Deps, env: &Env) -> StdResult<Uint128> {
+    let denom = CONFIG.load(deps.storage)?.denom;
+    let bank = deps
+        .querier
+        .query_balance(&env.contract.address, denom)?
+        .amount;
+
+    let mut earmarked = REWARD_RESERVE.may_load(deps.storage)?.unwrap_or_default();
+    if let Some(pending) = PENDING_REWARDS.may_load(deps.storage)? {
+        earmarked += pending.amount;
+    }
+    earmarked += CLAIMS_TOTAL.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(bank.saturating_sub(earmarked))
+}
+
+/// `actual - recorded`, an overflow error rather than a wrapped value when it doesn't fit
+fn balance_drift(recorded: Uint128, actual: Uint128) -> StdResult<i128> {
+    let overflow =
+        || StdError::overflow(OverflowError::new(OverflowOperation::Sub, actual, recorded));
+    let recorded = i128::try_from(recorded.u128()).map_err(|_| overflow())?;
+    let actual = i128::try_from(actual.u128()).map_err(|_| overflow())?;
+    actual.checked_sub(recorded).ok_or_else(overflow)
+}
+
+pub fn execute_reconcile_balance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let admin = config.admin.ok_or(ContractError::NoAdminConfigured {})?;
+    if info.sender != admin {
+        return Err(ContractError::Unauthorized {
+            expected: admin,
+            received: info.sender,
+        });
+    }
+
+    let recorded = BALANCE.load(deps.storage).unwrap_or_default();
+    let actual = held_staked_balance(deps.as_ref(), &env)?;
+    BALANCE.save(deps.storage, &actual)?;
+
+    let drift = balance_drift(recorded, actual)?;
+    let mut resp = Response::new()
+        .add_attribute("action", "reconcile_balance")
+        .add_attribute("recorded_balance", recorded)
+        .add_attribute("actual_balance", actual)
+        .add_attribute("drift", drift.to_string());
+    if drift != 0 {
+        resp = resp.add_attribute("warning", "balance_drift");
+    }
+    Ok(resp)
+}
+
+/// Credits `address` with the bonus its `shares` earned since its last settlement.
+/// Must run before every change of the address's shares
+fn settle_bonus(storage: &mut dyn Storage, address: &Addr, shares: Uint128) -> StdResult<()> {
+    let bonus = match BONUS_REWARDS.may_load(storage)? {
+        Some(bonus) => bonus,
+        None => return Ok(()),
+    };
+    let mut accrual = BONUS_ACCRUALS
+        .may_load(storage, address)?
+        .unwrap_or_default();
+    if accrual.index == bonus.index {
+        return Ok(());
+    }
+    accrual.pending = accrual
+        .pending
+        .checked_add(shares * (bonus.index - accrual.index))?;
+    accrual.index = bonus.index;
+    BONUS_ACCRUALS.save(storage, address, &accrual)
+}
+
+pub fn execute_fund_bonus(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_fund_allowlist(&config, &info.sender)?;
+    let funds = cw_utils::one_coin(&info)?;
+    if config.bonus_denom.as_ref() != Some(&funds.denom) {
+        return Err(ContractError::InvalidBonusDenom { denom: funds.denom });
+    }
+    let mut bonus = BONUS_REWARDS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| BonusRewards {
+            denom: funds.denom.clone(),
+            index: Decimal::zero(),
+        });
+    let staked_total = STAKED_TOTAL.may_load(deps.storage)?.unwrap_or_default();
+    if staked_total.is_zero() {
+        return Err(ContractError::NoStakers {});
+    }
+
+    bonus.index += Decimal::from_ratio(funds.amount, staked_total);
+    BONUS_REWARDS.save(deps.storage, &bonus)?;
+    Ok(Response::new()
+        .add_attribute("action", "fund_bonus")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", funds.to_string()))
+}
+
+pub fn execute_claim_bonus(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let bonus = BONUS_REWARDS
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NothingToClaim {})?;
+    let shares = STAKED_BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    settle_bonus(deps.storage, &info.sender, shares)?;
+
+    let mut accrual = BONUS_ACCRUALS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if accrual.pending.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    let amount = accrual.pending;
+    accrual.pending = Uint128::zero();
+    BONUS_ACCRUALS.save(deps.storage, &info.sender, &accrual)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(amount.u128(), &bonus.denom),
+        })
+        .add_attribute("action", "claim_bonus")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", coin(amount.u128(), bonus.denom).to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // claims created before `CLAIMS_TOTAL` was kept
+    let claims_total = CLAIMS_BY_ADDRESS
+        .range(deps.storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |total, item| -> StdResult<_> {
+            let (_, claims) = item?;
+            Ok(total + claims.iter().map(|claim| claim.amount).sum::<Uint128>())
+        })?;
+    CLAIMS_TOTAL.save(deps.storage, &claims_total)?;
+    // bonus denoms fixed by the first `FundBonus` before `Config::bonus_denom` existed
+    if let Some(bonus) = BONUS_REWARDS.may_load(deps.storage)? {
+        CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+            config.bonus_denom = Some(bonus.denom);
+            Ok(config)
+        })?;
+    }
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
+        QueryMsg::StakedBalanceAtHeight { address, height } => {
+            to_binary(&query_staked_balance_at_height(deps, env, address, height)?)
+        }
+        QueryMsg::StakedBalancesAtHeight { addresses, height } => to_binary(
+            &query_staked_balances_at_height(deps, env, addresses, height)?,
+        ),
+        QueryMsg::TotalStakedAtHeight { height } => {
+            to_binary(&query_total_staked_at_height(deps, env, height)?)
+        }
+        QueryMsg::TotalStakedHistory {
+            from_height,
+            to_height,
+            step,
+        } => to_binary(&query_total_staked_history(
+            deps,
+            env,
+            from_height,
+            to_height,
+            step,
+        )?),
+        QueryMsg::StakedValue { address } => to_binary(&query_staked_value(deps, env, address)?),
+        QueryMsg::TotalValue {} => to_binary(&query_total_value(deps, env)?),
+        QueryMsg::Claims { address } => to_binary(&query_claims(deps, address)?),
+        QueryMsg::StakerLifetime { address } => to_binary(&query_staker_lifetime(deps, address)?),
+        QueryMsg::SlashHistory {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_slash_history(deps, address, start_after, limit)?),
+        QueryMsg::AllSlashRecords { start_after, limit } => {
+            to_binary(&query_all_slash_records(deps, start_after, limit)?)
+        }
+        QueryMsg::BasketComposition {} => to_binary(&query_basket_composition(deps, env)?),
+        QueryMsg::BasketClaims { address } => to_binary(&query_basket_claims(deps, address)?),
+        QueryMsg::ConvertToAssets { shares } => {
+            to_binary(&query_convert_to_assets(deps, env, shares)?)
+        }
+        QueryMsg::ConvertToShares { assets } => {
+            to_binary(&query_convert_to_shares(deps, env, assets)?)
+        }
+        QueryMsg::TopStakers { limit } => to_binary(&query_top_stakers(deps, limit)?),
+        QueryMsg::UnbondingQueue { start_after, limit } => {
+            to_binary(&query_unbonding_queue(deps, start_after, limit)?)
+        }
+        QueryMsg::ClaimStatus { address } => to_binary(&query_claim_status(deps, env, address)?),
+        QueryMsg::ForfeitableClaimsValue { address } => {
+            to_binary(&query_forfeitable_claims_value(deps, address)?)
+        }
+        QueryMsg::PendingBonus { address } => to_binary(&query_pending_bonus(deps, address)?),
+        QueryMsg::BalanceDrift {} => to_binary(&query_balance_drift(deps, env)?),
+        QueryMsg::TotalSuperfluidRewardsCollected {} => {
+            to_binary(&query_total_superfluid_rewards_collected(deps)?)
+        }
+    }
+}
+
+pub fn query_balance_drift(deps: Deps, env: Env) -> StdResult<BalanceDriftResponse> {
+    let recorded = BALANCE.load(deps.storage).unwrap_or_default();
+    let recorded_balance = vested_balance(deps.storage, env.block.height)?;
+    // the vested rewards are still part of `PENDING_REWARDS`, which the held balance leaves out
+    let actual_balance = held_staked_balance(deps, &env)?
+        .checked_add(recorded_balance - recorded)
+        .map_err(StdError::overflow)?;
+    Ok(BalanceDriftResponse {
+        recorded_balance,
+        actual_balance,
+        drift: balance_drift(recorded_balance, actual_balance)?,
+    })
+}
+
+pub fn query_total_superfluid_rewards_collected(
+    deps: Deps,
+) -> StdResult<TotalSuperfluidRewardsCollectedResponse> {
+    Ok(TotalSuperfluidRewardsCollectedResponse {
+        total: SUPERFLUID_REWARDS_COLLECTED
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+    })
+}
+
+pub fn query_pending_bonus(deps: Deps, address: String) -> StdResult<PendingBonusResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let bonus = match BONUS_REWARDS.may_load(deps.storage)? {
+        Some(bonus) => bonus,
+        None => {
+            return Ok(PendingBonusResponse {
+                denom: None,
+                amount: Uint128::zero(),
+            })
+        }
+    };
+    let accrual = BONUS_ACCRUALS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let shares = STAKED_BALANCES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let amount = accrual
+        .pending
+        .checked_add(shares * (bonus.index - accrual.index))?;
+    Ok(PendingBonusResponse {
+        denom: Some(bonus.denom),
+        amount,
+    })
+}
+
+pub fn query_staked_balance_at_height(
+    deps: Deps,
+    env: Env,
+    address: String,
+    height: Option<u64>,
+) -> StdResult<StakedBalanceAtHeightResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let height = height.unwrap_or(env.block.height);
+    let balance = voting_balance_at_height(deps, &address, height)?;
+    Ok(StakedBalanceAtHeightResponse { balance, height })
+}
+
+pub fn query_staked_balances_at_height(
+    deps: Deps,
+    env: Env,
+    addresses: Vec<String>,
+    height: Option<u64>,
+) -> StdResult<StakedBalancesAtHeightResponse> {
+    if addresses.len() > MAX_LIMIT as usize {
+        return Err(StdError::generic_err(format!(
+            "Cannot query more than {} addresses at once",
+            MAX_LIMIT
+        )));
+    }
+
+    let height = height.unwrap_or(env.block.height);
+    let balances = addresses
+        .iter()
+        .map(|address| {
+            let address = deps.api.addr_validate(address)?;
+            let balance = voting_balance_at_height(deps, &address, height)?;
+            Ok(AddressStakedBalance { address, balance })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(StakedBalancesAtHeightResponse { balances, height })
+}
+
+/// Falls back to the nearest prior snapshot: a height between two staking events reports the
+/// total left by the earlier one, and a height before any stake reports zero
+pub fn query_total_staked_at_height(
+    deps: Deps,
+    env: Env,
+    height: Option<u64>,
+) -> StdResult<TotalStakedAtHeightResponse> {
+    let height = height.unwrap_or(env.block.height);
+    Ok(TotalStakedAtHeightResponse {
+        total: total_voting_power_at_height(deps, height)?,
+        height,
+    })
+}
+
+/// Staked shares plus the compounded voting power, minus the stake still warming up: the
+/// total the DAO weighs votes against
+fn total_voting_power_at_height(deps: Deps, height: u64) -> StdResult<Uint128> {
+    let staked = STAKED_TOTAL
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+    let compounded = COMPOUNDED_TOTAL
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+    let warming = WARMING_TOTAL
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+    // ended, but nobody staked or unstaked since to settle them
+    let unsettled = WARMUP_ENDS
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::inclusive(height)),
+            Order::Ascending,
+        )
+        .try_fold(Uint128::zero(), |total, item| -> StdResult<_> {
+            let (_, amount) = item?;
+            Ok(total.checked_add(amount)?)
+        })?;
+    Ok(staked
+        .checked_add(compounded)?
+        .checked_sub(warming.checked_sub(unsettled)?)?)
+}
+
+pub fn query_total_staked_history(
+    deps: Deps,
+    env: Env,
+    from_height: u64,
+    to_height: Option<u64>,
+    step: Option<u64>,
+) -> StdResult<TotalStakedHistoryResponse> {
+    let to_height = to_height.unwrap_or(env.block.height);
+    if to_height < from_height {
+        return Err(StdError::generic_err(
+            "to_height cannot be lower than from_height",
+        ));
+    }
+    let step = step.unwrap_or((to_height - from_height) / 10).max(1);
+
+    let samples = std::iter::successors(Some(from_height), |height| height.checked_add(step))
+        .take_while(|height| *height <= to_height)
+        .take(MAX_HISTORY_SAMPLES)
+        .map(|height| Ok((height, total_voting_power_at_height(deps, height)?)))
+        .collect::<StdResult<_>>()?;
+    Ok(TotalStakedHistoryResponse { samples })
+}
+
+/// Staked shares plus the voting power credited by `CompoundVotingPower` at `height`, minus
+/// the stake still warming up then
+fn voting_balance_at_height(deps: Deps, address: &Addr, height: u64) -> StdResult<Uint128> {
+    let staked = STAKED_BALANCES
+        .may_load_at_height(deps.storage, address, height)?
+        .unwrap_or_default();
+    let compounded = COMPOUNDED_BALANCES
+        .may_load_at_height(deps.storage, address, height)?
+        .unwrap_or_default();
+    Ok(staked
+        .checked_add(compounded)?
+        .saturating_sub(warming_up_at_height(deps, address, height)?))
+}
+
+/// Shares staked before `height` whose warmup hadn't ended by then. Stakes made at `height`
+/// itself are not part of the snapshot read there yet
+fn warming_up_at_height(deps: Deps, address: &Addr, height: u64) -> StdResult<Uint128> {
+    let warming = WARMING_BALANCES
+        .may_load_at_height(deps.storage, address, height)?
+        .unwrap_or_default();
+    // ended, but the staker hasn't staked or unstaked since to settle them
+    let unsettled = WARMUP_DEPOSITS
+        .prefix(address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |total, item| -> StdResult<_> {
+            let (_, deposit) = item?;
+            if deposit.ends_at <= height {
+                Ok(total.checked_add(deposit.amount)?)
+            } else {
+                Ok(total)
+            }
+        })?;
+    Ok(warming.checked_sub(unsettled)?)
+}
+
+pub fn query_staked_value(deps: Deps, env: Env, address: String) -> StdResult<StakedValueResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = total_holdings(deps, env.block.height)?;
+    let staked = STAKED_BALANCES
+        .load(deps.storage, &address)
+        .unwrap_or_default();
+    let total = STAKED_TOTAL.load(deps.storage).unwrap_or_default();
+    if balance == Uint128::zero() || staked == Uint128::zero() || total == Uint128::zero() {
+        Ok(StakedValueResponse {
+            value: Uint128::zero(),
+        })
+    } else {
+        // 256-bit intermediate, `staked * balance` may not fit in 128 bits
+        let value = staked
+            .checked_multiply_ratio(balance, total)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        Ok(StakedValueResponse { value })
+    }
+}
+
+pub fn query_total_value(deps: Deps, env: Env) -> StdResult<TotalValueResponse> {
+    Ok(TotalValueResponse {
+        total: total_holdings(deps, env.block.height)?,
+    })
+}
+
+/// Converts `amount` at the `numerator / denominator` rate, 1:1 while the pool is empty
+fn convert(amount: Uint128, numerator: Uint128, denominator: Uint128) -> StdResult<Uint128> {
+    if numerator.is_zero() || denominator.is_zero() {
+        return Ok(amount);
+    }
+    amount
+        .checked_multiply_ratio(numerator, denominator)
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+pub fn query_convert_to_assets(
+    deps: Deps,
+    env: Env,
+    shares: Uint128,
+) -> StdResult<ConvertToAssetsResponse> {
+    let balance = total_holdings(deps, env.block.height)?;
+    let staked_total = STAKED_TOTAL.load(deps.storage).unwrap_or_default();
+    let assets = convert(shares, balance, staked_total)?;
+    Ok(ConvertToAssetsResponse { assets })
+}
+
+pub fn query_convert_to_shares(
+    deps: Deps,
+    env: Env,
+    assets: Uint128,
+) -> StdResult<ConvertToSharesResponse> {
+    let balance = total_holdings(deps, env.block.height)?;
+    let staked_total = STAKED_TOTAL.load(deps.storage).unwrap_or_default();
+    let shares = convert(assets, staked_total, balance)?;
+    Ok(ConvertToSharesResponse { shares })
+}
+
+pub fn query_config(deps: Deps) -> StdResult<GetConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(GetConfigResponse {
+        admin: config.admin,
+        denom: config.denom,
         unstaking_duration: config.unstaking_duration,
+        reward_multiplier: config.reward_multiplier,
+        fund_allowlist: config.fund_allowlist,
+        reward_vesting_blocks: config.reward_vesting_blocks,
+        min_claim_amount: config.min_claim_amount,
+        warmup_period: config.warmup_period,
+        bonus_denom: config.bonus_denom,
+        emergency_claim_height: EMERGENCY_CLAIM_HEIGHT.may_load(deps.storage)?,
+        basket_denoms: config.basket_denoms,
     })
 }
 
+pub fn query_basket_composition(deps: Deps, env: Env) -> StdResult<BasketCompositionResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let balance = vested_balance(deps.storage, env.block.height)?;
+    let balances = std::iter::once(Ok(coin(balance.u128(), &config.denom)))
+        .chain(config.basket_denoms.iter().map(|basket| {
+            let held = BASKET_BALANCES
+                .may_load(deps.storage, &basket.denom)?
+                .unwrap_or_default();
+            Ok(coin(held.u128(), &basket.denom))
+        }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(BasketCompositionResponse { balances })
+}
+
+pub fn query_basket_claims(deps: Deps, address: String) -> StdResult<BasketClaimsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let claims = BASKET_CLAIMS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(BasketClaimsResponse { claims })
+}
+
 pub fn query_claims(deps: Deps, address: String) -> StdResult<ClaimsResponse> {
     CLAIMS.query_claims(deps, &deps.api.addr_validate(&address)?)
 }
+
+pub fn query_claim_status(deps: Deps, env: Env, address: String) -> StdResult<ClaimStatusResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let block = maturity_block(deps.storage, env.block.clone())?;
+
+    let claims = CLAIMS_BY_ADDRESS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|claim| {
+            let is_mature = claim.release_at.is_expired(&block);
+            let blocks_remaining = match claim.release_at {
+                Expiration::AtHeight(height) if !is_mature => Some(height - env.block.height),
+                _ => None,
+            };
+            ClaimStatusEntry {
+                amount: claim.amount,
+                release_at: claim.release_at,
+                is_mature,
+                blocks_remaining,
+            }
+        })
+        .collect();
+
+    Ok(ClaimStatusResponse { claims })
+}
+
+pub fn query_staker_lifetime(deps: Deps, address: String) -> StdResult<StakerLifetimeResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let totals = LIFETIME_TOTALS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(StakerLifetimeResponse {
+        lifetime_staked: totals.staked,
+        lifetime_claimed: totals.claimed,
+    })
+}
+
+pub fn query_slash_history(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SlashRecordsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let records = SLASH_RECORDS
+        .prefix(&address)
+        .range(
+            deps.storage,
+            None,
+            start_after.map(Bound::exclusive),
+            Order::Descending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (seq, record) = item?;
+            Ok(SlashRecordEntry {
+                address: address.clone(),
+                seq,
+                height: record.height,
+                record,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(SlashRecordsResponse { records })
+}
+
+pub fn query_top_stakers(deps: Deps, limit: u32) -> StdResult<TopStakersResponse> {
+    let limit = limit.min(MAX_TOP_STAKERS) as usize;
+    // min-heap holding the `limit` largest balances seen so far, ties keep the lower address
+    let mut top = BinaryHeap::with_capacity(limit + 1);
+    for item in STAKED_BALANCES.range(deps.storage, None, None, Order::Ascending) {
+        let (address, balance) = item?;
+        if balance.is_zero() {
+            continue;
+        }
+        top.push(Reverse((balance, Reverse(address))));
+        if top.len() > limit {
+            top.pop();
+        }
+    }
+
+    let stakers = top
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((balance, Reverse(address)))| StakerInfo { address, balance })
+        .collect();
+    Ok(TopStakersResponse { stakers })
+}
+
+pub fn query_unbonding_queue(
+    deps: Deps,
+    start_after: Option<(String, u32)>,
+    limit: Option<u32>,
+) -> StdResult<UnbondingQueueResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|(address, index)| -> StdResult<_> { Ok((deps.api.addr_validate(&address)?, index)) })
+        .transpose()?;
+    // inclusive, the cursor address may still have claims after `index`
+    let min = start_after
+        .as_ref()
+        .map(|(address, _)| Bound::inclusive(address));
+
+    let mut claims = Vec::with_capacity(limit);
+    for item in CLAIMS_BY_ADDRESS.range(deps.storage, min, None, Order::Ascending) {
+        let (address, address_claims) = item?;
+        let skip = match &start_after {
+            Some((start, index)) if *start == address => *index as usize + 1,
+            _ => 0,
+        };
+        for (index, claim) in address_claims.into_iter().enumerate().skip(skip) {
+            if claims.len() == limit {
+                return Ok(UnbondingQueueResponse { claims });
+            }
+            claims.push(UnbondingQueueEntry {
+                address: address.clone(),
+                index: index as u32,
+                amount: claim.amount,
+                release_at: claim.release_at,
+            });
+        }
+    }
+    Ok(UnbondingQueueResponse { claims })
+}
+
+pub fn query_forfeitable_claims_value(
+    deps: Deps,
+    address: String,
+) -> StdResult<ForfeitableClaimsValueResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let prices = basket_prices(deps, &config)?;
+    let basket_claims = BASKET_CLAIMS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let value = basket_claims
+        .iter()
+        .flat_map(|claim| claim.amounts.iter())
+        .try_fold(
+            pending_claims_value(deps.storage, &address)?,
+            |value, amount| {
+                let price = prices
+                    .iter()
+                    .find(|(denom, _)| denom == &amount.denom)
+                    .map_or(Decimal::zero(), |(_, price)| *price);
+                Ok::<_, StdError>(value.checked_add(value_in_denom(amount.amount, price)?)?)
+            },
+        )?;
+    Ok(ForfeitableClaimsValueResponse { value })
+}
+
+pub fn query_all_slash_records(
+    deps: Deps,
+    start_after: Option<(String, u64)>,
+    limit: Option<u32>,
+) -> StdResult<SlashRecordsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = match start_after {
+        Some((address, seq)) => Some((deps.api.addr_validate(&address)?, seq)),
+        None => None,
+    };
+    let records = SLASH_RECORDS
+        .range(
+            deps.storage,
+            start_after
+                .as_ref()
+                .map(|(address, seq)| Bound::exclusive((address, *seq))),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let ((address, seq), record) = item?;
+            Ok(SlashRecordEntry {
+                address,
+                seq,
+                height: record.height,
+                record,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(SlashRecordsResponse { records })
+}
@@ -1,4 +1,5 @@
-use cosmwasm_std::{Addr, StdError};
+use cosmwasm_std::{Addr, Decimal, StdError, Uint128};
+use cw_utils::{Duration, PaymentError};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -7,14 +8,44 @@ pub enum ContractError {
     Std(#[from] StdError),
     #[error("{0}")]
     Cw20Error(#[from] cw20_base::ContractError),
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
     #[error("Nothing to claim")]
     NothingToClaim {},
+    #[error("No pending claims to forfeit")]
+    NothingToForfeit {},
+    #[error("Nothing staked to compound")]
+    NothingToCompound {},
     #[error("Invalid token")]
     InvalidToken { received: Addr, expected: Addr },
     #[error("Unauthorized")]
     Unauthorized { received: Addr, expected: Addr },
+    #[error("Claim of {received} is below the minimum of {min}")]
+    ClaimAmountTooSmall { min: Uint128, received: Uint128 },
     #[error("Too many outstanding claims. Claim some tokens before unstaking more.")]
     TooManyClaims {},
     #[error("No admin configured")]
     NoAdminConfigured {},
+    #[error("Slash fraction must be between 0 and 1")]
+    InvalidSlashFraction {},
+    #[error("Unstaking duration cannot exceed {max}")]
+    UnstakingDurationTooLong { max: Duration },
+    #[error("Warmup period cannot exceed {max} blocks")]
+    WarmupPeriodTooLong { max: u64 },
+    #[error("Reward multiplier must be between 1.0 and {max}")]
+    InvalidRewardMultiplier { max: Decimal },
+    #[error("Denom {denom} is not part of the staking basket")]
+    NotInBasket { denom: String },
+    #[error("Invalid staking basket: {reason}")]
+    InvalidBasket { reason: String },
+    #[error("Bonus rewards cannot be paid in {denom}")]
+    InvalidBonusDenom { denom: String },
+    #[error("Bonus rewards are already paid in {denom}")]
+    BonusDenomLocked { denom: String },
+    #[error("Nothing staked to share the bonus with")]
+    NoStakers {},
+    #[error("Reward reserve ({reserve}) cannot cover boosted reward ({required})")]
+    InsufficientRewardReserve { required: Uint128, reserve: Uint128 },
+    #[error("The contract has no delegations to collect rewards from")]
+    NoDelegations {},
 }
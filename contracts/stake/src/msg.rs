@@ -1,14 +1,23 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
 pub use cw_controllers::ClaimsResponse;
 pub use cw_utils::Duration;
+use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::state::{BasketClaim, BasketDenom, SlashRecord};
+
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct InstantiateMsg {
     pub admin: Option<Addr>,
     pub denom: String,
     pub unstaking_duration: Option<Duration>,
+    /// Additional denoms `Stake` accepts, see `Config::basket_denoms`
+    #[serde(default)]
+    pub basket_denoms: Vec<BasketDenom>,
+    /// Denom paid out through `ClaimBonus`, see `Config::bonus_denom`
+    #[serde(default)]
+    pub bonus_denom: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -19,10 +28,54 @@ pub enum ExecuteMsg {
         amount: Uint128,
     },
     Fund {},
+    /// Add the attached staking denom to the staked balance like `Fund`, without the
+    /// `reward_multiplier` boost. The DAO hands confiscated deposits over with it
+    FundUnboosted {},
+    FundRewardReserve {},
     Claim {},
+    /// Distribute the attached `Config::bonus_denom` over the current stakers by share.
+    /// Restricted to the fund allowlist like `Fund`
+    FundBonus {},
+    /// Pay out the bonus accrued by the sender's shares
+    ClaimBonus {},
+    /// Withdraw the staking rewards of every delegation held by the contract (superfluid
+    /// staked OSMO) and add them to the staked balance like `FundUnboosted`
+    CollectSuperfluidRewards {},
+    /// Overwrite `BALANCE` with the balance reported by `BalanceDrift` (admin only)
+    ReconcileBalance {},
+    /// Give up every pending claim of the sender and stake its amount again right away.
+    /// Basket claims go back into the basket, priced like a basket `Stake`
+    ForfeitClaims {},
+    /// Credit the sender's accrued rewards as voting power (see `StakedValue`)
+    CompoundVotingPower {},
+    /// Treat all claims as matured from `height` on (admin only)
+    SetEmergencyClaimHeight {
+        height: u64,
+    },
+    ClearEmergencyClaimHeight {},
+    Slash {
+        address: String,
+        fraction: Decimal,
+        reason: Option<String>,
+    },
     UpdateConfig {
         admin: Option<Addr>,
         duration: Option<Duration>,
+        reward_multiplier: Option<Decimal>,
+        /// Replaces the addresses allowed to `Fund`, the current list is kept when omitted
+        fund_allowlist: Option<Vec<Addr>>,
+        /// Drops the allowlist, letting anyone `Fund` again. Overrides `fund_allowlist`
+        #[serde(default)]
+        clear_fund_allowlist: bool,
+        reward_vesting_blocks: Option<u64>,
+        /// Smallest claim `Unstake` may create, `Claim` drops matured claims below it as dust
+        min_claim_amount: Option<Uint128>,
+        /// Blocks before new stake counts towards voting power, at most `MAX_WARMUP_PERIOD`.
+        /// Stake made before the change keeps its warmup
+        warmup_period: Option<u64>,
+        /// Denom `FundBonus` accepts, cannot be changed once a bonus was funded
+        #[serde(default)]
+        bonus_denom: Option<String>,
     },
 }
 
@@ -33,9 +86,21 @@ pub enum QueryMsg {
         address: String,
         height: Option<u64>,
     },
+    StakedBalancesAtHeight {
+        addresses: Vec<String>,
+        height: Option<u64>,
+    },
     TotalStakedAtHeight {
         height: Option<u64>,
     },
+    /// `TotalStakedAtHeight` (compounded voting power included) sampled every `step` blocks from
+    /// `from_height` to `to_height` (default: current height). `step` defaults to a tenth
+    /// of the range, at most 100 samples are returned
+    TotalStakedHistory {
+        from_height: u64,
+        to_height: Option<u64>,
+        step: Option<u64>,
+    },
     StakedValue {
         address: String,
     },
@@ -44,6 +109,71 @@ pub enum QueryMsg {
     Claims {
         address: String,
     },
+    StakerLifetime {
+        address: String,
+    },
+    /// Most recent first, `start_after` is the `seq` of the last entry of the previous page
+    SlashHistory {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Ordered by address, then `seq`
+    AllSlashRecords {
+        start_after: Option<(String, u64)>,
+        limit: Option<u32>,
+    },
+    /// Held amount of the staking denom and every basket denom
+    BasketComposition {},
+    /// Basket tokens still waiting for the unstaking duration
+    BasketClaims {
+        address: String,
+    },
+    /// Value in the staking denom currently backing `shares`, basket tokens at spot price
+    ConvertToAssets {
+        shares: Uint128,
+    },
+    /// Shares that staking `assets` worth of value would mint right now
+    ConvertToShares {
+        assets: Uint128,
+    },
+    /// Addresses with the most staked shares, highest first. `limit` is capped at 50.
+    /// Walks every staker, so the gas cost grows with the number of stakers
+    TopStakers {
+        limit: u32,
+    },
+    /// Every outstanding claim, ordered by address then claim index.
+    /// `start_after` is the `(address, index)` of the last entry of the previous page
+    UnbondingQueue {
+        start_after: Option<(String, u32)>,
+        limit: Option<u32>,
+    },
+    /// Claims of `address`, telling which ones can be claimed right now
+    ClaimStatus {
+        address: String,
+    },
+    /// Value in the staking denom `ForfeitClaims` would re-stake for `address`, basket claims
+    /// at spot price
+    ForfeitableClaimsValue {
+        address: String,
+    },
+    /// Bonus `ClaimBonus` would pay `address` right now
+    PendingBonus {
+        address: String,
+    },
+    /// Recorded staked balance against the staking denom actually held for stakers
+    BalanceDrift {},
+    /// Running total of the rewards credited by `CollectSuperfluidRewards`
+    TotalSuperfluidRewardsCollected {},
+}
+
+/// `data` of a `Stake` response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakeResponse {
+    pub shares_minted: Uint128,
+    /// Staked shares of the sender after the stake
+    pub new_balance: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -53,6 +183,20 @@ pub struct StakedBalanceAtHeightResponse {
     pub height: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AddressStakedBalance {
+    pub address: Addr,
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakedBalancesAtHeightResponse {
+    pub balances: Vec<AddressStakedBalance>,
+    pub height: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct TotalStakedAtHeightResponse {
@@ -60,6 +204,13 @@ pub struct TotalStakedAtHeightResponse {
     pub height: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TotalStakedHistoryResponse {
+    /// (height, total staked)
+    pub samples: Vec<(u64, Uint128)>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct StakedValueResponse {
@@ -72,10 +223,144 @@ pub struct TotalValueResponse {
     pub total: Uint128,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TotalSuperfluidRewardsCollectedResponse {
+    pub total: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ForfeitableClaimsValueResponse {
+    pub value: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BalanceDriftResponse {
+    pub recorded_balance: Uint128,
+    /// Bank balance minus outstanding claims, the reward reserve and unvested rewards
+    pub actual_balance: Uint128,
+    /// `actual_balance - recorded_balance`
+    pub drift: i128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingBonusResponse {
+    /// `None` until the first `FundBonus`
+    pub denom: Option<String>,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ClaimStatusEntry {
+    pub amount: Uint128,
+    pub release_at: Expiration,
+    /// `Claim` would pay it out now, emergency claim height included
+    pub is_mature: bool,
+    /// Blocks left until a height based claim matures, `None` once mature or for time based ones
+    pub blocks_remaining: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ClaimStatusResponse {
+    pub claims: Vec<ClaimStatusEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct UnbondingQueueEntry {
+    pub address: Addr,
+    /// Position among the address' claims
+    pub index: u32,
+    pub amount: Uint128,
+    pub release_at: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct UnbondingQueueResponse {
+    pub claims: Vec<UnbondingQueueEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakerInfo {
+    pub address: Addr,
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TopStakersResponse {
+    pub stakers: Vec<StakerInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConvertToAssetsResponse {
+    pub assets: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConvertToSharesResponse {
+    pub shares: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct GetConfigResponse {
     pub admin: Option<Addr>,
     pub denom: String,
     pub unstaking_duration: Option<Duration>,
+    pub reward_multiplier: Decimal,
+    pub fund_allowlist: Option<Vec<Addr>>,
+    pub reward_vesting_blocks: u64,
+    pub min_claim_amount: Uint128,
+    pub warmup_period: u64,
+    pub bonus_denom: Option<String>,
+    pub emergency_claim_height: Option<u64>,
+    pub basket_denoms: Vec<BasketDenom>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SlashRecordEntry {
+    pub address: Addr,
+    /// position in the staker's slash history
+    pub seq: u64,
+    pub height: u64,
+    pub record: SlashRecord,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SlashRecordsResponse {
+    pub records: Vec<SlashRecordEntry>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakerLifetimeResponse {
+    pub lifetime_staked: Uint128,
+    pub lifetime_claimed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BasketCompositionResponse {
+    pub balances: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BasketClaimsResponse {
+    pub claims: Vec<BasketClaim>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
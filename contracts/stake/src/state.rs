@@ -1,19 +1,52 @@
-use cosmwasm_std::{Addr, Uint128};
-use cw_controllers::Claims;
-use cw_storage_plus::{Item, SnapshotItem, SnapshotMap, Strategy};
-use cw_utils::Duration;
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
+use cw_controllers::{Claim, Claims};
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
+use cw_utils::{Duration, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Config {
     pub admin: Option<Addr>,
+    /// Immutable after instantiation: every staked balance, claim and reward is
+    /// denominated in it. Multi-denom support would have to add denoms, never replace this one
     pub denom: String,
     pub unstaking_duration: Option<Duration>,
+    /// Multiplier applied to every `Fund` amount. Anything above 1.0 is paid from `REWARD_RESERVE`
+    #[serde(default = "Decimal::one")]
+    pub reward_multiplier: Decimal,
+    /// When set, only these addresses (and the admin) may call `Fund`
+    pub fund_allowlist: Option<Vec<Addr>>,
+    /// `Fund` rewards are released linearly over this many blocks (0 = instant)
+    #[serde(default)]
+    pub reward_vesting_blocks: u64,
+    /// Extra tokens accepted by `Stake` next to `denom`, valued through their pool's spot price.
+    /// Fixed at instantiation like `denom`
+    #[serde(default)]
+    pub basket_denoms: Vec<BasketDenom>,
+    /// Smallest claim `Unstake` may create, so the `MAX_CLAIMS` slots can't be filled with dust
+    #[serde(default)]
+    pub min_claim_amount: Uint128,
+    /// New stake only counts towards voting power this many blocks after it was made (0 = off)
+    #[serde(default)]
+    pub warmup_period: u64,
+    /// Denom `FundBonus` accepts. Set by the admin, locked once a bonus has been funded
+    #[serde(default)]
+    pub bonus_denom: Option<String>,
+}
+
+/// A basket token and the pool pricing it against the staking denom
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BasketDenom {
+    pub denom: String,
+    pub pool_id: u64,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// `EveryBlock` keeps a changelog entry for every block that touched a balance, so any past
+/// height can be read back. A read at height `h` sees the value as of the start of block `h`,
+/// i.e. the last change made at or before `h - 1`
 pub const STAKED_BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
     "staked_balances",
     "staked_balance__checkpoints",
@@ -21,6 +54,7 @@ pub const STAKED_BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
     Strategy::EveryBlock,
 );
 
+/// Snapshotted like `STAKED_BALANCES`, backing `TotalStakedAtHeight`
 pub const STAKED_TOTAL: SnapshotItem<Uint128> = SnapshotItem::new(
     "total_staked",
     "total_staked__checkpoints",
@@ -28,9 +62,157 @@ pub const STAKED_TOTAL: SnapshotItem<Uint128> = SnapshotItem::new(
     Strategy::EveryBlock,
 );
 
+/// Voting weight credited by `CompoundVotingPower` on top of the staked shares, so
+/// accrued rewards count in governance. Only reported by the balance queries, never unstakable
+pub const COMPOUNDED_BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "compounded_balances",
+    "compounded_balance__checkpoints",
+    "compounded_balance__changelog",
+    Strategy::EveryBlock,
+);
+
+pub const COMPOUNDED_TOTAL: SnapshotItem<Uint128> = SnapshotItem::new(
+    "compounded_total",
+    "compounded_total__checkpoints",
+    "compounded_total__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Longest accepted `Config::unstaking_duration`, so a typo can't lock every staker in.
+/// About 90 days either way, assuming 6 second blocks for the height variant
+pub const MAX_UNSTAKING_DURATION_HEIGHT: u64 = 1_296_000;
+pub const MAX_UNSTAKING_DURATION_TIME: u64 = 90 * 24 * 60 * 60;
+
+/// Longest accepted `Config::warmup_period`
+pub const MAX_WARMUP_PERIOD: u64 = MAX_UNSTAKING_DURATION_HEIGHT;
+
+/// Highest accepted `Config::reward_multiplier`, in percent
+pub const MAX_REWARD_MULTIPLIER_PERCENT: u64 = 1_000;
+
 /// The maximum number of claims that may be outstanding.
 pub const MAX_CLAIMS: u64 = 100;
 
 pub const CLAIMS: Claims = Claims::new("claims");
+/// Read-only view of the `CLAIMS` namespace, `Claims` itself can't be iterated
+pub const CLAIMS_BY_ADDRESS: Map<&Addr, Vec<Claim>> = Map::new("claims");
+/// Sum of every outstanding `CLAIMS` entry, so it never has to be added up from the whole map
+pub const CLAIMS_TOTAL: Item<Uint128> = Item::new("claims_total");
+
+/// From this height on, every outstanding claim can be withdrawn regardless of its
+/// release date. Set by the admin to let stakers exit in an emergency
+pub const EMERGENCY_CLAIM_HEIGHT: Item<u64> = Item::new("emergency_claim_height");
 
 pub const BALANCE: Item<Uint128> = Item::new("balance");
+
+/// Held amount of every basket denom, `BALANCE` keeps tracking the staking denom
+pub const BASKET_BALANCES: Map<&str, Uint128> = Map::new("basket_balances");
+
+/// Basket tokens waiting for the unstaking duration, `CLAIMS` only covers the staking denom
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BasketClaim {
+    pub amounts: Vec<Coin>,
+    pub release_at: Expiration,
+}
+
+pub const BASKET_CLAIMS: Map<&Addr, Vec<BasketClaim>> = Map::new("basket_claims");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SlashRecord {
+    pub height: u64,
+    pub slasher: Addr,
+    pub amount_before: Uint128,
+    pub amount_after: Uint128,
+    pub fraction: Decimal,
+    pub reason: Option<String>,
+}
+
+/// Shares minted by a stake and the height they start counting as voting power from
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct WarmupDeposit {
+    pub amount: Uint128,
+    pub ends_at: u64,
+}
+
+/// (staker, stake height) -> deposit still warming up. The end is fixed when staking, so
+/// changing `warmup_period` later doesn't rewrite past voting power. Ended deposits are settled
+/// into `WARMING_BALANCES` and dropped on the staker's next stake or unstake
+pub const WARMUP_DEPOSITS: Map<(&Addr, u64), WarmupDeposit> = Map::new("warmup_deposits");
+
+/// End height -> unsettled `WARMUP_DEPOSITS` of every staker ending there, settled into
+/// `WARMING_TOTAL` by the next stake or unstake of anyone
+pub const WARMUP_ENDS: Map<u64, Uint128> = Map::new("warmup_ends");
+
+/// Shares still warming up, left out of the voting power. A settled deposit is written at the
+/// block before it ended, so past heights read back exactly; reads still have to take off
+/// deposits that ended but are not settled yet
+pub const WARMING_BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "warming_balances",
+    "warming_balance__checkpoints",
+    "warming_balance__changelog",
+    Strategy::EveryBlock,
+);
+
+pub const WARMING_TOTAL: SnapshotItem<Uint128> = SnapshotItem::new(
+    "warming_total",
+    "warming_total__checkpoints",
+    "warming_total__changelog",
+    Strategy::EveryBlock,
+);
+
+/// (staker, per-staker sequence number) -> slash event, several slashes may share a block
+pub const SLASH_RECORDS: Map<(&Addr, u64), SlashRecord> = Map::new("slash_records");
+
+/// Slashes recorded per staker so far, i.e. the next sequence number
+pub const SLASH_COUNTS: Map<&Addr, u64> = Map::new("slash_counts");
+
+/// Monotonic per-address counters, denominated in the staking denom
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct LifetimeTotals {
+    pub staked: Uint128,
+    /// Includes immediate payouts from `Unstake` when no unstaking duration is set
+    pub claimed: Uint128,
+}
+
+pub const LIFETIME_TOTALS: Map<&Addr, LifetimeTotals> = Map::new("lifetime_totals");
+
+/// Fund rewards that have not been released into `BALANCE` yet. Every `Fund` vests on its own
+/// over the `reward_vesting_blocks` set at the time, `rate` is what they release per block together
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct PendingRewards {
+    pub amount: Uint128,
+    pub rate: Decimal,
+    pub last_height: u64,
+}
+
+pub const PENDING_REWARDS: Item<PendingRewards> = Item::new("pending_rewards");
+
+/// End height -> per block release of the `Fund` rewards vesting until then
+pub const REWARD_VESTING_ENDS: Map<u64, Decimal> = Map::new("reward_vesting_ends");
+
+/// Pre-funded tokens used to cover boosted rewards when `reward_multiplier` > 1.0
+pub const REWARD_RESERVE: Item<Uint128> = Item::new("reward_reserve");
+
+/// Staking rewards withdrawn from the contract's own delegations and credited to `BALANCE`
+pub const SUPERFLUID_REWARDS_COLLECTED: Item<Uint128> = Item::new("superfluid_rewards_collected");
+
+/// Held staking denom right before a `CollectSuperfluidRewards` withdrawal, cleared by its reply
+pub const PRE_COLLECT_BALANCE: Item<Uint128> = Item::new("pre_collect_balance");
+
+/// Second reward token paid out through `ClaimBonus`, apart from the compounding `Fund` rewards.
+/// `index` is the bonus paid per staked share so far, in `Config::bonus_denom` at the first `FundBonus`
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BonusRewards {
+    pub denom: String,
+    pub index: Decimal,
+}
+
+pub const BONUS_REWARDS: Item<BonusRewards> = Item::new("bonus_rewards");
+
+/// Bonus owed to a staker, settled up to `index` whenever their shares change
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct BonusAccrual {
+    pub index: Decimal,
+    pub pending: Uint128,
+}
+
+pub const BONUS_ACCRUALS: Map<&Addr, BonusAccrual> = Map::new("bonus_accruals");
@@ -1,20 +1,35 @@
+use std::marker::PhantomData;
+
 use anyhow::Result as AnyResult;
-use cosmwasm_std::testing::mock_info;
-use cosmwasm_std::{coin, coins, Addr, BankMsg, Coin, Uint128};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    coin, coins, from_binary, Addr, BankMsg, Coin, Decimal, DepsMut, DistributionMsg,
+    FullDelegation, OwnedDeps, Reply, StdResult, SubMsg, SubMsgResponse, SubMsgResult, Uint128,
+};
 use cw_controllers::Claim;
 use cw_multi_test::{
     next_block, AppResponse, BankSudo, Contract, ContractWrapper, Executor, SudoMsg,
 };
 use cw_utils::Expiration::AtHeight;
+use cw_utils::PaymentError;
 use osmo_bindings::{OsmosisMsg, OsmosisQuery};
-use osmo_bindings_test::OsmosisApp;
+use osmo_bindings_test::{OsmosisApp, Pool};
 
+use crate::contract::COLLECT_SUPERFLUID_REWARDS_REPLY_ID;
 use crate::msg::{
-    ClaimsResponse, Duration, ExecuteMsg, GetConfigResponse, QueryMsg,
-    StakedBalanceAtHeightResponse, StakedValueResponse, TotalStakedAtHeightResponse,
-    TotalValueResponse,
+    AddressStakedBalance, BalanceDriftResponse, BasketClaimsResponse, BasketCompositionResponse,
+    ClaimStatusEntry, ClaimStatusResponse, ClaimsResponse, ConvertToAssetsResponse,
+    ConvertToSharesResponse, Duration, ExecuteMsg, ForfeitableClaimsValueResponse,
+    GetConfigResponse, PendingBonusResponse, QueryMsg, SlashRecordsResponse, StakeResponse,
+    StakedBalanceAtHeightResponse, StakedBalancesAtHeightResponse, StakedValueResponse,
+    StakerLifetimeResponse, TopStakersResponse, TotalStakedAtHeightResponse,
+    TotalStakedHistoryResponse, TotalSuperfluidRewardsCollectedResponse, TotalValueResponse,
+    UnbondingQueueResponse,
+};
+use crate::state::{
+    BasketDenom, CLAIMS_BY_ADDRESS, CLAIMS_TOTAL, MAX_CLAIMS, MAX_REWARD_MULTIPLIER_PERCENT,
+    MAX_UNSTAKING_DURATION_HEIGHT, MAX_UNSTAKING_DURATION_TIME, MAX_WARMUP_PERIOD, WARMUP_DEPOSITS,
 };
-use crate::state::MAX_CLAIMS;
 use crate::ContractError;
 
 const DENOM: &str = "denom";
@@ -43,6 +58,8 @@ fn mock_staking(app: &mut OsmosisApp, unstaking_duration: Option<Duration>) -> S
         admin: Some(Addr::unchecked(ADDR_OWNER)),
         denom: DENOM.to_string(),
         unstaking_duration,
+        basket_denoms: vec![],
+        bonus_denom: None,
     };
     let address = app
         .instantiate_contract(
@@ -103,6 +120,20 @@ impl Stake {
         )
     }
 
+    pub fn stake_basket(
+        &self,
+        app: &mut OsmosisApp,
+        sender: &Addr,
+        funds: &[Coin],
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            self.address.clone(),
+            &ExecuteMsg::Stake {},
+            funds,
+        )
+    }
+
     pub fn fund(
         &self,
         app: &mut OsmosisApp,
@@ -117,6 +148,34 @@ impl Stake {
         )
     }
 
+    pub fn fund_unboosted(
+        &self,
+        app: &mut OsmosisApp,
+        sender: &Addr,
+        amount: Coin,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            self.address.clone(),
+            &ExecuteMsg::FundUnboosted {},
+            &[amount],
+        )
+    }
+
+    pub fn fund_reward_reserve(
+        &self,
+        app: &mut OsmosisApp,
+        sender: &Addr,
+        amount: Coin,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            self.address.clone(),
+            &ExecuteMsg::FundRewardReserve {},
+            &[amount],
+        )
+    }
+
     pub fn unstake(
         &self,
         app: &mut OsmosisApp,
@@ -140,17 +199,120 @@ impl Stake {
         )
     }
 
+    pub fn fund_bonus(
+        &self,
+        app: &mut OsmosisApp,
+        sender: &Addr,
+        amount: Coin,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            self.address.clone(),
+            &ExecuteMsg::FundBonus {},
+            &[amount],
+        )
+    }
+
+    pub fn claim_bonus(&self, app: &mut OsmosisApp, sender: &Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            self.address.clone(),
+            &ExecuteMsg::ClaimBonus {},
+            &[],
+        )
+    }
+
+    pub fn reconcile_balance(&self, app: &mut OsmosisApp, sender: &Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            self.address.clone(),
+            &ExecuteMsg::ReconcileBalance {},
+            &[],
+        )
+    }
+
+    pub fn forfeit_claims(&self, app: &mut OsmosisApp, sender: &Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            self.address.clone(),
+            &ExecuteMsg::ForfeitClaims {},
+            &[],
+        )
+    }
+
+    pub fn set_emergency_claim_height(
+        &self,
+        app: &mut OsmosisApp,
+        sender: &Addr,
+        height: Option<u64>,
+    ) -> AnyResult<AppResponse> {
+        let msg = match height {
+            Some(height) => ExecuteMsg::SetEmergencyClaimHeight { height },
+            None => ExecuteMsg::ClearEmergencyClaimHeight {},
+        };
+        app.execute_contract(sender.clone(), self.address.clone(), &msg, &[])
+    }
+
+    pub fn compound_voting_power(
+        &self,
+        app: &mut OsmosisApp,
+        sender: &Addr,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            self.address.clone(),
+            &ExecuteMsg::CompoundVotingPower {},
+            &[],
+        )
+    }
+
+    pub fn slash(
+        &self,
+        app: &mut OsmosisApp,
+        sender: &Addr,
+        address: &str,
+        fraction: Decimal,
+        reason: Option<&str>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            self.address.clone(),
+            &ExecuteMsg::Slash {
+                address: address.to_string(),
+                fraction,
+                reason: reason.map(|r| r.to_string()),
+            },
+            &[],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn update_config(
         &self,
         app: &mut OsmosisApp,
         sender: &Addr,
         admin: Option<Addr>,
         duration: Option<Duration>,
+        reward_multiplier: Option<Decimal>,
+        fund_allowlist: Option<Vec<Addr>>,
+        reward_vesting_blocks: Option<u64>,
+        min_claim_amount: Option<Uint128>,
+        warmup_period: Option<u64>,
     ) -> AnyResult<AppResponse> {
         app.execute_contract(
             sender.clone(),
             self.address.clone(),
-            &ExecuteMsg::UpdateConfig { admin, duration },
+            &ExecuteMsg::UpdateConfig {
+                admin,
+                duration,
+                reward_multiplier,
+                fund_allowlist,
+                clear_fund_allowlist: false,
+                reward_vesting_blocks,
+                min_claim_amount,
+                warmup_period,
+                bonus_denom: None,
+            },
             &[],
         )
     }
@@ -174,6 +336,23 @@ impl Stake {
             .unwrap()
     }
 
+    pub fn query_staked_balances_at_height(
+        &self,
+        app: &OsmosisApp,
+        addresses: Vec<&str>,
+        height: Option<u64>,
+    ) -> StakedBalancesAtHeightResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::StakedBalancesAtHeight {
+                    addresses: addresses.into_iter().map(|a| a.to_string()).collect(),
+                    height,
+                },
+            )
+            .unwrap()
+    }
+
     pub fn query_total_staked_at_height(
         &self,
         app: &OsmosisApp,
@@ -184,6 +363,28 @@ impl Stake {
             .unwrap()
     }
 
+    pub fn query_total_staked_history(
+        &self,
+        app: &OsmosisApp,
+        from_height: u64,
+        to_height: Option<u64>,
+        step: Option<u64>,
+    ) -> StdResult<Vec<(u64, u128)>> {
+        let res: TotalStakedHistoryResponse = app.wrap().query_wasm_smart(
+            &self.address,
+            &QueryMsg::TotalStakedHistory {
+                from_height,
+                to_height,
+                step,
+            },
+        )?;
+        Ok(res
+            .samples
+            .into_iter()
+            .map(|(height, total)| (height, total.u128()))
+            .collect())
+    }
+
     pub fn query_staked_value(
         &self,
         app: &OsmosisApp,
@@ -205,12 +406,186 @@ impl Stake {
             .unwrap()
     }
 
+    pub fn query_convert_to_assets(&self, app: &OsmosisApp, shares: u128) -> Uint128 {
+        let res: ConvertToAssetsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::ConvertToAssets {
+                    shares: Uint128::new(shares),
+                },
+            )
+            .unwrap();
+        res.assets
+    }
+
+    pub fn query_convert_to_shares(&self, app: &OsmosisApp, assets: u128) -> Uint128 {
+        let res: ConvertToSharesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::ConvertToShares {
+                    assets: Uint128::new(assets),
+                },
+            )
+            .unwrap();
+        res.shares
+    }
+
+    pub fn query_top_stakers(&self, app: &OsmosisApp, limit: u32) -> Vec<(String, u128)> {
+        let res: TopStakersResponse = app
+            .wrap()
+            .query_wasm_smart(&self.address, &QueryMsg::TopStakers { limit })
+            .unwrap();
+        res.stakers
+            .into_iter()
+            .map(|staker| (staker.address.to_string(), staker.balance.u128()))
+            .collect()
+    }
+
     pub fn query_config(&self, app: &OsmosisApp) -> GetConfigResponse {
         app.wrap()
             .query_wasm_smart(&self.address, &QueryMsg::GetConfig {})
             .unwrap()
     }
 
+    pub fn query_staker_lifetime(
+        &self,
+        app: &OsmosisApp,
+        address: impl Into<String>,
+    ) -> StakerLifetimeResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::StakerLifetime {
+                    address: address.into(),
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_slash_history(
+        &self,
+        app: &OsmosisApp,
+        address: impl Into<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> SlashRecordsResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::SlashHistory {
+                    address: address.into(),
+                    start_after,
+                    limit,
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_all_slash_records(
+        &self,
+        app: &OsmosisApp,
+        start_after: Option<(&str, u64)>,
+        limit: Option<u32>,
+    ) -> SlashRecordsResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::AllSlashRecords {
+                    start_after: start_after.map(|(address, seq)| (address.to_string(), seq)),
+                    limit,
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_basket_composition(&self, app: &OsmosisApp) -> BasketCompositionResponse {
+        app.wrap()
+            .query_wasm_smart(&self.address, &QueryMsg::BasketComposition {})
+            .unwrap()
+    }
+
+    pub fn query_basket_claims(
+        &self,
+        app: &OsmosisApp,
+        address: impl Into<String>,
+    ) -> BasketClaimsResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::BasketClaims {
+                    address: address.into(),
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_unbonding_queue(
+        &self,
+        app: &OsmosisApp,
+        start_after: Option<(&str, u32)>,
+        limit: Option<u32>,
+    ) -> Vec<(String, u32, u128)> {
+        let res: UnbondingQueueResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::UnbondingQueue {
+                    start_after: start_after.map(|(address, index)| (address.to_string(), index)),
+                    limit,
+                },
+            )
+            .unwrap();
+        res.claims
+            .into_iter()
+            .map(|claim| (claim.address.to_string(), claim.index, claim.amount.u128()))
+            .collect()
+    }
+
+    pub fn query_claim_status(&self, app: &OsmosisApp, address: &str) -> Vec<ClaimStatusEntry> {
+        let res: ClaimStatusResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::ClaimStatus {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.claims
+    }
+
+    pub fn query_forfeitable_claims_value(&self, app: &OsmosisApp, address: &str) -> Uint128 {
+        let res: ForfeitableClaimsValueResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::ForfeitableClaimsValue {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.value
+    }
+
+    pub fn query_balance_drift(&self, app: &OsmosisApp) -> BalanceDriftResponse {
+        app.wrap()
+            .query_wasm_smart(&self.address, &QueryMsg::BalanceDrift {})
+            .unwrap()
+    }
+
+    pub fn query_pending_bonus(&self, app: &OsmosisApp, address: &str) -> PendingBonusResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &self.address,
+                &QueryMsg::PendingBonus {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
     pub fn query_claims(&self, app: &OsmosisApp, address: impl Into<String>) -> ClaimsResponse {
         app.wrap()
             .query_wasm_smart(
@@ -234,47 +609,168 @@ fn test_initialize() {
 }
 
 #[test]
-fn test_update_config() {
+fn test_max_unstaking_duration() {
     let mut app = mock_app();
-    let staking = setup_test_case(&mut app, vec![], None);
-
-    // success - happy path
-    let info = mock_info(ADDR_OWNER, &[]);
-    let _res = staking
-        .update_config(
-            &mut app,
-            &info.sender,
-            Some(Addr::unchecked(ADDR_OWNER2)),
-            Some(Duration::Height(100)),
+    let staking_code_id = app.store_code(mock_staking_code());
+    let mut instantiate = |unstaking_duration| {
+        let msg = crate::msg::InstantiateMsg {
+            admin: Some(Addr::unchecked(ADDR_OWNER)),
+            denom: DENOM.to_string(),
+            unstaking_duration: Some(unstaking_duration),
+            basket_denoms: vec![],
+            bonus_denom: None,
+        };
+        app.instantiate_contract(
+            staking_code_id,
+            Addr::unchecked(ADDR1),
+            &msg,
+            &[],
+            "staking",
+            None,
         )
-        .unwrap();
+    };
+
+    instantiate(Duration::Height(MAX_UNSTAKING_DURATION_HEIGHT)).unwrap();
+    instantiate(Duration::Time(MAX_UNSTAKING_DURATION_TIME)).unwrap();
+    let err = instantiate(Duration::Height(MAX_UNSTAKING_DURATION_HEIGHT + 1)).unwrap_err();
     assert_eq!(
-        staking.query_config(&app),
-        GetConfigResponse {
-            admin: Some(Addr::unchecked(ADDR_OWNER2)),
-            denom: DENOM.to_string(),
-            unstaking_duration: Some(Duration::Height(100))
-        }
+        ContractError::UnstakingDurationTooLong {
+            max: Duration::Height(MAX_UNSTAKING_DURATION_HEIGHT)
+        },
+        err.downcast().unwrap()
     );
-
-    // success - remove all
-    let info = mock_info(ADDR_OWNER2, &[]);
-    let _res = staking
-        .update_config(&mut app, &info.sender, None, None)
-        .unwrap();
+    let err = instantiate(Duration::Time(MAX_UNSTAKING_DURATION_TIME + 1)).unwrap_err();
     assert_eq!(
-        staking.query_config(&app),
-        GetConfigResponse {
-            admin: None,
-            denom: DENOM.to_string(),
-            unstaking_duration: None
-        }
+        ContractError::UnstakingDurationTooLong {
+            max: Duration::Time(MAX_UNSTAKING_DURATION_TIME)
+        },
+        err.downcast().unwrap()
     );
 
-    // fail
+    // same bound when updating
+    let staking = mock_staking(&mut app, None);
+    let owner = Addr::unchecked(ADDR_OWNER);
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            Some(Duration::Height(MAX_UNSTAKING_DURATION_HEIGHT)),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    let err = staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            Some(Duration::Height(MAX_UNSTAKING_DURATION_HEIGHT + 1)),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnstakingDurationTooLong {
+            max: Duration::Height(MAX_UNSTAKING_DURATION_HEIGHT)
+        },
+        err.downcast().unwrap()
+    );
+    assert_eq!(
+        staking.query_config(&app).unstaking_duration,
+        Some(Duration::Height(MAX_UNSTAKING_DURATION_HEIGHT))
+    );
+}
+
+#[test]
+fn test_update_config() {
+    let mut app = mock_app();
+    let staking = setup_test_case(&mut app, vec![], None);
+
+    // success - happy path
+    let info = mock_info(ADDR_OWNER, &[]);
+    let _res = staking
+        .update_config(
+            &mut app,
+            &info.sender,
+            Some(Addr::unchecked(ADDR_OWNER2)),
+            Some(Duration::Height(100)),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        staking.query_config(&app),
+        GetConfigResponse {
+            admin: Some(Addr::unchecked(ADDR_OWNER2)),
+            denom: DENOM.to_string(),
+            unstaking_duration: Some(Duration::Height(100)),
+            reward_multiplier: Decimal::one(),
+            fund_allowlist: None,
+            reward_vesting_blocks: 0,
+            min_claim_amount: Uint128::zero(),
+            warmup_period: 0,
+            emergency_claim_height: None,
+            basket_denoms: vec![],
+            bonus_denom: None,
+        }
+    );
+
+    // success - remove all
+    let info = mock_info(ADDR_OWNER2, &[]);
+    let _res = staking
+        .update_config(
+            &mut app,
+            &info.sender,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        staking.query_config(&app),
+        GetConfigResponse {
+            admin: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_multiplier: Decimal::one(),
+            fund_allowlist: None,
+            reward_vesting_blocks: 0,
+            min_claim_amount: Uint128::zero(),
+            warmup_period: 0,
+            emergency_claim_height: None,
+            basket_denoms: vec![],
+            bonus_denom: None,
+        }
+    );
+
+    // fail
     let info = mock_info(ADDR_OWNER, &[]);
     let _err = staking
-        .update_config(&mut app, &info.sender, None, None)
+        .update_config(
+            &mut app,
+            &info.sender,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
         .unwrap_err();
 }
 
@@ -304,7 +800,7 @@ fn test_staking() {
         Uint128::from(50u128)
     );
     assert_eq!(
-        (&app).wrap().query_balance(ADDR1, DENOM).unwrap().amount,
+        app.wrap().query_balance(ADDR1, DENOM).unwrap().amount,
         Uint128::from(50u128)
     );
 
@@ -313,16 +809,14 @@ fn test_staking() {
         to_address: ADDR2.to_string(),
         amount: coins(51, DENOM),
     };
-    let _err = (&mut app)
-        .execute(info.sender.clone(), msg.into())
-        .unwrap_err();
+    let _err = app.execute(info.sender.clone(), msg.into()).unwrap_err();
 
     // Sucessful transfer of unbonded amount
     let msg = BankMsg::Send {
         to_address: ADDR2.to_string(),
         amount: coins(20, DENOM),
     };
-    let _res = (&mut app).execute(info.sender.clone(), msg.into()).unwrap();
+    let _res = app.execute(info.sender.clone(), msg.into()).unwrap();
 
     assert_eq!(get_balance(&app, ADDR1), Uint128::from(30u128));
     assert_eq!(get_balance(&app, ADDR2), Uint128::from(20u128));
@@ -563,7 +1057,7 @@ fn multiple_address_staking() {
     );
 
     for addr in &[ADDR1, ADDR2, ADDR3, ADDR4] {
-        let info = mock_info(*addr, &[]);
+        let info = mock_info(addr, &[]);
         // Successful bond
         let _res = staking
             .stake(&mut app, &info.sender, coin(amount1.u128(), DENOM))
@@ -576,7 +1070,7 @@ fn multiple_address_staking() {
                 .balance,
             amount1
         );
-        assert_eq!(get_balance(&app, *addr), Uint128::zero())
+        assert_eq!(get_balance(&app, addr), Uint128::zero())
     }
     assert_eq!(
         staking.query_total_staked_at_height(&app, None).total,
@@ -648,7 +1142,7 @@ fn test_auto_compounding_staking() {
         to_address: ADDR2.to_string(),
         amount: coins(100, DENOM),
     };
-    let _res = (&mut app).execute(info.sender, msg.into()).unwrap();
+    let _res = app.execute(info.sender, msg.into()).unwrap();
 
     assert_eq!(get_balance(&app, ADDR1), Uint128::from(700u128));
     assert_eq!(get_balance(&app, ADDR2), Uint128::from(100u128));
@@ -825,3 +1319,1986 @@ fn test_simple_unstaking_with_duration() {
     staking.claim(&mut app, &info.sender).unwrap();
     assert_eq!(get_balance(&app, ADDR2), Uint128::from(100u128));
 }
+
+#[test]
+fn test_reward_multiplier() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000u128), (ADDR_OWNER, 1000u128)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    let staker = Addr::unchecked(ADDR1);
+    let owner = Addr::unchecked(ADDR_OWNER);
+    staking.stake(&mut app, &staker, coin(100, DENOM)).unwrap();
+    app.update_block(next_block);
+
+    // multiplier below 1.0 is rejected
+    let err: ContractError = staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            Some(Decimal::percent(50)),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    let max = Decimal::percent(MAX_REWARD_MULTIPLIER_PERCENT);
+    assert_eq!(err, ContractError::InvalidRewardMultiplier { max });
+
+    // so is one above the cap
+    let err: ContractError = staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            Some(max + Decimal::percent(1)),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidRewardMultiplier { max });
+
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            Some(Decimal::percent(200)),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        staking.query_config(&app).reward_multiplier,
+        Decimal::percent(200)
+    );
+
+    // reserve is empty, boosted portion can't be covered
+    let err: ContractError = staking
+        .fund(&mut app, &owner, coin(50, DENOM))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InsufficientRewardReserve {
+            required: Uint128::new(50),
+            reserve: Uint128::zero()
+        }
+    );
+
+    staking
+        .fund_reward_reserve(&mut app, &owner, coin(60, DENOM))
+        .unwrap();
+    staking.fund(&mut app, &owner, coin(50, DENOM)).unwrap();
+    assert_eq!(
+        staking.query_total_value(&app).total,
+        Uint128::from(200u128)
+    );
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR1).value,
+        Uint128::from(200u128)
+    );
+
+    // 10 left in the reserve
+    let err: ContractError = staking
+        .fund(&mut app, &owner, coin(20, DENOM))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InsufficientRewardReserve {
+            required: Uint128::new(20),
+            reserve: Uint128::new(10)
+        }
+    );
+    staking.fund(&mut app, &owner, coin(10, DENOM)).unwrap();
+    assert_eq!(
+        staking.query_total_value(&app).total,
+        Uint128::from(220u128)
+    );
+
+    // the reserve only takes the staking denom
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: ADDR_OWNER.to_string(),
+        amount: coins(10, "other"),
+    }))
+    .unwrap();
+    let err: ContractError = staking
+        .fund_reward_reserve(&mut app, &owner, coin(10, "other"))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Payment(PaymentError::ExtraDenom("other".to_string()))
+    );
+}
+
+#[test]
+fn test_fund_unboosted() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000u128), (ADDR_OWNER, 1000u128)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    let staker = Addr::unchecked(ADDR1);
+    let owner = Addr::unchecked(ADDR_OWNER);
+    staking.stake(&mut app, &staker, coin(100, DENOM)).unwrap();
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            Some(Decimal::percent(200)),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    app.update_block(next_block);
+
+    // credited as is, the empty reserve is never touched
+    staking
+        .fund_unboosted(&mut app, &owner, coin(50, DENOM))
+        .unwrap();
+    assert_eq!(
+        staking.query_total_value(&app).total,
+        Uint128::from(150u128)
+    );
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: ADDR_OWNER.to_string(),
+        amount: coins(10, "other"),
+    }))
+    .unwrap();
+    let err: ContractError = staking
+        .fund_unboosted(&mut app, &owner, coin(10, "other"))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Payment(PaymentError::ExtraDenom("other".to_string()))
+    );
+}
+
+#[test]
+fn test_fund_allowlist() {
+    let mut app = mock_app();
+    let initial_balances = vec![
+        (ADDR1, 1000u128),
+        (ADDR2, 1000u128),
+        (ADDR3, 1000u128),
+        (ADDR_OWNER, 1000u128),
+    ];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    let owner = Addr::unchecked(ADDR_OWNER);
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR1), coin(100, DENOM))
+        .unwrap();
+
+    // open funding by default
+    staking
+        .fund(&mut app, &Addr::unchecked(ADDR3), coin(10, DENOM))
+        .unwrap();
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(110));
+
+    // gated funding
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            None,
+            Some(vec![Addr::unchecked(ADDR2)]),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        staking.query_config(&app).fund_allowlist,
+        Some(vec![Addr::unchecked(ADDR2)])
+    );
+
+    let err: ContractError = staking
+        .fund(&mut app, &Addr::unchecked(ADDR3), coin(10, DENOM))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            received: Addr::unchecked(ADDR3),
+            expected: owner.clone()
+        }
+    );
+
+    staking
+        .fund(&mut app, &Addr::unchecked(ADDR2), coin(10, DENOM))
+        .unwrap();
+    // admin is always allowed
+    staking.fund(&mut app, &owner, coin(10, DENOM)).unwrap();
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(130));
+
+    // updating anything else keeps the allowlist
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            Some(Decimal::one()),
+            None,
+            Some(5),
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        staking.query_config(&app).fund_allowlist,
+        Some(vec![Addr::unchecked(ADDR2)])
+    );
+
+    // reopen funding
+    app.execute_contract(
+        owner.clone(),
+        staking.address.clone(),
+        &ExecuteMsg::UpdateConfig {
+            admin: Some(owner.clone()),
+            duration: None,
+            reward_multiplier: None,
+            fund_allowlist: None,
+            clear_fund_allowlist: true,
+            reward_vesting_blocks: Some(0),
+            min_claim_amount: None,
+            warmup_period: None,
+            bonus_denom: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(staking.query_config(&app).fund_allowlist, None);
+    staking
+        .fund(&mut app, &Addr::unchecked(ADDR3), coin(10, DENOM))
+        .unwrap();
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(140));
+}
+
+#[test]
+fn test_slash_records() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000u128), (ADDR2, 1000u128)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    let owner = Addr::unchecked(ADDR_OWNER);
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR1), coin(100, DENOM))
+        .unwrap();
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(100, DENOM))
+        .unwrap();
+    app.update_block(next_block);
+
+    // only admin can slash
+    let err: ContractError = staking
+        .slash(
+            &mut app,
+            &Addr::unchecked(ADDR2),
+            ADDR1,
+            Decimal::percent(10),
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            received: Addr::unchecked(ADDR2),
+            expected: owner.clone()
+        }
+    );
+
+    let err: ContractError = staking
+        .slash(&mut app, &owner, ADDR1, Decimal::percent(150), None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidSlashFraction {});
+
+    let slash_height = app.block_info().height;
+    staking
+        .slash(
+            &mut app,
+            &owner,
+            ADDR1,
+            Decimal::percent(10),
+            Some("double sign"),
+        )
+        .unwrap();
+    app.update_block(next_block);
+    // two slashes in one block are both recorded
+    staking
+        .slash(&mut app, &owner, ADDR1, Decimal::percent(50), None)
+        .unwrap();
+    staking
+        .slash(&mut app, &owner, ADDR1, Decimal::percent(20), Some("again"))
+        .unwrap();
+    app.update_block(next_block);
+    staking
+        .slash(&mut app, &owner, ADDR2, Decimal::percent(20), None)
+        .unwrap();
+    app.update_block(next_block);
+
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR1, None)
+            .balance,
+        Uint128::new(36)
+    );
+    assert_eq!(get_balance(&app, ADDR_OWNER), Uint128::new(84));
+
+    // most recent first
+    let history = staking.query_slash_history(&app, ADDR1, None, None).records;
+    assert_eq!(history.len(), 3);
+    assert_eq!((history[0].seq, history[0].height), (2, slash_height + 1));
+    assert_eq!(history[0].record.reason, Some("again".to_string()));
+    assert_eq!(history[0].record.amount_after, Uint128::new(36));
+    assert_eq!((history[1].seq, history[1].height), (1, slash_height + 1));
+    assert_eq!(history[1].record.amount_before, Uint128::new(90));
+    assert_eq!(history[1].record.amount_after, Uint128::new(45));
+    assert_eq!((history[2].seq, history[2].height), (0, slash_height));
+    assert_eq!(history[2].record.slasher, owner);
+    assert_eq!(history[2].record.fraction, Decimal::percent(10));
+    assert_eq!(history[2].record.reason, Some("double sign".to_string()));
+
+    let page = staking
+        .query_slash_history(&app, ADDR1, None, Some(1))
+        .records;
+    assert_eq!(page, history[..1].to_vec());
+    let page = staking
+        .query_slash_history(&app, ADDR1, Some(2), Some(5))
+        .records;
+    assert_eq!(page, history[1..].to_vec());
+    assert!(staking
+        .query_slash_history(&app, ADDR3, None, None)
+        .records
+        .is_empty());
+
+    let all = staking.query_all_slash_records(&app, None, None).records;
+    assert_eq!(all.len(), 4);
+    assert_eq!(all[3].address, Addr::unchecked(ADDR2));
+    assert_eq!(all[3].record.amount_after, Uint128::new(80));
+    let page = staking
+        .query_all_slash_records(&app, Some((ADDR1, 2)), None)
+        .records;
+    assert_eq!(page, all[3..].to_vec());
+}
+
+#[test]
+fn test_staker_lifetime() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000u128), (ADDR2, 1000u128)];
+    let staking = setup_test_case(&mut app, initial_balances, Some(Duration::Height(5)));
+
+    let addr1 = Addr::unchecked(ADDR1);
+    assert_eq!(
+        staking.query_staker_lifetime(&app, ADDR1),
+        StakerLifetimeResponse {
+            lifetime_staked: Uint128::zero(),
+            lifetime_claimed: Uint128::zero(),
+        }
+    );
+
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    staking.stake(&mut app, &addr1, coin(50, DENOM)).unwrap();
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(70, DENOM))
+        .unwrap();
+
+    staking.unstake(&mut app, &addr1, Uint128::new(40)).unwrap();
+    app.update_block(next_block);
+    staking.unstake(&mut app, &addr1, Uint128::new(60)).unwrap();
+    // unstaking alone doesn't count as claimed
+    assert_eq!(
+        staking.query_staker_lifetime(&app, ADDR1).lifetime_claimed,
+        Uint128::zero()
+    );
+
+    app.update_block(|b| b.height += 4);
+    staking.claim(&mut app, &addr1).unwrap();
+    app.update_block(next_block);
+    staking.claim(&mut app, &addr1).unwrap();
+
+    assert_eq!(
+        staking.query_staker_lifetime(&app, ADDR1),
+        StakerLifetimeResponse {
+            lifetime_staked: Uint128::new(150),
+            lifetime_claimed: Uint128::new(100),
+        }
+    );
+
+    // counters are monotonic
+    staking.stake(&mut app, &addr1, coin(20, DENOM)).unwrap();
+    assert_eq!(
+        staking.query_staker_lifetime(&app, ADDR1).lifetime_staked,
+        Uint128::new(170)
+    );
+    assert_eq!(
+        staking.query_staker_lifetime(&app, ADDR2),
+        StakerLifetimeResponse {
+            lifetime_staked: Uint128::new(70),
+            lifetime_claimed: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn test_staked_balances_at_height() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000u128), (ADDR2, 1000u128)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR1), coin(100, DENOM))
+        .unwrap();
+    app.update_block(next_block);
+    let height = app.block_info().height;
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(50, DENOM))
+        .unwrap();
+    app.update_block(next_block);
+
+    let resp = staking.query_staked_balances_at_height(&app, vec![ADDR1, ADDR2, ADDR3], None);
+    assert_eq!(resp.height, app.block_info().height);
+    assert_eq!(
+        resp.balances,
+        vec![
+            AddressStakedBalance {
+                address: Addr::unchecked(ADDR1),
+                balance: Uint128::new(100)
+            },
+            AddressStakedBalance {
+                address: Addr::unchecked(ADDR2),
+                balance: Uint128::new(50)
+            },
+            AddressStakedBalance {
+                address: Addr::unchecked(ADDR3),
+                balance: Uint128::zero()
+            },
+        ]
+    );
+
+    let resp = staking.query_staked_balances_at_height(&app, vec![ADDR1, ADDR2], Some(height));
+    assert_eq!(resp.height, height);
+    assert_eq!(resp.balances[0].balance, Uint128::new(100));
+    assert_eq!(resp.balances[1].balance, Uint128::zero());
+}
+
+#[test]
+fn test_snapshot_falls_back_to_prior_change() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000u128), (ADDR2, 1000u128)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    let first = app.block_info().height;
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR1), coin(100, DENOM))
+        .unwrap();
+    for _ in 0..5 {
+        app.update_block(next_block);
+    }
+    let second = app.block_info().height;
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR1), coin(50, DENOM))
+        .unwrap();
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(30, DENOM))
+        .unwrap();
+    app.update_block(next_block);
+
+    // reads at a height see the state as of the start of that block
+    let balance = |app: &OsmosisApp, height| {
+        staking
+            .query_staked_balance_at_height(app, ADDR1, Some(height))
+            .balance
+            .u128()
+    };
+    let total = |app: &OsmosisApp, height| {
+        staking
+            .query_total_staked_at_height(app, Some(height))
+            .total
+            .u128()
+    };
+
+    assert_eq!(balance(&app, first), 0);
+    assert_eq!(total(&app, first), 0);
+    for height in first + 1..=second {
+        assert_eq!(balance(&app, height), 100);
+        assert_eq!(total(&app, height), 100);
+    }
+    assert_eq!(balance(&app, second + 1), 150);
+    assert_eq!(total(&app, second + 1), 180);
+    assert_eq!(
+        staking.query_total_staked_at_height(&app, None).total,
+        Uint128::new(180)
+    );
+}
+
+#[test]
+fn test_reward_vesting() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000u128), (ADDR2, 1000u128), (ADDR_OWNER, 1000u128)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    let owner = Addr::unchecked(ADDR_OWNER);
+    let addr1 = Addr::unchecked(ADDR1);
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            None,
+            None,
+            Some(10),
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(staking.query_config(&app).reward_vesting_blocks, 10);
+
+    // reward doesn't hit the pool immediately
+    staking.fund(&mut app, &owner, coin(100, DENOM)).unwrap();
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(100));
+
+    // released linearly on subsequent executions, queries already count the vested part
+    app.update_block(|b| b.height += 4);
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(140));
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR1).value,
+        Uint128::new(140)
+    );
+    assert_eq!(
+        staking.query_convert_to_assets(&app, 100),
+        Uint128::new(140)
+    );
+    let drift = staking.query_balance_drift(&app);
+    assert_eq!(drift.recorded_balance, Uint128::new(140));
+    assert_eq!(drift.drift, 0);
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(140, DENOM))
+        .unwrap();
+    // 40 released before the stake, so 140 buys 100 shares at 1.4
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(280));
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR2).value,
+        Uint128::new(140)
+    );
+
+    app.update_block(|b| b.height += 6);
+    staking
+        .unstake(&mut app, &addr1, Uint128::new(100))
+        .unwrap();
+    // remaining 60 released before unstaking: 100 shares are now worth 170
+    assert_eq!(get_balance(&app, ADDR1), Uint128::new(1070));
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(170));
+
+    // zero vesting blocks releases immediately
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            None,
+            None,
+            Some(0),
+            None,
+            None,
+        )
+        .unwrap();
+    staking.fund(&mut app, &owner, coin(30, DENOM)).unwrap();
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(200));
+}
+
+#[test]
+fn test_reward_vesting_dust_funds() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000u128), (ADDR_OWNER, 1000u128)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    let owner = Addr::unchecked(ADDR_OWNER);
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR1), coin(100, DENOM))
+        .unwrap();
+    let update_vesting = |app: &mut OsmosisApp, reward_vesting_blocks| {
+        staking
+            .update_config(
+                app,
+                &owner,
+                Some(owner.clone()),
+                None,
+                None,
+                None,
+                Some(reward_vesting_blocks),
+                None,
+                None,
+            )
+            .unwrap();
+    };
+    update_vesting(&mut app, 10);
+
+    staking.fund(&mut app, &owner, coin(100, DENOM)).unwrap();
+    // funding dust every block doesn't hold back the earlier reward
+    for _ in 0..10 {
+        app.update_block(next_block);
+        staking.fund(&mut app, &owner, coin(1, DENOM)).unwrap();
+    }
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(200));
+
+    // the dust vests on its own schedule, then everything is out
+    app.update_block(|b| b.height += 10);
+    update_vesting(&mut app, 0);
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(210));
+}
+
+#[test]
+fn test_staked_value_large_balances() {
+    let mut app = mock_app();
+    let huge = 10u128.pow(30);
+    let initial_balances = vec![(ADDR1, huge), (ADDR2, huge)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR1), coin(huge, DENOM))
+        .unwrap();
+    staking
+        .fund(&mut app, &Addr::unchecked(ADDR2), coin(huge / 2, DENOM))
+        .unwrap();
+
+    // staked * balance is far above u128::MAX
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR1).value,
+        Uint128::new(huge + huge / 2)
+    );
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR2).value,
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn test_compound_voting_power() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000), (ADDR2, 1000)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+    let addr1 = Addr::unchecked(ADDR1);
+    let addr2 = Addr::unchecked(ADDR2);
+
+    let err = staking.compound_voting_power(&mut app, &addr1).unwrap_err();
+    assert_eq!(ContractError::NothingToCompound {}, err.downcast().unwrap());
+
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    staking.stake(&mut app, &addr2, coin(100, DENOM)).unwrap();
+    staking.fund(&mut app, &addr2, coin(100, DENOM)).unwrap();
+    app.update_block(next_block);
+
+    // rewards don't count as voting power until compounded
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR1, None)
+            .balance,
+        Uint128::new(100)
+    );
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR1).value,
+        Uint128::new(150)
+    );
+
+    staking.compound_voting_power(&mut app, &addr1).unwrap();
+    app.update_block(next_block);
+
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR1, None)
+            .balance,
+        Uint128::new(150)
+    );
+    assert_eq!(
+        staking.query_total_staked_at_height(&app, None).total,
+        Uint128::new(250)
+    );
+    let height = app.block_info().height;
+    assert_eq!(
+        staking
+            .query_total_staked_history(&app, height, None, None)
+            .unwrap(),
+        vec![(height, 250)]
+    );
+    // nothing changes for the other staker
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR2, None)
+            .balance,
+        Uint128::new(100)
+    );
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR1).value,
+        Uint128::new(150)
+    );
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR2).value,
+        Uint128::new(150)
+    );
+
+    // unstaking half the shares drops half the compounded power too
+    staking.unstake(&mut app, &addr1, Uint128::new(50)).unwrap();
+    app.update_block(next_block);
+
+    assert_eq!(get_balance(&app, ADDR1), Uint128::new(975));
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR1, None)
+            .balance,
+        Uint128::new(75)
+    );
+    assert_eq!(
+        staking.query_total_staked_at_height(&app, None).total,
+        Uint128::new(175)
+    );
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR2).value,
+        Uint128::new(150)
+    );
+}
+
+#[test]
+fn test_emergency_claim_height() {
+    let mut app = mock_app();
+    let staking = setup_test_case(&mut app, vec![(ADDR1, 100)], Some(Duration::Height(1_000)));
+    let addr1 = Addr::unchecked(ADDR1);
+    let owner = Addr::unchecked(ADDR_OWNER);
+
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    app.update_block(next_block);
+    staking.unstake(&mut app, &addr1, Uint128::new(40)).unwrap();
+    app.update_block(next_block);
+
+    // only the admin can set it
+    let err = staking
+        .set_emergency_claim_height(&mut app, &addr1, Some(0))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Unauthorized {
+            expected: owner.clone(),
+            received: addr1.clone(),
+        },
+        err.downcast().unwrap()
+    );
+
+    let height = app.block_info().height + 5;
+    staking
+        .set_emergency_claim_height(&mut app, &owner, Some(height))
+        .unwrap();
+    assert_eq!(
+        staking.query_config(&app).emergency_claim_height,
+        Some(height)
+    );
+
+    // not reached yet, the claim isn't mature either
+    let err = staking.claim(&mut app, &addr1).unwrap_err();
+    assert_eq!(ContractError::NothingToClaim {}, err.downcast().unwrap());
+
+    app.update_block(|b| b.height += 5);
+    staking.claim(&mut app, &addr1).unwrap();
+    assert_eq!(get_balance(&app, ADDR1), Uint128::new(40));
+
+    staking
+        .set_emergency_claim_height(&mut app, &owner, None)
+        .unwrap();
+    assert_eq!(staking.query_config(&app).emergency_claim_height, None);
+
+    // back to the normal maturity rules
+    staking.unstake(&mut app, &addr1, Uint128::new(60)).unwrap();
+    app.update_block(next_block);
+    let err = staking.claim(&mut app, &addr1).unwrap_err();
+    assert_eq!(ContractError::NothingToClaim {}, err.downcast().unwrap());
+}
+
+#[test]
+fn test_denom_is_immutable() {
+    let mut app = mock_app();
+    let staking = setup_test_case(&mut app, vec![(ADDR1, 100), (ADDR_OWNER, 100)], None);
+    let addr1 = Addr::unchecked(ADDR1);
+    let owner = Addr::unchecked(ADDR_OWNER);
+
+    // run every message that touches the config or the balances
+    staking.stake(&mut app, &addr1, coin(50, DENOM)).unwrap();
+    staking.fund(&mut app, &owner, coin(10, DENOM)).unwrap();
+    staking
+        .fund_reward_reserve(&mut app, &owner, coin(10, DENOM))
+        .unwrap();
+    app.update_block(next_block);
+    staking.compound_voting_power(&mut app, &addr1).unwrap();
+    staking
+        .slash(&mut app, &owner, ADDR1, Decimal::percent(10), None)
+        .unwrap();
+    staking
+        .set_emergency_claim_height(&mut app, &owner, Some(1))
+        .unwrap();
+    staking
+        .set_emergency_claim_height(&mut app, &owner, None)
+        .unwrap();
+    staking.unstake(&mut app, &addr1, Uint128::new(10)).unwrap();
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            Some(Duration::Height(5)),
+            Some(Decimal::percent(150)),
+            Some(vec![addr1.clone()]),
+            Some(10),
+            None,
+            None,
+        )
+        .unwrap();
+    staking
+        .update_config(&mut app, &owner, None, None, None, None, None, None, None)
+        .unwrap();
+
+    assert_eq!(staking.query_config(&app).denom, DENOM);
+}
+
+#[test]
+fn test_basket_staking() {
+    const BASKET_DENOM: &str = "uatom";
+
+    let mut app = mock_app();
+    let staking_code_id = app.store_code(mock_staking_code());
+    let basket_msg = |basket_denoms: Vec<BasketDenom>| crate::msg::InstantiateMsg {
+        admin: Some(Addr::unchecked(ADDR_OWNER)),
+        denom: DENOM.to_string(),
+        unstaking_duration: Some(Duration::Height(10)),
+        basket_denoms,
+        bonus_denom: None,
+    };
+    let atom = BasketDenom {
+        denom: BASKET_DENOM.to_string(),
+        pool_id: 1,
+    };
+
+    // a basket denom must differ from the staking denom and every other basket denom
+    let err = app
+        .instantiate_contract(
+            staking_code_id,
+            Addr::unchecked(ADDR1),
+            &basket_msg(vec![atom.clone(), atom.clone()]),
+            &[],
+            "staking",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidBasket {
+            reason: "uatom is listed twice".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    let address = app
+        .instantiate_contract(
+            staking_code_id,
+            Addr::unchecked(ADDR1),
+            &basket_msg(vec![atom.clone()]),
+            &[],
+            "staking",
+            None,
+        )
+        .unwrap();
+    let staking = Stake { address };
+    assert_eq!(staking.query_config(&app).basket_denoms, vec![atom]);
+
+    // 1 uatom = 2 denom
+    app.init_modules(|router, _, storage| {
+        router
+            .custom
+            .set_pool(
+                storage,
+                1,
+                &Pool::new(coin(100_000, BASKET_DENOM), coin(200_000, DENOM)),
+            )
+            .unwrap()
+    });
+    for (address, funds) in [
+        (ADDR1, vec![coin(100, DENOM), coin(50, BASKET_DENOM)]),
+        (ADDR2, vec![coin(100, BASKET_DENOM)]),
+        (ADDR3, vec![coin(10, "uosmo")]),
+    ] {
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: address.to_string(),
+            amount: funds,
+        }))
+        .unwrap();
+    }
+    let addr1 = Addr::unchecked(ADDR1);
+    let addr2 = Addr::unchecked(ADDR2);
+
+    // shares follow the value in the staking denom
+    staking
+        .stake_basket(
+            &mut app,
+            &addr1,
+            &[coin(100, DENOM), coin(50, BASKET_DENOM)],
+        )
+        .unwrap();
+    staking
+        .stake_basket(&mut app, &addr2, &[coin(100, BASKET_DENOM)])
+        .unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR1, None)
+            .balance,
+        Uint128::new(200)
+    );
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR2, None)
+            .balance,
+        Uint128::new(200)
+    );
+    assert_eq!(
+        staking.query_basket_composition(&app).balances,
+        vec![coin(100, DENOM), coin(150, BASKET_DENOM)]
+    );
+    // the basket tokens count towards the value behind every share
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(400));
+    assert_eq!(
+        staking.query_staked_value(&app, ADDR2).value,
+        Uint128::new(200)
+    );
+    assert_eq!(
+        staking.query_convert_to_assets(&app, 100),
+        Uint128::new(100)
+    );
+    assert_eq!(
+        staking.query_convert_to_shares(&app, 100),
+        Uint128::new(100)
+    );
+
+    let err = staking
+        .stake_basket(&mut app, &Addr::unchecked(ADDR3), &[coin(10, "uosmo")])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NotInBasket {
+            denom: "uosmo".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    // a quarter of the shares takes a quarter of every denom
+    staking
+        .unstake(&mut app, &addr2, Uint128::new(100))
+        .unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        staking.query_basket_composition(&app).balances,
+        vec![coin(75, DENOM), coin(113, BASKET_DENOM)]
+    );
+    assert_eq!(
+        staking.query_basket_claims(&app, ADDR2).claims[0].amounts,
+        vec![coin(37, BASKET_DENOM)]
+    );
+
+    let err = staking.claim(&mut app, &addr2).unwrap_err();
+    assert_eq!(ContractError::NothingToClaim {}, err.downcast().unwrap());
+
+    app.update_block(|b| b.height += 10);
+    staking.claim(&mut app, &addr2).unwrap();
+    assert_eq!(get_balance(&app, ADDR2), Uint128::new(25));
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR2, BASKET_DENOM)
+            .unwrap()
+            .amount,
+        Uint128::new(37)
+    );
+    assert!(staking.query_basket_claims(&app, ADDR2).claims.is_empty());
+
+    // forfeiting puts the basket claims back into the basket at the current price
+    staking
+        .unstake(&mut app, &addr1, Uint128::new(100))
+        .unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        staking.query_basket_composition(&app).balances,
+        vec![coin(50, DENOM), coin(76, BASKET_DENOM)]
+    );
+    assert_eq!(
+        staking.query_forfeitable_claims_value(&app, ADDR1),
+        Uint128::new(25 + 37 * 2)
+    );
+    staking.forfeit_claims(&mut app, &addr1).unwrap();
+    app.update_block(next_block);
+    assert!(staking.query_claims(&app, ADDR1).claims.is_empty());
+    assert!(staking.query_basket_claims(&app, ADDR1).claims.is_empty());
+    assert_eq!(
+        staking.query_basket_composition(&app).balances,
+        vec![coin(75, DENOM), coin(113, BASKET_DENOM)]
+    );
+    // 99 worth of claims against 202 worth of holdings backing 200 shares
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR1, None)
+            .balance,
+        Uint128::new(198)
+    );
+
+    app.update_block(|b| b.height += 10);
+    let err = staking.claim(&mut app, &addr1).unwrap_err();
+    assert_eq!(ContractError::NothingToClaim {}, err.downcast().unwrap());
+
+    // the re-staked shares unstake and claim like any other
+    staking.unstake(&mut app, &addr1, Uint128::new(98)).unwrap();
+    app.update_block(|b| b.height += 10);
+    staking.claim(&mut app, &addr1).unwrap();
+    assert_eq!(get_balance(&app, ADDR1), Uint128::new(24));
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR1, BASKET_DENOM)
+            .unwrap()
+            .amount,
+        Uint128::new(37)
+    );
+}
+
+#[test]
+fn test_share_conversion() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000), (ADDR2, 1000)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+
+    // empty pool converts 1:1
+    assert_eq!(
+        staking.query_convert_to_assets(&app, 100),
+        Uint128::new(100)
+    );
+    assert_eq!(
+        staking.query_convert_to_shares(&app, 100),
+        Uint128::new(100)
+    );
+
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR1), coin(100, DENOM))
+        .unwrap();
+    assert_eq!(staking.query_convert_to_assets(&app, 50), Uint128::new(50));
+    assert_eq!(staking.query_convert_to_shares(&app, 50), Uint128::new(50));
+
+    // rewards double the value of every share
+    staking
+        .fund(&mut app, &Addr::unchecked(ADDR2), coin(100, DENOM))
+        .unwrap();
+    assert_eq!(staking.query_convert_to_assets(&app, 50), Uint128::new(100));
+    assert_eq!(staking.query_convert_to_shares(&app, 100), Uint128::new(50));
+    assert_eq!(
+        staking.query_convert_to_assets(&app, 100),
+        staking.query_staked_value(&app, ADDR1).value
+    );
+
+    // the quoted share count matches what staking actually mints
+    let quoted = staking.query_convert_to_shares(&app, 300);
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(300, DENOM))
+        .unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR2.to_string(), None)
+            .balance,
+        quoted
+    );
+    assert_eq!(staking.query_convert_to_shares(&app, 3), Uint128::new(1));
+}
+
+#[test]
+fn test_top_stakers() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 1000), (ADDR2, 1000), (ADDR3, 1000), (ADDR4, 1000)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+    assert!(staking.query_top_stakers(&app, 10).is_empty());
+
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR1), coin(100, DENOM))
+        .unwrap();
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(300, DENOM))
+        .unwrap();
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR3), coin(200, DENOM))
+        .unwrap();
+    assert_eq!(
+        staking.query_top_stakers(&app, 10),
+        vec![
+            (ADDR2.to_string(), 300),
+            (ADDR3.to_string(), 200),
+            (ADDR1.to_string(), 100)
+        ]
+    );
+
+    // rewards halve the shares minted for new stake
+    staking
+        .fund(&mut app, &Addr::unchecked(ADDR4), coin(600, DENOM))
+        .unwrap();
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR4), coin(400, DENOM))
+        .unwrap();
+    staking
+        .unstake(&mut app, &Addr::unchecked(ADDR2), Uint128::new(250))
+        .unwrap();
+    assert_eq!(
+        staking.query_top_stakers(&app, 10),
+        vec![
+            (ADDR3.to_string(), 200),
+            (ADDR4.to_string(), 200),
+            (ADDR1.to_string(), 100),
+            (ADDR2.to_string(), 50)
+        ]
+    );
+
+    // fully unstaked addresses drop out
+    staking
+        .unstake(&mut app, &Addr::unchecked(ADDR2), Uint128::new(50))
+        .unwrap();
+    assert_eq!(
+        staking.query_top_stakers(&app, 2),
+        vec![(ADDR3.to_string(), 200), (ADDR4.to_string(), 200)]
+    );
+    assert_eq!(staking.query_top_stakers(&app, 100).len(), 3);
+}
+
+#[test]
+fn test_unbonding_queue() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 100), (ADDR2, 100), (ADDR3, 100)];
+    let staking = setup_test_case(&mut app, initial_balances, Some(Duration::Height(10)));
+    assert!(staking.query_unbonding_queue(&app, None, None).is_empty());
+
+    for addr in [ADDR1, ADDR2, ADDR3] {
+        staking
+            .stake(&mut app, &Addr::unchecked(addr), coin(100, DENOM))
+            .unwrap();
+    }
+    app.update_block(next_block);
+    staking
+        .unstake(&mut app, &Addr::unchecked(ADDR1), Uint128::new(10))
+        .unwrap();
+    staking
+        .unstake(&mut app, &Addr::unchecked(ADDR3), Uint128::new(30))
+        .unwrap();
+    app.update_block(next_block);
+    staking
+        .unstake(&mut app, &Addr::unchecked(ADDR1), Uint128::new(20))
+        .unwrap();
+    staking
+        .unstake(&mut app, &Addr::unchecked(ADDR2), Uint128::new(40))
+        .unwrap();
+
+    let queue = staking.query_unbonding_queue(&app, None, None);
+    assert_eq!(
+        queue,
+        vec![
+            (ADDR1.to_string(), 0, 10),
+            (ADDR1.to_string(), 1, 20),
+            (ADDR2.to_string(), 0, 40),
+            (ADDR3.to_string(), 0, 30),
+        ]
+    );
+    let claims = staking.query_claims(&app, ADDR1).claims;
+    assert_eq!(claims[1].release_at, AtHeight(app.block_info().height + 10));
+
+    // paging, including a cursor in the middle of an address' claims
+    let first = staking.query_unbonding_queue(&app, None, Some(1));
+    assert_eq!(first, queue[..1]);
+    let second = staking.query_unbonding_queue(&app, Some((ADDR1, 0)), Some(2));
+    assert_eq!(second, queue[1..3]);
+    let last = staking.query_unbonding_queue(&app, Some((ADDR2, 0)), Some(2));
+    assert_eq!(last, queue[3..]);
+    assert!(staking
+        .query_unbonding_queue(&app, Some((ADDR3, 0)), None)
+        .is_empty());
+}
+
+#[test]
+fn test_forfeit_claims() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 100), (ADDR2, 100)];
+    let staking = setup_test_case(&mut app, initial_balances, Some(Duration::Height(10)));
+    let addr1 = Addr::unchecked(ADDR1);
+
+    let err = staking.forfeit_claims(&mut app, &addr1).unwrap_err();
+    assert_eq!(ContractError::NothingToForfeit {}, err.downcast().unwrap());
+
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(100, DENOM))
+        .unwrap();
+    app.update_block(next_block);
+    staking.unstake(&mut app, &addr1, Uint128::new(10)).unwrap();
+    staking.unstake(&mut app, &addr1, Uint128::new(30)).unwrap();
+    assert_eq!(
+        staking.query_forfeitable_claims_value(&app, ADDR1),
+        Uint128::new(40)
+    );
+    assert_eq!(
+        staking.query_forfeitable_claims_value(&app, ADDR2),
+        Uint128::zero()
+    );
+
+    let res = staking.forfeit_claims(&mut app, &addr1).unwrap();
+    assert_eq!(
+        from_binary::<StakeResponse>(&res.data.unwrap()).unwrap(),
+        StakeResponse {
+            shares_minted: Uint128::new(40),
+            new_balance: Uint128::new(100),
+        }
+    );
+    app.update_block(next_block);
+
+    assert!(staking.query_claims(&app, ADDR1).claims.is_empty());
+    // the forfeited tokens were already counted when first staked
+    assert_eq!(
+        staking.query_staker_lifetime(&app, ADDR1).lifetime_staked,
+        Uint128::new(100)
+    );
+    assert_eq!(
+        staking.query_forfeitable_claims_value(&app, ADDR1),
+        Uint128::zero()
+    );
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR1, None)
+            .balance,
+        Uint128::new(100)
+    );
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(200));
+    // nothing was paid out
+    assert_eq!(get_balance(&app, ADDR1), Uint128::zero());
+
+    let err = staking.forfeit_claims(&mut app, &addr1).unwrap_err();
+    assert_eq!(ContractError::NothingToForfeit {}, err.downcast().unwrap());
+}
+
+#[test]
+fn test_stake_response_data() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 300), (ADDR2, 100)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+    let addr1 = Addr::unchecked(ADDR1);
+    let stake_data =
+        |res: AppResponse| -> StakeResponse { from_binary(&res.data.unwrap()).unwrap() };
+
+    let res = staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    assert_eq!(
+        stake_data(res),
+        StakeResponse {
+            shares_minted: Uint128::new(100),
+            new_balance: Uint128::new(100),
+        }
+    );
+
+    // rewards double the share price
+    staking.fund(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    let res = staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    assert_eq!(
+        stake_data(res),
+        StakeResponse {
+            shares_minted: Uint128::new(50),
+            new_balance: Uint128::new(150),
+        }
+    );
+    let res = staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(50, DENOM))
+        .unwrap();
+    assert_eq!(
+        stake_data(res),
+        StakeResponse {
+            shares_minted: Uint128::new(25),
+            new_balance: Uint128::new(25),
+        }
+    );
+}
+
+#[test]
+fn test_claim_status() {
+    let mut app = mock_app();
+    let staking = setup_test_case(&mut app, vec![(ADDR1, 100)], Some(Duration::Height(10)));
+    let addr1 = Addr::unchecked(ADDR1);
+    let owner = Addr::unchecked(ADDR_OWNER);
+
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    app.update_block(next_block);
+    let first = app.block_info().height + 10;
+    staking.unstake(&mut app, &addr1, Uint128::new(10)).unwrap();
+    app.update_block(|b| b.height += 5);
+    let second = app.block_info().height + 10;
+    staking.unstake(&mut app, &addr1, Uint128::new(20)).unwrap();
+    assert!(staking.query_claim_status(&app, ADDR2).is_empty());
+
+    app.update_block(|b| b.height += 5);
+    assert_eq!(
+        staking.query_claim_status(&app, ADDR1),
+        vec![
+            ClaimStatusEntry {
+                amount: Uint128::new(10),
+                release_at: AtHeight(first),
+                is_mature: true,
+                blocks_remaining: None,
+            },
+            ClaimStatusEntry {
+                amount: Uint128::new(20),
+                release_at: AtHeight(second),
+                is_mature: false,
+                blocks_remaining: Some(5),
+            }
+        ]
+    );
+
+    // the emergency height matures everything
+    let height = app.block_info().height;
+    staking
+        .set_emergency_claim_height(&mut app, &owner, Some(height))
+        .unwrap();
+    assert!(staking
+        .query_claim_status(&app, ADDR1)
+        .iter()
+        .all(|claim| claim.is_mature && claim.blocks_remaining.is_none()));
+}
+
+#[test]
+fn test_total_staked_history() {
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 100), (ADDR2, 100)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+    let start = app.block_info().height;
+
+    // +10 every block for 10 blocks
+    for _ in 0..10 {
+        app.update_block(next_block);
+        staking
+            .stake(&mut app, &Addr::unchecked(ADDR1), coin(10, DENOM))
+            .unwrap();
+    }
+    app.update_block(next_block);
+    let now = app.block_info().height;
+
+    // default step is a tenth of the range
+    let history = staking
+        .query_total_staked_history(&app, start, None, None)
+        .unwrap();
+    assert_eq!(history.len(), 12);
+    assert_eq!(history[0], (start, 0));
+    assert_eq!(history[1], (start + 1, 0));
+    assert_eq!(history[11], (now, 100));
+
+    let history = staking
+        .query_total_staked_history(&app, start + 2, Some(start + 10), Some(4))
+        .unwrap();
+    assert_eq!(
+        history,
+        vec![(start + 2, 10), (start + 6, 50), (start + 10, 90)]
+    );
+
+    // capped at 100 samples
+    let history = staking
+        .query_total_staked_history(&app, 0, Some(now), Some(1))
+        .unwrap();
+    assert_eq!(history.len(), 100);
+
+    staking
+        .query_total_staked_history(&app, now, Some(start), None)
+        .unwrap_err();
+}
+
+#[test]
+fn test_bonus_rewards() {
+    const BONUS: &str = "bonus";
+    let mut app = mock_app();
+    let initial_balances = vec![(ADDR1, 100), (ADDR2, 300)];
+    let staking = setup_test_case(&mut app, initial_balances, None);
+    let addr1 = Addr::unchecked(ADDR1);
+    let addr2 = Addr::unchecked(ADDR2);
+    let funder = Addr::unchecked(ADDR_OWNER);
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: funder.to_string(),
+        amount: vec![coin(1000, BONUS), coin(100, DENOM), coin(100, "other")],
+    }))
+    .unwrap();
+    let bonus_balance =
+        |app: &OsmosisApp, addr: &str| app.wrap().query_balance(addr, BONUS).unwrap().amount;
+    let set_bonus_denom = |app: &mut OsmosisApp, denom: &str| {
+        app.execute_contract(
+            funder.clone(),
+            staking.address.clone(),
+            &ExecuteMsg::UpdateConfig {
+                admin: Some(funder.clone()),
+                duration: None,
+                reward_multiplier: None,
+                fund_allowlist: Some(vec![funder.clone()]),
+                clear_fund_allowlist: false,
+                reward_vesting_blocks: None,
+                min_claim_amount: None,
+                warmup_period: None,
+                bonus_denom: Some(denom.to_string()),
+            },
+            &[],
+        )
+    };
+
+    // the first funding can't pick the bonus denom
+    let err: ContractError = staking
+        .fund_bonus(&mut app, &funder, coin(100, "other"))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidBonusDenom {
+            denom: "other".to_string()
+        }
+    );
+    let err: ContractError = set_bonus_denom(&mut app, DENOM)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidBonusDenom {
+            denom: DENOM.to_string()
+        }
+    );
+    set_bonus_denom(&mut app, BONUS).unwrap();
+    assert_eq!(
+        staking.query_config(&app).bonus_denom,
+        Some(BONUS.to_string())
+    );
+
+    // nobody to distribute to yet
+    let err: ContractError = staking
+        .fund_bonus(&mut app, &funder, coin(400, BONUS))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoStakers {});
+
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    staking.stake(&mut app, &addr2, coin(300, DENOM)).unwrap();
+    app.update_block(next_block);
+
+    // the staking denom is compounded through `Fund`, not paid as a bonus
+    let err: ContractError = staking
+        .fund_bonus(&mut app, &funder, coin(100, DENOM))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidBonusDenom {
+            denom: DENOM.to_string()
+        }
+    );
+
+    staking
+        .fund_bonus(&mut app, &funder, coin(400, BONUS))
+        .unwrap();
+    assert_eq!(
+        staking.query_pending_bonus(&app, ADDR1),
+        PendingBonusResponse {
+            denom: Some(BONUS.to_string()),
+            amount: Uint128::new(100),
+        }
+    );
+    assert_eq!(
+        staking.query_pending_bonus(&app, ADDR2).amount,
+        Uint128::new(300)
+    );
+
+    // only the configured denom is accepted, and it can't change under funded bonus
+    let err: ContractError = staking
+        .fund_bonus(&mut app, &funder, coin(100, "other"))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidBonusDenom {
+            denom: "other".to_string()
+        }
+    );
+    let err: ContractError = set_bonus_denom(&mut app, "other")
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::BonusDenomLocked {
+            denom: BONUS.to_string()
+        }
+    );
+
+    // funding is restricted by the allowlist like `Fund`
+    let outsider = Addr::unchecked(ADDR3);
+    app.send_tokens(funder.clone(), outsider.clone(), &[coin(100, BONUS)])
+        .unwrap();
+    let err: ContractError = staking
+        .fund_bonus(&mut app, &outsider, coin(100, BONUS))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+    // the bonus leaves the share price alone
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(400));
+
+    staking.claim_bonus(&mut app, &addr1).unwrap();
+    assert_eq!(bonus_balance(&app, ADDR1), Uint128::new(100));
+    let err: ContractError = staking
+        .claim_bonus(&mut app, &addr1)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NothingToClaim {});
+
+    // shares unstaked after a funding keep the bonus they already earned
+    staking
+        .unstake(&mut app, &addr2, Uint128::new(200))
+        .unwrap();
+    staking
+        .fund_bonus(&mut app, &funder, coin(200, BONUS))
+        .unwrap();
+    staking.claim_bonus(&mut app, &addr1).unwrap();
+    staking.claim_bonus(&mut app, &addr2).unwrap();
+    assert_eq!(bonus_balance(&app, ADDR1), Uint128::new(200));
+    assert_eq!(bonus_balance(&app, ADDR2), Uint128::new(400));
+}
+
+#[test]
+fn test_min_claim_amount() {
+    let mut app = mock_app();
+    let staking = setup_test_case(&mut app, vec![(ADDR1, 100)], Some(Duration::Height(10)));
+    let addr1 = Addr::unchecked(ADDR1);
+    let owner = Addr::unchecked(ADDR_OWNER);
+
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    app.update_block(next_block);
+    // created before the minimum exists
+    staking.unstake(&mut app, &addr1, Uint128::new(5)).unwrap();
+
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            Some(Duration::Height(10)),
+            None,
+            None,
+            None,
+            Some(Uint128::new(10)),
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        staking.query_config(&app).min_claim_amount,
+        Uint128::new(10)
+    );
+
+    let err = staking
+        .unstake(&mut app, &addr1, Uint128::new(5))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ClaimAmountTooSmall {
+            min: Uint128::new(10),
+            received: Uint128::new(5),
+        },
+        err.downcast().unwrap()
+    );
+    staking.unstake(&mut app, &addr1, Uint128::new(20)).unwrap();
+    assert_eq!(staking.query_claims(&app, ADDR1).claims.len(), 2);
+
+    app.update_block(|b| b.height += 10);
+    let res = staking.claim(&mut app, &addr1).unwrap();
+    let wasm = res.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "skipped_dust" && attr.value == "5"));
+    // the dust claim is gone without being paid out
+    assert_eq!(get_balance(&app, ADDR1), Uint128::new(20));
+    assert!(staking.query_claims(&app, ADDR1).claims.is_empty());
+}
+
+#[test]
+fn test_balance_drift() {
+    let mut app = mock_app();
+    let staking = setup_test_case(&mut app, vec![(ADDR1, 300)], Some(Duration::Height(10)));
+    let addr1 = Addr::unchecked(ADDR1);
+    let owner = Addr::unchecked(ADDR_OWNER);
+
+    staking.stake(&mut app, &addr1, coin(200, DENOM)).unwrap();
+    staking.unstake(&mut app, &addr1, Uint128::new(50)).unwrap();
+    // the pending claim is still held, but no longer staked
+    assert_eq!(
+        staking.query_balance_drift(&app),
+        BalanceDriftResponse {
+            recorded_balance: Uint128::new(150),
+            actual_balance: Uint128::new(150),
+            drift: 0,
+        }
+    );
+
+    // tokens sent without `Stake` or `Fund` aren't accounted for
+    app.execute(
+        addr1.clone(),
+        BankMsg::Send {
+            to_address: staking.address.to_string(),
+            amount: coins(30, DENOM),
+        }
+        .into(),
+    )
+    .unwrap();
+    assert_eq!(staking.query_balance_drift(&app).drift, 30);
+
+    let err = staking.reconcile_balance(&mut app, &addr1).unwrap_err();
+    assert_eq!(
+        ContractError::Unauthorized {
+            expected: owner.clone(),
+            received: addr1.clone(),
+        },
+        err.downcast().unwrap()
+    );
+
+    let res = staking.reconcile_balance(&mut app, &owner).unwrap();
+    let wasm = res.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "drift" && attr.value == "30"));
+    assert_eq!(staking.query_balance_drift(&app).drift, 0);
+    assert_eq!(staking.query_total_value(&app).total, Uint128::new(180));
+
+    // paying out the claim doesn't show up as drift either
+    app.update_block(|block| block.height += 10);
+    staking.claim(&mut app, &addr1).unwrap();
+    assert_eq!(staking.query_balance_drift(&app).drift, 0);
+}
+
+#[test]
+fn test_migrate_claims_total() {
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: MockQuerier::<OsmosisQuery>::new(&[]),
+        custom_query_type: PhantomData,
+    };
+    let claims = vec![Claim::new(30, AtHeight(10)), Claim::new(20, AtHeight(20))];
+    CLAIMS_BY_ADDRESS
+        .save(&mut deps.storage, &Addr::unchecked(ADDR1), &claims)
+        .unwrap();
+    CLAIMS_BY_ADDRESS
+        .save(
+            &mut deps.storage,
+            &Addr::unchecked(ADDR2),
+            &vec![Claim::new(5, AtHeight(15))],
+        )
+        .unwrap();
+
+    crate::contract::migrate(deps.as_mut(), mock_env(), crate::msg::MigrateMsg {}).unwrap();
+
+    assert_eq!(CLAIMS_TOTAL.load(&deps.storage).unwrap(), Uint128::new(55));
+}
+
+#[test]
+fn test_warmup_period() {
+    let mut app = mock_app();
+    let staking = setup_test_case(&mut app, vec![(ADDR1, 1000), (ADDR2, 1000)], None);
+    let addr1 = Addr::unchecked(ADDR1);
+    let owner = Addr::unchecked(ADDR_OWNER);
+
+    staking
+        .update_config(
+            &mut app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(5),
+        )
+        .unwrap();
+    assert_eq!(staking.query_config(&app).warmup_period, 5);
+
+    let first = app.block_info().height;
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    staking
+        .stake(&mut app, &Addr::unchecked(ADDR2), coin(50, DENOM))
+        .unwrap();
+    for _ in 0..5 {
+        app.update_block(next_block);
+    }
+    let second = app.block_info().height;
+    staking.stake(&mut app, &addr1, coin(50, DENOM)).unwrap();
+    for _ in 0..5 {
+        app.update_block(next_block);
+    }
+
+    let balance = |app: &OsmosisApp, height| {
+        staking
+            .query_staked_balance_at_height(app, ADDR1, Some(height))
+            .balance
+            .u128()
+    };
+    for height in first + 1..first + 5 {
+        assert_eq!(balance(&app, height), 0);
+    }
+    assert_eq!(balance(&app, first + 5), 100);
+    assert_eq!(balance(&app, second + 1), 100);
+    assert_eq!(balance(&app, second + 5), 150);
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR1, None)
+            .balance,
+        Uint128::new(150)
+    );
+
+    // warming stake is left out of the total too
+    let total = |app: &OsmosisApp, height| {
+        staking
+            .query_total_staked_at_height(app, Some(height))
+            .total
+            .u128()
+    };
+    assert_eq!(total(&app, first + 1), 0);
+    assert_eq!(total(&app, first + 5), 150);
+    assert_eq!(total(&app, second + 1), 150);
+    assert_eq!(total(&app, second + 5), 200);
+    // ended without ADDR2 touching its stake again
+    assert_eq!(
+        staking
+            .query_staked_balance_at_height(&app, ADDR2, Some(first + 5))
+            .balance,
+        Uint128::new(50)
+    );
+
+    // ADDR1's second stake settled its first deposit, ADDR2's waits for its next interaction
+    let has_deposit = |app: &OsmosisApp, address: &str| {
+        app.wrap()
+            .query_wasm_raw(
+                &staking.address,
+                WARMUP_DEPOSITS
+                    .key((&Addr::unchecked(address), first))
+                    .to_vec(),
+            )
+            .unwrap()
+            .is_some()
+    };
+    assert!(!has_deposit(&app, ADDR1));
+    assert!(has_deposit(&app, ADDR2));
+
+    let resp = staking.query_staked_balances_at_height(&app, vec![ADDR1, ADDR2], Some(first + 1));
+    assert!(resp.balances.iter().all(|b| b.balance.is_zero()));
+
+    // changing the period only affects new stake, past voting power stays as it was
+    let update_warmup = |app: &mut OsmosisApp, warmup_period| {
+        staking.update_config(
+            app,
+            &owner,
+            Some(owner.clone()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(warmup_period),
+        )
+    };
+    update_warmup(&mut app, 0).unwrap();
+    assert_eq!(balance(&app, first + 1), 0);
+    assert_eq!(balance(&app, second + 1), 100);
+    let third = app.block_info().height;
+    staking.stake(&mut app, &addr1, coin(50, DENOM)).unwrap();
+    app.update_block(next_block);
+    assert_eq!(balance(&app, third + 1), 200);
+
+    // burned shares come out of the newest warmup first
+    update_warmup(&mut app, 5).unwrap();
+    let fourth = app.block_info().height;
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    app.update_block(next_block);
+    staking
+        .unstake(&mut app, &addr1, Uint128::new(100))
+        .unwrap();
+    app.update_block(next_block);
+    assert_eq!(balance(&app, fourth + 1), 200);
+    assert_eq!(balance(&app, fourth + 2), 200);
+    assert_eq!(total(&app, fourth + 1), 250);
+    assert_eq!(total(&app, fourth + 2), 250);
+
+    let fifth = app.block_info().height;
+    staking.stake(&mut app, &addr1, coin(100, DENOM)).unwrap();
+    app.update_block(next_block);
+    staking
+        .slash(&mut app, &owner, ADDR1, Decimal::percent(50), None)
+        .unwrap();
+    for _ in 0..5 {
+        app.update_block(next_block);
+    }
+    assert_eq!(balance(&app, fifth + 1), 200);
+    assert_eq!(balance(&app, fifth + 2), 150);
+    assert_eq!(balance(&app, fifth + 6), 150);
+    assert_eq!(total(&app, fifth + 1), 250);
+    assert_eq!(total(&app, fifth + 6), 200);
+
+    let err = update_warmup(&mut app, MAX_WARMUP_PERIOD + 1).unwrap_err();
+    assert_eq!(
+        ContractError::WarmupPeriodTooLong {
+            max: MAX_WARMUP_PERIOD
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn test_collect_superfluid_rewards() {
+    // multi-test can't run distribution messages, so drive the contract entry points directly
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: MockQuerier::<OsmosisQuery>::new(&[]),
+        custom_query_type: PhantomData,
+    };
+    let env = mock_env();
+    let contract = env.contract.address.to_string();
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR_OWNER, &[]),
+        crate::msg::InstantiateMsg {
+            admin: Some(Addr::unchecked(ADDR_OWNER)),
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            basket_denoms: vec![],
+            bonus_denom: None,
+        },
+    )
+    .unwrap();
+
+    let collect = |deps: DepsMut<OsmosisQuery>| {
+        crate::contract::execute(
+            deps,
+            mock_env(),
+            mock_info(ADDR1, &[]),
+            ExecuteMsg::CollectSuperfluidRewards {},
+        )
+    };
+    assert_eq!(
+        collect(deps.as_mut()).unwrap_err(),
+        ContractError::NoDelegations {}
+    );
+
+    let delegation = |validator: &str| FullDelegation {
+        delegator: Addr::unchecked(&contract),
+        validator: validator.to_string(),
+        amount: coin(1000, "uosmo"),
+        can_redelegate: coin(0, "uosmo"),
+        accumulated_rewards: vec![],
+    };
+    deps.querier
+        .update_staking("uosmo", &[], &[delegation("val1"), delegation("val2")]);
+    deps.querier.update_balance(&contract, coins(100, DENOM));
+
+    let resp = collect(deps.as_mut()).unwrap();
+    assert_eq!(
+        resp.messages,
+        vec![
+            SubMsg::new(DistributionMsg::WithdrawDelegatorReward {
+                validator: "val1".to_string()
+            }),
+            SubMsg::reply_on_success(
+                DistributionMsg::WithdrawDelegatorReward {
+                    validator: "val2".to_string()
+                },
+                COLLECT_SUPERFLUID_REWARDS_REPLY_ID
+            ),
+        ]
+    );
+
+    // the rewards aren't boosted, so no reserve is needed for the multiplier
+    crate::contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADDR_OWNER, &[]),
+        ExecuteMsg::UpdateConfig {
+            admin: Some(Addr::unchecked(ADDR_OWNER)),
+            duration: None,
+            reward_multiplier: Some(Decimal::percent(200)),
+            fund_allowlist: None,
+            clear_fund_allowlist: false,
+            reward_vesting_blocks: None,
+            min_claim_amount: None,
+            warmup_period: None,
+            bonus_denom: None,
+        },
+    )
+    .unwrap();
+
+    // the withdrawals paid out 40 of the staking denom
+    deps.querier.update_balance(&contract, coins(140, DENOM));
+    let reply = Reply {
+        id: COLLECT_SUPERFLUID_REWARDS_REPLY_ID,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    crate::contract::reply(deps.as_mut(), env.clone(), reply).unwrap();
+
+    let total: TotalValueResponse = from_binary(
+        &crate::contract::query(deps.as_ref(), env.clone(), QueryMsg::TotalValue {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(total.total, Uint128::new(40));
+    let collected: TotalSuperfluidRewardsCollectedResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            env,
+            QueryMsg::TotalSuperfluidRewardsCollected {},
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(collected.total, Uint128::new(40));
+}